@@ -0,0 +1,45 @@
+use crate::models::TaskTree;
+use std::sync::RwLock;
+
+/// Last task tree built by `get_task_tree`, tagged with the `data_version` it was built at.
+///
+/// Triggers bump `data_version` on every insert, update or delete of a task, so a
+/// matching version means the cached tree is still current.
+#[derive(Debug, Default)]
+pub struct TreeCache {
+    state: RwLock<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entry: Option<(i64, Vec<TaskTree>)>,
+    builds: u64,
+}
+
+impl TreeCache {
+    /// The cached tree if it was built at `version`.
+    pub fn get(&self, version: i64) -> Option<Vec<TaskTree>> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        match &state.entry {
+            Some((cached_version, tree)) if *cached_version == version => Some(tree.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn store(&self, version: i64, tree: Vec<TaskTree>) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.entry = Some((version, tree));
+        state.builds += 1;
+        log::debug!(
+            "task tree cache rebuilt at version {} ({} builds)",
+            version,
+            state.builds
+        );
+    }
+
+    /// How many times a tree has been built and stored, for observing cache hits.
+    #[cfg(test)]
+    pub fn builds(&self) -> u64 {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).builds
+    }
+}
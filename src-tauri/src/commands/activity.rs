@@ -0,0 +1,52 @@
+use crate::error::AppError;
+use crate::models::TaskActivity;
+use sqlx::{Sqlite, SqlitePool};
+use tauri::State;
+
+// Append an entry to a task's activity log
+pub(crate) async fn log_activity<'e, E>(
+    executor: E,
+    task_id: i64,
+    action: &str,
+    detail: Option<&str>,
+) -> Result<(), AppError>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let now = chrono::Utc::now().timestamp();
+
+    sqlx::query(
+        "INSERT INTO task_activity (task_id, action, detail, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(task_id)
+    .bind(action)
+    .bind(detail)
+    .bind(now)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn get_task_activity_impl(
+    pool: &SqlitePool,
+    task_id: i64,
+) -> Result<Vec<TaskActivity>, AppError> {
+    // Timestamps have second resolution, so fall back to insertion order for ties
+    let entries = sqlx::query_as::<_, TaskActivity>(
+        "SELECT * FROM task_activity WHERE task_id = ? ORDER BY created_at DESC, id DESC",
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn get_task_activity(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<Vec<TaskActivity>, AppError> {
+    get_task_activity_impl(pool.inner(), id).await
+}
@@ -1,13 +1,70 @@
+use crate::commands::maintenance::normalize_group;
+use crate::commands::settings::default_category_id;
+use crate::commands::tasks::get_next_position;
+use crate::db::with_retry;
 use crate::error::AppError;
-use crate::models::{Category, CreateCategoryInput, UpdateCategoryInput};
+use crate::models::{
+    text_color_for, Category, CategoryUsage, CategoryWithCounts, CreateCategoryInput,
+    PagedCategories, UpdateCategoryInput,
+};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use tauri::State;
 
+// Soft cap on page size so a bad client can't request everything at once
+const MAX_PAGE_SIZE: i64 = 200;
+
+// Validation function for category names
+fn validate_category_name(name: &str) -> Result<(), AppError> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::ValidationError(
+            "Category name cannot be empty".to_string(),
+        ));
+    }
+    if trimmed.chars().count() > 100 {
+        return Err(AppError::ValidationError(
+            "Category name is too long (max 100 characters)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Validation function for category and task colors, which must be #RRGGBB
+pub(crate) fn validate_color(color: &str) -> Result<(), AppError> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(AppError::ValidationError(
+            "Color must be a valid hex code (e.g., #FF5733)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Foreground color (`#000000` or `#FFFFFF`) that stays readable on `color`.
 #[tauri::command]
-pub async fn create_category(
-    pool: State<'_, SqlitePool>,
-    input: CreateCategoryInput,
+pub fn suggested_text_color(color: String) -> Result<String, AppError> {
+    validate_color(&color)?;
+    let text_color = text_color_for(&color).expect("validated hex color");
+    Ok(text_color.to_string())
+}
+
+/// Pre-flight check so the frontend can show inline errors using the same rules
+/// `create_category` applies. Never touches the database.
+#[tauri::command]
+pub fn validate_category_input(input: CreateCategoryInput) -> Result<(), AppError> {
+    validate_category_name(&input.name)?;
+    validate_color(&input.color)
+}
+
+pub(crate) async fn create_category_impl(
+    pool: &SqlitePool,
+    input: &CreateCategoryInput,
 ) -> Result<Category, AppError> {
+    validate_category_name(&input.name)?;
+    validate_color(&input.color)?;
     let now = chrono::Utc::now().timestamp();
 
     let category = sqlx::query_as::<_, Category>(
@@ -17,38 +74,280 @@ pub async fn create_category(
         RETURNING *
         "#,
     )
-    .bind(&input.name)
+    .bind(input.name.trim())
     .bind(&input.color)
     .bind(now)
     .bind(now)
-    .fetch_one(pool.inner())
+    .fetch_one(pool)
     .await?;
 
     Ok(category)
 }
 
+#[tauri::command]
+pub async fn create_category(
+    pool: State<'_, SqlitePool>,
+    input: CreateCategoryInput,
+) -> Result<Category, AppError> {
+    with_retry(|| create_category_impl(pool.inner(), &input)).await
+}
+
+/// Categories that aren't archived, the ones offered when filing tasks.
+pub(crate) async fn get_all_categories_impl(pool: &SqlitePool) -> Result<Vec<Category>, AppError> {
+    let categories = sqlx::query_as::<_, Category>(
+        "SELECT * FROM categories WHERE archived_at IS NULL ORDER BY name ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(categories)
+}
+
 #[tauri::command]
 pub async fn get_all_categories(pool: State<'_, SqlitePool>) -> Result<Vec<Category>, AppError> {
-    let categories = sqlx::query_as::<_, Category>("SELECT * FROM categories ORDER BY name ASC")
-        .fetch_all(pool.inner())
-        .await?;
+    get_all_categories_impl(pool.inner()).await
+}
+
+/// Archived categories, most recently archived first.
+pub(crate) async fn get_archived_categories_impl(
+    pool: &SqlitePool,
+) -> Result<Vec<Category>, AppError> {
+    let categories = sqlx::query_as::<_, Category>(
+        "SELECT * FROM categories WHERE archived_at IS NOT NULL ORDER BY archived_at DESC, name ASC",
+    )
+    .fetch_all(pool)
+    .await?;
 
     Ok(categories)
 }
 
 #[tauri::command]
-pub async fn update_category(
+pub async fn get_archived_categories(
+    pool: State<'_, SqlitePool>,
+) -> Result<Vec<Category>, AppError> {
+    get_archived_categories_impl(pool.inner()).await
+}
+
+/// Color of every category by id. Tasks filed under archived categories still need
+/// coloring; pass `include_archived` to get those too.
+pub(crate) async fn get_category_colors_impl(
+    pool: &SqlitePool,
+    include_archived: bool,
+) -> Result<HashMap<i64, String>, AppError> {
+    let colors: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, color FROM categories WHERE ? OR archived_at IS NULL")
+            .bind(include_archived)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(colors.into_iter().collect())
+}
+
+#[tauri::command]
+pub async fn get_category_colors(
+    pool: State<'_, SqlitePool>,
+    include_archived: Option<bool>,
+) -> Result<HashMap<i64, String>, AppError> {
+    get_category_colors_impl(pool.inner(), include_archived.unwrap_or(false)).await
+}
+
+/// Archives or restores a category. Its existing tasks are left where they are.
+pub(crate) async fn set_category_archived_impl(
+    pool: &SqlitePool,
+    id: i64,
+    archived: bool,
+) -> Result<Category, AppError> {
+    let now = chrono::Utc::now().timestamp();
+
+    // Re-archiving keeps the original archive time
+    let category = sqlx::query_as::<_, Category>(
+        r#"
+        UPDATE categories
+        SET archived_at = CASE WHEN ?1 THEN COALESCE(archived_at, ?2) ELSE NULL END,
+            updated_at = ?2
+        WHERE id = ?3
+        RETURNING *
+        "#,
+    )
+    .bind(archived)
+    .bind(now)
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Category {} not found", id)))?;
+
+    Ok(category)
+}
+
+#[tauri::command]
+pub async fn set_category_archived(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    archived: bool,
+) -> Result<Category, AppError> {
+    with_retry(|| set_category_archived_impl(pool.inner(), id, archived)).await
+}
+
+/// One page of categories by name; archived ones only with `include_archived`.
+pub(crate) async fn get_categories_paged_impl(
+    pool: &SqlitePool,
+    limit: i64,
+    offset: i64,
+    include_archived: bool,
+) -> Result<PagedCategories, AppError> {
+    if limit <= 0 || offset < 0 {
+        return Err(AppError::ValidationError(
+            "Limit must be positive and offset non-negative".to_string(),
+        ));
+    }
+    let limit = limit.min(MAX_PAGE_SIZE);
+
+    let items = sqlx::query_as::<_, Category>(
+        r#"
+        SELECT * FROM categories
+        WHERE ? OR archived_at IS NULL
+        ORDER BY name ASC, id ASC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(include_archived)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    let (total,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM categories WHERE ? OR archived_at IS NULL")
+            .bind(include_archived)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(PagedCategories { items, total })
+}
+
+#[tauri::command]
+pub async fn get_categories_paged(
+    pool: State<'_, SqlitePool>,
+    limit: i64,
+    offset: i64,
+    include_archived: Option<bool>,
+) -> Result<PagedCategories, AppError> {
+    get_categories_paged_impl(
+        pool.inner(),
+        limit,
+        offset,
+        include_archived.unwrap_or(false),
+    )
+    .await
+}
+
+/// Categories with their task counts; archived ones only with `include_archived`.
+pub(crate) async fn get_categories_with_counts_impl(
+    pool: &SqlitePool,
+    include_archived: bool,
+) -> Result<Vec<CategoryWithCounts>, AppError> {
+    // LEFT JOIN keeps categories without tasks, reported with zero counts
+    let categories = sqlx::query_as::<_, CategoryWithCounts>(
+        r#"
+        SELECT c.*, COUNT(t.id) AS total, COALESCE(SUM(t.is_done), 0) AS done
+        FROM categories c
+        LEFT JOIN tasks t ON t.category_id = c.id
+        WHERE ? OR c.archived_at IS NULL
+        GROUP BY c.id
+        ORDER BY c.name ASC
+        "#,
+    )
+    .bind(include_archived)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(categories)
+}
+
+#[tauri::command]
+pub async fn get_categories_with_counts(
+    pool: State<'_, SqlitePool>,
+    include_archived: Option<bool>,
+) -> Result<Vec<CategoryWithCounts>, AppError> {
+    get_categories_with_counts_impl(pool.inner(), include_archived.unwrap_or(false)).await
+}
+
+/// How many tasks are filed under category `id`, so the UI can warn before deleting it.
+pub(crate) async fn get_category_usage_impl(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<CategoryUsage, AppError> {
+    let usage = sqlx::query_as::<_, CategoryUsage>(
+        r#"
+        SELECT
+            COUNT(t.id) AS total_tasks,
+            COALESCE(SUM(t.is_done = 0), 0) AS open_tasks,
+            COALESCE(SUM(t.is_done), 0) AS done_tasks,
+            (
+                SELECT COUNT(*) FROM tasks d
+                WHERE EXISTS (
+                    SELECT 1 FROM tasks r
+                    WHERE r.category_id = c.id
+                    AND substr(d.path, 1, length(r.path)) = r.path
+                )
+            ) AS subtree_tasks
+        FROM categories c
+        LEFT JOIN tasks t ON t.category_id = c.id
+        WHERE c.id = ?
+        GROUP BY c.id
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Category {} not found", id)))?;
+
+    Ok(usage)
+}
+
+#[tauri::command]
+pub async fn get_category_usage(
     pool: State<'_, SqlitePool>,
     id: i64,
+) -> Result<CategoryUsage, AppError> {
+    get_category_usage_impl(pool.inner(), id).await
+}
+
+pub(crate) async fn update_category_impl(
+    pool: &SqlitePool,
+    id: i64,
     input: UpdateCategoryInput,
 ) -> Result<Category, AppError> {
+    // Every check runs before anything is written, so a bad color can't leave a
+    // half-applied rename behind
+    if let Some(ref name) = input.name {
+        validate_category_name(name)?;
+    }
+    if let Some(ref color) = input.color {
+        validate_color(color)?;
+    }
+    let name = input.name.as_deref().map(str::trim);
     let now = chrono::Utc::now().timestamp();
 
+    let mut tx = pool.begin().await?;
+
+    if let Some(name) = name {
+        let conflict: Option<(i64,)> =
+            sqlx::query_as("SELECT id FROM categories WHERE name = ? AND id != ?")
+                .bind(name)
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if conflict.is_some() {
+            return Err(AppError::ValidationError("name already in use".to_string()));
+        }
+    }
+
     // Use QueryBuilder for safe dynamic query construction
     let mut builder = sqlx::QueryBuilder::new("UPDATE categories SET updated_at = ");
     builder.push_bind(now);
 
-    if let Some(name) = input.name {
+    if let Some(name) = name {
         builder.push(", name = ");
         builder.push_bind(name);
     }
@@ -63,18 +362,183 @@ pub async fn update_category(
 
     let category = builder
         .build_query_as::<Category>()
-        .fetch_one(pool.inner())
-        .await?;
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Category {} not found", id)))?;
+
+    tx.commit().await?;
 
     Ok(category)
 }
 
 #[tauri::command]
-pub async fn delete_category(pool: State<'_, SqlitePool>, id: i64) -> Result<(), AppError> {
-    sqlx::query("DELETE FROM categories WHERE id = ?")
+pub async fn update_category(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    input: UpdateCategoryInput,
+) -> Result<Category, AppError> {
+    with_retry(|| update_category_impl(pool.inner(), id, input.clone())).await
+}
+
+/// Renames a category; `update_category_impl` with only the name set.
+pub(crate) async fn rename_category_impl(
+    pool: &SqlitePool,
+    id: i64,
+    new_name: &str,
+) -> Result<Category, AppError> {
+    let input = UpdateCategoryInput {
+        name: Some(new_name.to_string()),
+        color: None,
+    };
+    update_category_impl(pool, id, input).await
+}
+
+#[tauri::command]
+pub async fn rename_category(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    new_name: String,
+) -> Result<Category, AppError> {
+    with_retry(|| rename_category_impl(pool.inner(), id, &new_name)).await
+}
+
+/// Deletes a category. Its tasks move to `reassign_to` when given, appended after the
+/// target's existing tasks; otherwise the foreign key leaves them uncategorized.
+pub(crate) async fn delete_category_impl(
+    pool: &SqlitePool,
+    id: i64,
+    reassign_to: Option<i64>,
+) -> Result<(), AppError> {
+    // Reassigning and deleting commit together, so a failure can't strand tasks
+    let mut tx = pool.begin().await?;
+
+    if let Some(target_id) = reassign_to {
+        if target_id == id {
+            return Err(AppError::ValidationError(
+                "Cannot reassign tasks to the category being deleted".to_string(),
+            ));
+        }
+        // Archived categories can't take new tasks, reassigned ones included
+        let target: Option<(Option<i64>,)> =
+            sqlx::query_as("SELECT archived_at FROM categories WHERE id = ?")
+                .bind(target_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        match target {
+            None => {
+                return Err(AppError::ValidationError(format!(
+                    "Category {} does not exist",
+                    target_id
+                )));
+            }
+            Some((Some(_),)) => {
+                return Err(AppError::ValidationError(format!(
+                    "Category {} is archived",
+                    target_id
+                )));
+            }
+            Some((None,)) => {}
+        }
+
+        let tasks: Vec<(i64, Option<i64>)> = sqlx::query_as(
+            "SELECT id, parent_id FROM tasks WHERE category_id = ? ORDER BY parent_id, position ASC, id ASC",
+        )
         .bind(id)
-        .execute(pool.inner())
+        .fetch_all(&mut *tx)
         .await?;
 
+        let now = chrono::Utc::now().timestamp();
+        for (task_id, parent_id) in tasks {
+            let position = get_next_position(&mut tx, parent_id, Some(target_id)).await?;
+            sqlx::query(
+                "UPDATE tasks SET category_id = ?, position = ?, updated_at = ? WHERE id = ?",
+            )
+            .bind(target_id)
+            .bind(position)
+            .bind(now)
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    // Without a target the foreign key clears the category, merging these tasks into the
+    // uncategorized groups of their parents
+    let parents: Vec<(Option<i64>,)> =
+        sqlx::query_as("SELECT DISTINCT parent_id FROM tasks WHERE category_id = ?")
+            .bind(id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+    let result = sqlx::query("DELETE FROM categories WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("category {id} not found")));
+    }
+
+    // Renumber those groups so merged positions don't collide
+    for (parent_id,) in parents {
+        normalize_group(&mut tx, parent_id, None).await?;
+    }
+
+    tx.commit().await?;
+
     Ok(())
 }
+
+#[tauri::command]
+pub async fn delete_category(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    reassign_to: Option<i64>,
+) -> Result<(), AppError> {
+    with_retry(|| delete_category_impl(pool.inner(), id, reassign_to)).await
+}
+
+/// Deletes every category without tasks, except the ids in `protect`, the configured
+/// default category and the seeded defaults. Returns the removed names in alphabetical
+/// order.
+pub(crate) async fn prune_empty_categories_impl(
+    pool: &SqlitePool,
+    protect: &[i64],
+) -> Result<Vec<String>, AppError> {
+    let mut tx = pool.begin().await?;
+    let default_id = default_category_id(&mut *tx).await?;
+
+    let empty: Vec<(i64, String)> = sqlx::query_as(
+        r#"
+        SELECT c.id, c.name FROM categories c
+        WHERE NOT EXISTS (SELECT 1 FROM tasks t WHERE t.category_id = c.id)
+        AND c.name NOT IN (SELECT name FROM seeded_categories)
+        ORDER BY c.name ASC
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut removed = Vec::new();
+    for (id, name) in empty {
+        if protect.contains(&id) || default_id == Some(id) {
+            continue;
+        }
+        sqlx::query("DELETE FROM categories WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        removed.push(name);
+    }
+
+    tx.commit().await?;
+
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn prune_empty_categories(
+    pool: State<'_, SqlitePool>,
+    protect: Vec<i64>,
+) -> Result<Vec<String>, AppError> {
+    with_retry(|| prune_empty_categories_impl(pool.inner(), &protect)).await
+}
@@ -0,0 +1,214 @@
+use crate::commands::activity::log_activity;
+use crate::commands::categories::validate_category_input;
+use crate::commands::tasks::{get_next_position, validate_task_input};
+use crate::db::with_retry;
+use crate::error::AppError;
+use crate::models::{
+    Category, CreateCategoryInput, CreateTaskInput, ExportBundle, ImportSummary, Task,
+    EXPORT_FORMAT_VERSION,
+};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+/// Bundles category `category_id` with its tasks for sharing.
+///
+/// Tasks whose parent sits in another category are exported as roots, so the bundle
+/// never points at a task it doesn't contain.
+pub(crate) async fn export_category_impl(
+    pool: &SqlitePool,
+    category_id: i64,
+) -> Result<ExportBundle, AppError> {
+    let category = sqlx::query_as::<_, Category>("SELECT * FROM categories WHERE id = ?")
+        .bind(category_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Category {} not found", category_id)))?;
+
+    // A path sorts after every prefix of itself, so parents come first
+    let mut tasks =
+        sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE category_id = ? ORDER BY path, id")
+            .bind(category_id)
+            .fetch_all(pool)
+            .await?;
+
+    let ids: HashSet<i64> = tasks.iter().map(|task| task.id).collect();
+    for task in &mut tasks {
+        if task
+            .parent_id
+            .is_some_and(|parent_id| !ids.contains(&parent_id))
+        {
+            task.parent_id = None;
+        }
+    }
+
+    Ok(ExportBundle {
+        version: EXPORT_FORMAT_VERSION,
+        exported_at: chrono::Utc::now().timestamp(),
+        categories: vec![category],
+        tasks,
+    })
+}
+
+#[tauri::command]
+pub async fn export_category(
+    pool: State<'_, SqlitePool>,
+    category_id: i64,
+) -> Result<ExportBundle, AppError> {
+    export_category_impl(pool.inner(), category_id).await
+}
+
+/// Loads an [`ExportBundle`] in one transaction, giving every row a fresh id.
+///
+/// Categories are matched to existing ones by name and only created when missing.
+/// Tasks keep their tree shape and sibling order; they are appended after any
+/// tasks already in the same place. External ids and series links aren't carried
+/// over.
+pub(crate) async fn import_data_impl(
+    pool: &SqlitePool,
+    bundle: ExportBundle,
+) -> Result<ImportSummary, AppError> {
+    if bundle.version != EXPORT_FORMAT_VERSION {
+        return Err(AppError::ValidationError(format!(
+            "Unsupported export version {} (expected {})",
+            bundle.version, EXPORT_FORMAT_VERSION
+        )));
+    }
+    for category in &bundle.categories {
+        validate_category_input(CreateCategoryInput {
+            name: category.name.clone(),
+            color: category.color.clone(),
+        })?;
+    }
+    for task in &bundle.tasks {
+        validate_task_input(CreateTaskInput {
+            title: task.title.clone(),
+            description: None,
+            category_id: None,
+            priority: Some(task.priority.to_string()),
+            parent_id: None,
+            due_date: None,
+            effort: task.effort,
+            reminder_offset_minutes: task.reminder_offset_minutes,
+            color: task.color.clone(),
+        })?;
+    }
+
+    let mut tx = pool.begin().await?;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut categories_created = 0;
+    let mut category_ids = HashMap::new();
+    for category in &bundle.categories {
+        let name = category.name.trim();
+        let existing: Option<(i64, Option<i64>)> =
+            sqlx::query_as("SELECT id, archived_at FROM categories WHERE name = ?")
+                .bind(name)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let id = match existing {
+            // Archived categories can't take the imported tasks
+            Some((_, Some(_))) => {
+                return Err(AppError::ValidationError(format!(
+                    "Category '{}' is archived",
+                    name
+                )));
+            }
+            Some((id, None)) => id,
+            None => {
+                categories_created += 1;
+                sqlx::query_scalar::<_, i64>(
+                    r#"
+                    INSERT INTO categories (name, color, created_at, updated_at)
+                    VALUES (?, ?, ?, ?)
+                    RETURNING id
+                    "#,
+                )
+                .bind(name)
+                .bind(&category.color)
+                .bind(category.created_at)
+                .bind(now)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        };
+        category_ids.insert(category.id, id);
+    }
+
+    // Siblings become insertable in the same pass, so visiting them by position
+    // keeps their order
+    let exported: HashSet<i64> = bundle.tasks.iter().map(|task| task.id).collect();
+    let mut pending: Vec<&Task> = bundle.tasks.iter().collect();
+    pending.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+    let mut task_ids = HashMap::new();
+    while !pending.is_empty() {
+        let mut waiting = Vec::new();
+        for task in &pending {
+            let parent_id = match task.parent_id.filter(|id| exported.contains(id)) {
+                None => None,
+                Some(old_parent) => match task_ids.get(&old_parent) {
+                    Some(&new_parent) => Some(new_parent),
+                    None => {
+                        waiting.push(*task);
+                        continue;
+                    }
+                },
+            };
+            let category_id = task
+                .category_id
+                .and_then(|old| category_ids.get(&old).copied());
+            let position = get_next_position(&mut tx, parent_id, category_id).await?;
+
+            let id = sqlx::query_scalar::<_, i64>(
+                r#"
+                INSERT INTO tasks (title, description, category_id, priority, parent_id, position, is_done, due_date, completed_at, effort, reminder_offset_minutes, color, is_locked, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                RETURNING id
+                "#,
+            )
+            .bind(task.title.trim())
+            .bind(&task.description)
+            .bind(category_id)
+            .bind(task.priority)
+            .bind(parent_id)
+            .bind(position)
+            .bind(task.is_done)
+            .bind(task.due_date)
+            .bind(task.completed_at)
+            .bind(task.effort)
+            .bind(task.reminder_offset_minutes)
+            .bind(&task.color)
+            .bind(task.is_locked)
+            .bind(task.created_at)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+            log_activity(&mut *tx, id, "created", Some("import")).await?;
+            task_ids.insert(task.id, id);
+        }
+
+        // Nothing placed this pass means the remaining parents form a cycle
+        if waiting.len() == pending.len() {
+            return Err(AppError::ValidationError(
+                "Export contains a parent cycle".to_string(),
+            ));
+        }
+        pending = waiting;
+    }
+
+    tx.commit().await?;
+
+    Ok(ImportSummary {
+        categories_created,
+        tasks_created: task_ids.len() as u64,
+    })
+}
+
+#[tauri::command]
+pub async fn import_data(
+    pool: State<'_, SqlitePool>,
+    bundle: ExportBundle,
+) -> Result<ImportSummary, AppError> {
+    with_retry(|| import_data_impl(pool.inner(), bundle.clone())).await
+}
@@ -0,0 +1,258 @@
+use crate::commands::activity::log_activity;
+use crate::commands::tasks::get_next_position;
+use crate::db::with_retry;
+use crate::error::AppError;
+use crate::models::{ForeignKeyViolation, StorageInfo, TableRowCount, Task};
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::BTreeSet;
+use tauri::State;
+
+// Rewrite one sibling group's positions to 0..n, keeping the current order (ties broken by id)
+pub(crate) async fn normalize_group(
+    conn: &mut SqliteConnection,
+    parent_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE tasks
+        SET position = ranked.new_position
+        FROM (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY position ASC, id ASC) - 1 AS new_position
+            FROM tasks
+            WHERE parent_id IS ? AND category_id IS ?
+        ) AS ranked
+        WHERE tasks.id = ranked.id
+        AND tasks.position != ranked.new_position
+        "#,
+    )
+    .bind(parent_id)
+    .bind(category_id)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub(crate) async fn normalize_positions_impl(
+    pool: &SqlitePool,
+    parent_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<u64, AppError> {
+    let mut tx = pool.begin().await?;
+    let changed = normalize_group(&mut tx, parent_id, category_id).await?;
+    tx.commit().await?;
+
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn normalize_positions(
+    pool: State<'_, SqlitePool>,
+    parent_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<u64, AppError> {
+    with_retry(|| normalize_positions_impl(pool.inner(), parent_id, category_id)).await
+}
+
+pub(crate) async fn normalize_all_positions_impl(pool: &SqlitePool) -> Result<u64, AppError> {
+    let mut tx = pool.begin().await?;
+
+    // PARTITION BY treats NULLs as equal, matching the `IS ?` grouping used elsewhere
+    let result = sqlx::query(
+        r#"
+        UPDATE tasks
+        SET position = ranked.new_position
+        FROM (
+            SELECT
+                id,
+                ROW_NUMBER() OVER (
+                    PARTITION BY parent_id, category_id
+                    ORDER BY position ASC, id ASC
+                ) - 1 AS new_position
+            FROM tasks
+        ) AS ranked
+        WHERE tasks.id = ranked.id
+        AND tasks.position != ranked.new_position
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+#[tauri::command]
+pub async fn normalize_all_positions(pool: State<'_, SqlitePool>) -> Result<u64, AppError> {
+    with_retry(|| normalize_all_positions_impl(pool.inner())).await
+}
+
+// Tasks whose parent_id points at a row that no longer exists
+const ORPHANS_QUERY: &str = r#"
+    SELECT t.* FROM tasks t
+    WHERE t.parent_id IS NOT NULL
+    AND NOT EXISTS (SELECT 1 FROM tasks p WHERE p.id = t.parent_id)
+    ORDER BY t.id ASC
+"#;
+
+pub(crate) async fn find_orphaned_tasks_impl(pool: &SqlitePool) -> Result<Vec<Task>, AppError> {
+    let orphans = sqlx::query_as::<_, Task>(ORPHANS_QUERY)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(orphans)
+}
+
+#[tauri::command]
+pub async fn find_orphaned_tasks(pool: State<'_, SqlitePool>) -> Result<Vec<Task>, AppError> {
+    find_orphaned_tasks_impl(pool.inner()).await
+}
+
+// Promote orphaned tasks to roots, appended to the end of their category
+async fn promote_orphans(conn: &mut SqliteConnection) -> Result<u64, AppError> {
+    let orphans = sqlx::query_as::<_, Task>(ORPHANS_QUERY)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let now = chrono::Utc::now().timestamp();
+    for orphan in &orphans {
+        let position = get_next_position(&mut *conn, None, orphan.category_id).await?;
+        sqlx::query("UPDATE tasks SET parent_id = NULL, position = ?, updated_at = ? WHERE id = ?")
+            .bind(position)
+            .bind(now)
+            .bind(orphan.id)
+            .execute(&mut *conn)
+            .await?;
+        log_activity(&mut *conn, orphan.id, "moved", Some("reattached orphan")).await?;
+    }
+
+    Ok(orphans.len() as u64)
+}
+
+/// Promotes orphaned tasks to roots, appended to the end of their category.
+pub(crate) async fn reattach_orphans_impl(pool: &SqlitePool) -> Result<u64, AppError> {
+    let mut tx = pool.begin().await?;
+    let promoted = promote_orphans(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(promoted)
+}
+
+#[tauri::command]
+pub async fn reattach_orphans(pool: State<'_, SqlitePool>) -> Result<u64, AppError> {
+    with_retry(|| reattach_orphans_impl(pool.inner())).await
+}
+
+pub(crate) async fn check_foreign_keys_impl(
+    pool: &SqlitePool,
+) -> Result<Vec<ForeignKeyViolation>, AppError> {
+    // Rows written while enforcement was off (e.g. by an import) are only caught here
+    let violations = sqlx::query_as::<_, ForeignKeyViolation>("PRAGMA foreign_key_check")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(violations)
+}
+
+#[tauri::command]
+pub async fn check_foreign_keys(
+    pool: State<'_, SqlitePool>,
+) -> Result<Vec<ForeignKeyViolation>, AppError> {
+    check_foreign_keys_impl(pool.inner()).await
+}
+
+/// Clears dangling category and series references and promotes tasks whose parent is
+/// missing to roots. Returns the number of tasks changed.
+pub(crate) async fn repair_foreign_keys_impl(pool: &SqlitePool) -> Result<u64, AppError> {
+    let mut tx = pool.begin().await?;
+    let now = chrono::Utc::now().timestamp();
+
+    // Categories first, so promoted orphans land in the right root group
+    let uncategorized: Vec<(Option<i64>,)> = sqlx::query_as(
+        r#"
+        UPDATE tasks SET category_id = NULL, updated_at = ?
+        WHERE category_id IS NOT NULL
+        AND category_id NOT IN (SELECT id FROM categories)
+        RETURNING parent_id
+        "#,
+    )
+    .bind(now)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // Those tasks joined the uncategorized groups, so renumber them to keep positions distinct
+    let parents: BTreeSet<Option<i64>> = uncategorized.iter().map(|(p,)| *p).collect();
+    for parent_id in parents {
+        normalize_group(&mut tx, parent_id, None).await?;
+    }
+
+    let series = sqlx::query(
+        r#"
+        UPDATE tasks SET series_id = NULL, updated_at = ?
+        WHERE series_id IS NOT NULL
+        AND series_id NOT IN (SELECT id FROM task_series)
+        "#,
+    )
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    let promoted = promote_orphans(&mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(uncategorized.len() as u64 + series.rows_affected() + promoted)
+}
+
+#[tauri::command]
+pub async fn repair_foreign_keys(pool: State<'_, SqlitePool>) -> Result<u64, AppError> {
+    with_retry(|| repair_foreign_keys_impl(pool.inner())).await
+}
+
+pub(crate) async fn get_storage_info_impl(pool: &SqlitePool) -> Result<StorageInfo, AppError> {
+    // One connection so every pragma describes the same database
+    let mut conn = pool.acquire().await?;
+
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+        .fetch_one(&mut *conn)
+        .await?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+        .fetch_one(&mut *conn)
+        .await?;
+
+    // The file name is empty for in-memory databases, which have no WAL
+    let file: Option<String> =
+        sqlx::query_scalar("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .fetch_optional(&mut *conn)
+            .await?;
+    let wal_bytes = file
+        .filter(|file| !file.is_empty())
+        .and_then(|file| std::fs::metadata(format!("{}-wal", file)).ok())
+        .map_or(0, |metadata| metadata.len() as i64);
+
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut tables = Vec::with_capacity(names.len());
+    for table in names {
+        let sql = format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\""));
+        let rows: i64 = sqlx::query_scalar(&sql).fetch_one(&mut *conn).await?;
+        tables.push(TableRowCount { table, rows });
+    }
+
+    Ok(StorageInfo {
+        db_bytes: page_count * page_size,
+        wal_bytes,
+        tables,
+    })
+}
+
+#[tauri::command]
+pub async fn get_storage_info(pool: State<'_, SqlitePool>) -> Result<StorageInfo, AppError> {
+    get_storage_info_impl(pool.inner()).await
+}
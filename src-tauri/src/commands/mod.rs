@@ -1,5 +1,14 @@
+pub mod activity;
 pub mod categories;
+pub mod export;
+pub mod maintenance;
+pub mod recurrence;
+pub mod settings;
+pub mod stats;
+pub mod sync;
 pub mod tasks;
+pub mod templates;
+pub mod trash;
 
 #[cfg(test)]
 mod tests;
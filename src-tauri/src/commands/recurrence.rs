@@ -0,0 +1,153 @@
+use crate::commands::tasks::insert_task;
+use crate::db::with_retry;
+use crate::error::AppError;
+use crate::models::{CreateTaskInput, RecurrenceRule, Task};
+use sqlx::SqlitePool;
+use tauri::State;
+
+// Upper bound on instances generated up front for a single series
+const MAX_SERIES_LENGTH: usize = 366;
+
+fn parse_rule(rule: &str) -> Result<RecurrenceRule, AppError> {
+    rule.parse::<RecurrenceRule>()
+        .map_err(AppError::ValidationError)
+}
+
+/// Checks a rule before it is saved, using the same parser as the series commands.
+#[tauri::command]
+pub fn validate_recurrence(rule: String) -> Result<(), AppError> {
+    parse_rule(&rule).map(|_| ())
+}
+
+pub(crate) async fn create_task_series_impl(
+    pool: &SqlitePool,
+    input: CreateTaskInput,
+    rule: &str,
+    count: usize,
+) -> Result<Vec<Task>, AppError> {
+    let rule = parse_rule(rule)?;
+    if count == 0 || count > MAX_SERIES_LENGTH {
+        return Err(AppError::ValidationError(format!(
+            "Series length must be between 1 and {}",
+            MAX_SERIES_LENGTH
+        )));
+    }
+    let start = input
+        .due_date
+        .ok_or_else(|| AppError::ValidationError("Recurring tasks need a due date".to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let now = chrono::Utc::now().timestamp();
+
+    let (series_id,): (i64,) =
+        sqlx::query_as("INSERT INTO task_series (rule, created_at) VALUES (?, ?) RETURNING id")
+            .bind(rule.to_string())
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    let mut tasks = Vec::with_capacity(count);
+    for due_date in rule.occurrences(start, count) {
+        let instance = CreateTaskInput {
+            due_date: Some(due_date),
+            ..input.clone()
+        };
+        let task = insert_task(&mut tx, &instance).await?;
+
+        let task =
+            sqlx::query_as::<_, Task>("UPDATE tasks SET series_id = ? WHERE id = ? RETURNING *")
+                .bind(series_id)
+                .bind(task.id)
+                .fetch_one(&mut *tx)
+                .await?;
+        tasks.push(task);
+    }
+
+    tx.commit().await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn create_task_series(
+    pool: State<'_, SqlitePool>,
+    input: CreateTaskInput,
+    rule: String,
+    count: usize,
+) -> Result<Vec<Task>, AppError> {
+    with_retry(|| create_task_series_impl(pool.inner(), input.clone(), &rule, count)).await
+}
+
+/// Re-spaces the not-yet-done instances of a series under `new_rule`.
+///
+/// The first upcoming instance keeps its due date unless `starting_at` moves it;
+/// the rest follow from the new rule. Completed instances are left untouched.
+pub(crate) async fn reschedule_series_impl(
+    pool: &SqlitePool,
+    series_id: i64,
+    new_rule: &str,
+    starting_at: Option<i64>,
+) -> Result<Vec<Task>, AppError> {
+    let rule = parse_rule(new_rule)?;
+
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query("UPDATE task_series SET rule = ? WHERE id = ?")
+        .bind(rule.to_string())
+        .bind(series_id)
+        .execute(&mut *tx)
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!(
+            "Series {} not found",
+            series_id
+        )));
+    }
+
+    let upcoming = sqlx::query_as::<_, (i64, Option<i64>)>(
+        r#"
+        SELECT id, due_date FROM tasks
+        WHERE series_id = ? AND is_done = 0
+        ORDER BY due_date IS NULL, due_date, id
+        "#,
+    )
+    .bind(series_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let start = match starting_at.or_else(|| upcoming.first().and_then(|(_, due)| *due)) {
+        Some(start) => start,
+        None => {
+            tx.commit().await?;
+            return Ok(Vec::new());
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let mut tasks = Vec::with_capacity(upcoming.len());
+    for ((id, _), due_date) in upcoming.iter().zip(rule.occurrences(start, upcoming.len())) {
+        let task = sqlx::query_as::<_, Task>(
+            "UPDATE tasks SET due_date = ?, updated_at = ? WHERE id = ? RETURNING *",
+        )
+        .bind(due_date)
+        .bind(now)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tasks.push(task);
+    }
+
+    tx.commit().await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn reschedule_series(
+    pool: State<'_, SqlitePool>,
+    series_id: i64,
+    new_rule: String,
+    starting_at: Option<i64>,
+) -> Result<Vec<Task>, AppError> {
+    with_retry(|| reschedule_series_impl(pool.inner(), series_id, &new_rule, starting_at)).await
+}
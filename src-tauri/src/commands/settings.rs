@@ -0,0 +1,123 @@
+use crate::db::with_retry;
+use crate::error::AppError;
+use sqlx::{Sqlite, SqliteConnection, SqlitePool};
+use tauri::State;
+
+/// Category new tasks land in when none is given.
+pub const DEFAULT_CATEGORY_KEY: &str = "default_category_id";
+
+// Validation function for setting values with a known meaning
+async fn validate_setting(
+    conn: &mut SqliteConnection,
+    key: &str,
+    value: &str,
+) -> Result<(), AppError> {
+    if key.trim().is_empty() {
+        return Err(AppError::ValidationError(
+            "Setting key cannot be empty".to_string(),
+        ));
+    }
+
+    if key == DEFAULT_CATEGORY_KEY {
+        let category_id = value
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| AppError::ValidationError(format!("Invalid category id '{}'", value)))?;
+        let (exists,): (bool,) =
+            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM categories WHERE id = ?)")
+                .bind(category_id)
+                .fetch_one(&mut *conn)
+                .await?;
+        if !exists {
+            return Err(AppError::ValidationError(format!(
+                "Category {} does not exist",
+                category_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_setting_impl(
+    pool: &SqlitePool,
+    key: &str,
+) -> Result<Option<String>, AppError> {
+    let value: Option<(String,)> = sqlx::query_as("SELECT value FROM app_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(value.map(|(value,)| value))
+}
+
+#[tauri::command]
+pub async fn get_setting(
+    pool: State<'_, SqlitePool>,
+    key: String,
+) -> Result<Option<String>, AppError> {
+    get_setting_impl(pool.inner(), &key).await
+}
+
+/// Stores a setting, or removes it when `value` is None.
+pub(crate) async fn set_setting_impl(
+    pool: &SqlitePool,
+    key: &str,
+    value: Option<&str>,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    match value {
+        Some(value) => {
+            validate_setting(&mut tx, key, value).await?;
+            sqlx::query(
+                r#"
+                INSERT INTO app_settings (key, value) VALUES (?, ?)
+                ON CONFLICT(key) DO UPDATE SET value = excluded.value
+                "#,
+            )
+            .bind(key)
+            .bind(value.trim())
+            .execute(&mut *tx)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM app_settings WHERE key = ?")
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_setting(
+    pool: State<'_, SqlitePool>,
+    key: String,
+    value: Option<String>,
+) -> Result<(), AppError> {
+    with_retry(|| set_setting_impl(pool.inner(), &key, value.as_deref())).await
+}
+
+// The configured default category, ignored if it has since been deleted or archived
+pub(crate) async fn default_category_id<'e, E>(executor: E) -> Result<Option<i64>, AppError>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let category: Option<(i64,)> = sqlx::query_as(
+        r#"
+        SELECT c.id FROM app_settings s
+        JOIN categories c ON c.id = CAST(s.value AS INTEGER)
+        WHERE s.key = ? AND c.archived_at IS NULL
+        "#,
+    )
+    .bind(DEFAULT_CATEGORY_KEY)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(category.map(|(id,)| id))
+}
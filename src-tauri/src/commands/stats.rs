@@ -0,0 +1,349 @@
+use crate::error::AppError;
+use crate::models::{
+    CycleTimeStats, DueBuckets, EffortSummary, PriorityBucket, TaskStats, TreeMetrics,
+};
+use sqlx::SqlitePool;
+use tauri::State;
+
+// Validation function for [from, to] time windows
+fn validate_range(from: i64, to: i64) -> Result<(), AppError> {
+    if from > to {
+        return Err(AppError::ValidationError(
+            "Range start must not be after its end".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Validates a UTC offset in minutes east and converts it to seconds
+fn tz_offset_seconds(tz_offset_minutes: i32) -> Result<i64, AppError> {
+    if tz_offset_minutes.abs() >= 24 * 60 {
+        return Err(AppError::ValidationError(format!(
+            "Invalid timezone offset {}",
+            tz_offset_minutes
+        )));
+    }
+    Ok(i64::from(tz_offset_minutes) * 60)
+}
+
+pub(crate) async fn get_priority_distribution_impl(
+    pool: &SqlitePool,
+    from: i64,
+    to: i64,
+) -> Result<Vec<PriorityBucket>, AppError> {
+    validate_range(from, to)?;
+
+    let buckets = sqlx::query_as::<_, PriorityBucket>(
+        r#"
+        SELECT priority, COUNT(*) AS count
+        FROM tasks
+        WHERE created_at BETWEEN ? AND ?
+        GROUP BY priority
+        ORDER BY priority
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(buckets)
+}
+
+#[tauri::command]
+pub async fn get_priority_distribution(
+    pool: State<'_, SqlitePool>,
+    from: i64,
+    to: i64,
+) -> Result<Vec<PriorityBucket>, AppError> {
+    get_priority_distribution_impl(pool.inner(), from, to).await
+}
+
+pub(crate) async fn get_effort_summary_impl(
+    pool: &SqlitePool,
+    category_id: Option<i64>,
+) -> Result<EffortSummary, AppError> {
+    // Without a category the summary covers every task
+    let summary = sqlx::query_as::<_, EffortSummary>(
+        r#"
+        SELECT
+            COALESCE(SUM(effort), 0) AS total,
+            COALESCE(SUM(CASE WHEN is_done THEN effort END), 0) AS completed
+        FROM tasks
+        WHERE ?1 IS NULL OR category_id = ?1
+        "#,
+    )
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn get_effort_summary(
+    pool: State<'_, SqlitePool>,
+    category_id: Option<i64>,
+) -> Result<EffortSummary, AppError> {
+    get_effort_summary_impl(pool.inner(), category_id).await
+}
+
+pub(crate) async fn get_task_stats_impl(pool: &SqlitePool) -> Result<TaskStats, AppError> {
+    // COUNT(DISTINCT) skips NULLs, so one-off completions are counted separately
+    let stats = sqlx::query_as::<_, TaskStats>(
+        r#"
+        SELECT
+            COUNT(*) AS total,
+            COALESCE(SUM(is_done), 0) AS total_completions,
+            COUNT(CASE WHEN is_done AND series_id IS NULL THEN 1 END)
+                + COUNT(DISTINCT CASE WHEN is_done THEN series_id END) AS unique_completions
+        FROM tasks
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(stats)
+}
+
+#[tauri::command]
+pub async fn get_task_stats(pool: State<'_, SqlitePool>) -> Result<TaskStats, AppError> {
+    get_task_stats_impl(pool.inner()).await
+}
+
+pub(crate) async fn get_tree_metrics_impl(pool: &SqlitePool) -> Result<TreeMetrics, AppError> {
+    // Depth is walked down from the roots, so orphaned rows don't count (they aren't drawn)
+    let metrics = sqlx::query_as::<_, TreeMetrics>(
+        r#"
+        WITH RECURSIVE tree(id, depth) AS (
+            SELECT id, 1 FROM tasks WHERE parent_id IS NULL
+            UNION ALL
+            SELECT t.id, tree.depth + 1
+            FROM tasks t
+            JOIN tree ON t.parent_id = tree.id
+        )
+        SELECT
+            (SELECT COALESCE(MAX(depth), 0) FROM tree) AS max_depth,
+            (SELECT COALESCE(MAX(siblings), 0)
+                FROM (SELECT COUNT(*) AS siblings FROM tasks GROUP BY parent_id)) AS max_siblings,
+            (SELECT COUNT(*) FROM tasks WHERE parent_id IS NULL) AS root_count
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(metrics)
+}
+
+#[tauri::command]
+pub async fn get_tree_metrics(pool: State<'_, SqlitePool>) -> Result<TreeMetrics, AppError> {
+    get_tree_metrics_impl(pool.inner()).await
+}
+
+/// Number of consecutive local days, ending today, with at least one completion.
+///
+/// `tz_offset_minutes` (east of UTC) decides where each day starts. A day without
+/// completions breaks the streak, so nothing completed yet today means 0.
+pub(crate) async fn get_completion_streak_impl(
+    pool: &SqlitePool,
+    tz_offset_minutes: i32,
+) -> Result<i64, AppError> {
+    let offset = tz_offset_seconds(tz_offset_minutes)?;
+
+    let days: Vec<i64> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT (completed_at + ?1) / 86400 AS day
+        FROM tasks
+        WHERE completed_at IS NOT NULL
+        ORDER BY day DESC
+        "#,
+    )
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    let today = (chrono::Utc::now().timestamp() + offset).div_euclid(86_400);
+    let mut streak = 0;
+    // Completions stamped in the future (clock skew) don't count towards today
+    for day in days.into_iter().skip_while(|day| *day > today) {
+        if day != today - streak {
+            break;
+        }
+        streak += 1;
+    }
+
+    Ok(streak)
+}
+
+#[tauri::command]
+pub async fn get_completion_streak(
+    pool: State<'_, SqlitePool>,
+    tz_offset_minutes: Option<i32>,
+) -> Result<i64, AppError> {
+    get_completion_streak_impl(pool.inner(), tz_offset_minutes.unwrap_or(0)).await
+}
+
+/// Longest range `get_completions_by_day` will fill, about ten years.
+const MAX_HEATMAP_DAYS: i64 = 3660;
+
+/// Completed-task counts per local day in [from, to], oldest first.
+///
+/// Every day in the range is listed, with 0 for days without completions, so the
+/// result can be drawn as a heatmap directly.
+pub(crate) async fn get_completions_by_day_impl(
+    pool: &SqlitePool,
+    from: i64,
+    to: i64,
+    tz_offset_minutes: i32,
+) -> Result<Vec<(String, i64)>, AppError> {
+    validate_range(from, to)?;
+    let offset = tz_offset_seconds(tz_offset_minutes)?;
+
+    let first_day = (from + offset).div_euclid(86_400);
+    let last_day = (to + offset).div_euclid(86_400);
+    if last_day - first_day >= MAX_HEATMAP_DAYS {
+        return Err(AppError::ValidationError(format!(
+            "Range must not span more than {} days",
+            MAX_HEATMAP_DAYS
+        )));
+    }
+
+    let counts: Vec<(i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT (completed_at + ?1) / 86400 AS day, COUNT(*) AS count
+        FROM tasks
+        WHERE completed_at BETWEEN ?2 AND ?3
+        GROUP BY day
+        "#,
+    )
+    .bind(offset)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    let counts: std::collections::HashMap<i64, i64> = counts.into_iter().collect();
+
+    let mut days = Vec::with_capacity((last_day - first_day + 1) as usize);
+    for day in first_day..=last_day {
+        let date = chrono::DateTime::from_timestamp(day * 86_400, 0)
+            .ok_or_else(|| AppError::ValidationError("Range is out of bounds".to_string()))?
+            .date_naive();
+        days.push((
+            date.format("%Y-%m-%d").to_string(),
+            counts.get(&day).copied().unwrap_or(0),
+        ));
+    }
+
+    Ok(days)
+}
+
+#[tauri::command]
+pub async fn get_completions_by_day(
+    pool: State<'_, SqlitePool>,
+    from: i64,
+    to: i64,
+    tz_offset_minutes: Option<i32>,
+) -> Result<Vec<(String, i64)>, AppError> {
+    get_completions_by_day_impl(pool.inner(), from, to, tz_offset_minutes.unwrap_or(0)).await
+}
+
+// Linear interpolation between the closest ranks of a sorted, non-empty slice
+fn percentile(sorted: &[i64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let weight = rank - lower as f64;
+    sorted[lower] as f64 + (sorted[upper] - sorted[lower]) as f64 * weight
+}
+
+/// Average, median and 90th percentile of `completed_at - created_at` for tasks
+/// completed in [from, to].
+pub(crate) async fn get_cycle_time_stats_impl(
+    pool: &SqlitePool,
+    from: i64,
+    to: i64,
+) -> Result<CycleTimeStats, AppError> {
+    validate_range(from, to)?;
+
+    let mut durations: Vec<i64> = sqlx::query_scalar(
+        r#"
+        SELECT completed_at - created_at
+        FROM tasks
+        WHERE completed_at IS NOT NULL
+        AND completed_at BETWEEN ? AND ?
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    durations.sort_unstable();
+
+    if durations.is_empty() {
+        return Ok(CycleTimeStats {
+            count: 0,
+            average: None,
+            median: None,
+            p90: None,
+        });
+    }
+
+    let total: i64 = durations.iter().sum();
+    Ok(CycleTimeStats {
+        count: durations.len() as i64,
+        average: Some(total as f64 / durations.len() as f64),
+        median: Some(percentile(&durations, 0.5)),
+        p90: Some(percentile(&durations, 0.9)),
+    })
+}
+
+#[tauri::command]
+pub async fn get_cycle_time_stats(
+    pool: State<'_, SqlitePool>,
+    from: i64,
+    to: i64,
+) -> Result<CycleTimeStats, AppError> {
+    get_cycle_time_stats_impl(pool.inner(), from, to).await
+}
+
+/// Counts of open tasks by when they are due. `tz_offset_minutes` (east of UTC)
+/// decides where today ends; "this week" is the six local days after today.
+pub(crate) async fn get_due_buckets_impl(
+    pool: &SqlitePool,
+    tz_offset_minutes: i32,
+) -> Result<DueBuckets, AppError> {
+    let offset = tz_offset_seconds(tz_offset_minutes)?;
+    let now = chrono::Utc::now().timestamp();
+    let today = (now + offset).div_euclid(86_400);
+    // UTC instants at which local days start
+    let day_start = |day: i64| day * 86_400 - offset;
+
+    // Comparisons are 0 or 1 in SQLite, so summing one counts the rows it holds for
+    let buckets = sqlx::query_as::<_, DueBuckets>(
+        r#"
+        SELECT
+            COALESCE(SUM(due_date < ?1), 0) AS overdue,
+            COALESCE(SUM(due_date >= ?1 AND due_date < ?2), 0) AS today,
+            COALESCE(SUM(due_date >= ?2 AND due_date < ?3), 0) AS this_week,
+            COALESCE(SUM(due_date >= ?3), 0) AS later,
+            COALESCE(SUM(due_date IS NULL), 0) AS none
+        FROM tasks
+        WHERE is_done = 0
+        "#,
+    )
+    .bind(now)
+    .bind(day_start(today + 1))
+    .bind(day_start(today + 7))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(buckets)
+}
+
+#[tauri::command]
+pub async fn get_due_buckets(
+    pool: State<'_, SqlitePool>,
+    tz_offset_minutes: Option<i32>,
+) -> Result<DueBuckets, AppError> {
+    get_due_buckets_impl(pool.inner(), tz_offset_minutes.unwrap_or(0)).await
+}
@@ -0,0 +1,105 @@
+use crate::db::DataVersionWatcher;
+use crate::error::AppError;
+use crate::models::{Category, ChangeSummary, StateFingerprint, Task};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tauri::State;
+
+// Bump whenever the fields fed into the fingerprint change
+const FINGERPRINT_VERSION: u32 = 5;
+
+pub(crate) async fn get_change_summary_since_impl(
+    pool: &SqlitePool,
+    since: i64,
+) -> Result<ChangeSummary, AppError> {
+    // Counts only - a single round-trip that never transfers rows
+    let summary = sqlx::query_as::<_, ChangeSummary>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM tasks WHERE updated_at > ?1) AS tasks_changed,
+            (SELECT COUNT(*) FROM categories WHERE updated_at > ?1) AS categories_changed,
+            (SELECT COUNT(*) FROM tombstones WHERE deleted_at > ?1) AS tombstones
+        "#,
+    )
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn get_change_summary_since(
+    pool: State<'_, SqlitePool>,
+    since: i64,
+) -> Result<ChangeSummary, AppError> {
+    get_change_summary_since_impl(pool.inner(), since).await
+}
+
+pub(crate) async fn get_state_fingerprint_impl(
+    pool: &SqlitePool,
+) -> Result<StateFingerprint, AppError> {
+    let tasks = sqlx::query_as::<_, Task>("SELECT * FROM tasks ORDER BY id ASC")
+        .fetch_all(pool)
+        .await?;
+    let categories = sqlx::query_as::<_, Category>("SELECT * FROM categories ORDER BY id ASC")
+        .fetch_all(pool)
+        .await?;
+
+    // One JSON array per row keeps field boundaries unambiguous. Write timestamps are
+    // left out so identical content hashes the same however it was produced.
+    let mut hasher = Sha256::new();
+    hasher.update(format!("v{}\n", FINGERPRINT_VERSION));
+    for category in &categories {
+        let row = serde_json::json!([
+            "category",
+            category.id,
+            category.name,
+            category.color,
+            category.archived_at,
+        ]);
+        hasher.update(row.to_string());
+        hasher.update(b"\n");
+    }
+    for task in &tasks {
+        let row = serde_json::json!([
+            "task",
+            task.id,
+            task.title,
+            task.description,
+            task.category_id,
+            task.priority,
+            task.parent_id,
+            task.is_done,
+            task.position,
+            task.due_date,
+            task.effort,
+            task.external_id,
+            task.series_id,
+            task.is_locked,
+            task.reminder_offset_minutes,
+            task.color,
+        ]);
+        hasher.update(row.to_string());
+        hasher.update(b"\n");
+    }
+
+    Ok(StateFingerprint {
+        version: FINGERPRINT_VERSION,
+        hash: format!("{:x}", hasher.finalize()),
+    })
+}
+
+#[tauri::command]
+pub async fn get_state_fingerprint(
+    pool: State<'_, SqlitePool>,
+) -> Result<StateFingerprint, AppError> {
+    get_state_fingerprint_impl(pool.inner()).await
+}
+
+/// SQLite's `data_version`, which changes whenever another connection commits (for
+/// example a sync daemon editing the database file). Poll it to know when to refresh.
+#[tauri::command]
+pub async fn get_data_version(watcher: State<'_, DataVersionWatcher>) -> Result<i64, AppError> {
+    Ok(watcher.data_version().await?)
+}
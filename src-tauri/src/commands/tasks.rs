@@ -1,7 +1,22 @@
+use crate::cache::TreeCache;
+use crate::commands::activity::log_activity;
+use crate::commands::categories::validate_color;
+use crate::commands::maintenance::normalize_group;
+use crate::commands::settings::default_category_id;
+use crate::commands::trash::move_to_trash;
+use crate::db::{timed, with_retry};
 use crate::error::AppError;
-use crate::models::{build_task_tree, CreateTaskInput, Task, TaskTree, UpdateTaskInput};
-use sqlx::SqlitePool;
-use tauri::State;
+use crate::models::{
+    build_task_tree, filter_task_tree, parse_markdown_checklist, parse_quick_add,
+    remove_done_tasks, sort_task_tree, CappedTasks, Category, CompletedWithFollowup,
+    CreateTaskInput, DeleteStrategy, FilteredTaskTree, Priority, Task, TaskDto, TaskFilter,
+    TaskListing, TaskNode, TaskPage, TaskTree, TaskWithCategory, Timed, TreeSort, UpdateTaskInput,
+    DEFAULT_PRIORITY, PRIORITIES,
+};
+use chrono::FixedOffset;
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, State};
 
 // Validation function for task input
 fn validate_task_title(title: &str) -> Result<(), AppError> {
@@ -17,221 +32,2776 @@ fn validate_task_title(title: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-// Helper function to get the next position for a task
-async fn get_next_position(
+// Validation function for task priority; the API speaks names, the column stores ranks
+fn parse_priority(priority: &str) -> Result<Priority, AppError> {
+    priority.parse().map_err(|_| {
+        AppError::ValidationError(format!(
+            "Invalid priority '{}' (expected one of: {})",
+            priority,
+            PRIORITIES.join(", ")
+        ))
+    })
+}
+
+// Priority for a new task, falling back to the default when none was given
+fn resolve_priority(priority: Option<&str>) -> Result<Priority, AppError> {
+    priority.map_or(Ok(DEFAULT_PRIORITY), parse_priority)
+}
+
+// Validation function for effort estimates
+fn validate_effort(effort: Option<i32>) -> Result<(), AppError> {
+    if effort.is_some_and(|points| points < 0) {
+        return Err(AppError::ValidationError(
+            "Effort cannot be negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Validation function for reminder lead times
+fn validate_reminder_offset(minutes: Option<i32>) -> Result<(), AppError> {
+    if minutes.is_some_and(|minutes| minutes < 0) {
+        return Err(AppError::ValidationError(
+            "Reminder offset cannot be negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Validation function for task colors, which follow the category color rules
+fn validate_task_color(color: Option<&str>) -> Result<(), AppError> {
+    color.map_or(Ok(()), validate_color)
+}
+
+// Every rule a new task must satisfy; none of them need the database
+fn validate_task_fields(input: &CreateTaskInput) -> Result<(), AppError> {
+    validate_task_title(&input.title)?;
+    resolve_priority(input.priority.as_deref())?;
+    validate_effort(input.effort)?;
+    validate_reminder_offset(input.reminder_offset_minutes)?;
+    validate_task_color(input.color.as_deref())
+}
+
+/// Pre-flight check so the frontend can show inline errors using the same rules
+/// `create_task` applies. Never touches the database.
+#[tauri::command]
+pub fn validate_task_input(input: CreateTaskInput) -> Result<(), AppError> {
+    validate_task_fields(&input)
+}
+
+// Highest position in a sibling group
+async fn max_position(
+    conn: &mut SqliteConnection,
+    parent_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<Option<f64>, AppError> {
+    let (max,): (Option<f64>,) =
+        sqlx::query_as("SELECT MAX(position) FROM tasks WHERE parent_id IS ? AND category_id IS ?")
+            .bind(parent_id)
+            .bind(category_id)
+            .fetch_one(conn)
+            .await?;
+
+    Ok(max)
+}
+
+// Past this a REAL position can no longer step by whole numbers
+const MAX_EXACT_POSITION: f64 = 9_007_199_254_740_992.0; // 2^53
+
+// Helper function to get the next position for a task. If the group has crept up
+// to `MAX_EXACT_POSITION` it is renumbered to 0..n first instead of losing precision.
+pub(crate) async fn get_next_position(
+    conn: &mut SqliteConnection,
+    parent_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<f64, AppError> {
+    let next = |max: Option<f64>| {
+        let next = max.map_or(0.0, |max| max + 1.0);
+        (next < MAX_EXACT_POSITION).then_some(next)
+    };
+
+    let max = max_position(&mut *conn, parent_id, category_id).await?;
+    if let Some(position) = next(max) {
+        return Ok(position);
+    }
+
+    log::warn!(
+        "positions under parent {:?} in category {:?} reached 2^53, normalizing",
+        parent_id,
+        category_id
+    );
+    normalize_group(&mut *conn, parent_id, category_id).await?;
+    let max = max_position(&mut *conn, parent_id, category_id).await?;
+    next(max).ok_or_else(|| AppError::ValidationError("Too many tasks in this group".to_string()))
+}
+
+// A group whose highest position is more than this many times its size is renumbered
+// before a task is added to it
+const POSITION_DENSITY_FACTOR: f64 = 4.0;
+
+// Renumber a sibling group to 0..n if deletes that didn't close their gaps have left
+// its positions much sparser than its size, so they don't grow without bound
+async fn compact_sparse_group(
+    conn: &mut SqliteConnection,
+    parent_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<(), AppError> {
+    let (max, count): (Option<f64>, i64) = sqlx::query_as(
+        "SELECT MAX(position), COUNT(*) FROM tasks WHERE parent_id IS ? AND category_id IS ?",
+    )
+    .bind(parent_id)
+    .bind(category_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if max.is_some_and(|max| max > count as f64 * POSITION_DENSITY_FACTOR) {
+        normalize_group(&mut *conn, parent_id, category_id).await?;
+    }
+
+    Ok(())
+}
+
+// Escape LIKE wildcards so user input only matches literally
+fn like_pattern(needle: &str) -> String {
+    let escaped = needle
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+// Append WHERE conditions for a filter; unset fields don't constrain the query.
+// Returns the separator to use for any further conditions.
+fn push_task_filter(builder: &mut QueryBuilder<'_, Sqlite>, filter: &TaskFilter) -> &'static str {
+    let mut separator = " WHERE ";
+
+    if let Some(ref search) = filter.search {
+        let needle = search.trim();
+        if !needle.is_empty() {
+            let pattern = like_pattern(needle);
+            builder.push(separator);
+            builder.push("(title LIKE ");
+            builder.push_bind(pattern.clone());
+            builder.push(" ESCAPE '\\' OR description LIKE ");
+            builder.push_bind(pattern);
+            builder.push(" ESCAPE '\\')");
+            separator = " AND ";
+        }
+    }
+    if let Some(ref priority) = filter.priority {
+        builder.push(separator);
+        builder.push("priority = ");
+        // An unknown name binds NULL, which matches nothing, like the in-memory filter
+        builder.push_bind(priority.parse::<Priority>().ok());
+        separator = " AND ";
+    }
+    if let Some(is_done) = filter.is_done {
+        builder.push(separator);
+        builder.push("is_done = ");
+        builder.push_bind(is_done);
+        separator = " AND ";
+    }
+    if let Some(due_before) = filter.due_before {
+        builder.push(separator);
+        builder.push("due_date < ");
+        builder.push_bind(due_before);
+        separator = " AND ";
+    }
+
+    separator
+}
+
+// Returns true if `ancestor_id` appears on the parent chain of `task_id` (or is `task_id` itself)
+async fn is_ancestor_or_self(
+    conn: &mut SqliteConnection,
+    ancestor_id: i64,
+    task_id: i64,
+) -> Result<bool, AppError> {
+    let (found,): (i64,) = sqlx::query_as(
+        r#"
+        WITH RECURSIVE ancestors(id, parent_id) AS (
+            SELECT id, parent_id FROM tasks WHERE id = ?
+            UNION ALL
+            SELECT t.id, t.parent_id FROM tasks t JOIN ancestors a ON t.id = a.parent_id
+        )
+        SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = ?)
+        "#,
+    )
+    .bind(task_id)
+    .bind(ancestor_id)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(found != 0)
+}
+
+// Archived categories can't take new tasks; unknown ids are left to the foreign key
+async fn ensure_category_active(
+    conn: &mut SqliteConnection,
+    category_id: i64,
+) -> Result<(), AppError> {
+    let archived: Option<(Option<i64>,)> =
+        sqlx::query_as("SELECT archived_at FROM categories WHERE id = ?")
+            .bind(category_id)
+            .fetch_optional(conn)
+            .await?;
+
+    if matches!(archived, Some((Some(_),))) {
+        return Err(AppError::ValidationError(format!(
+            "Category {} is archived",
+            category_id
+        )));
+    }
+    Ok(())
+}
+
+// Re-read a task after a write that triggers follow up on. RETURNING reports the row as
+// the statement itself left it, without e.g. the `path` the triggers fill in.
+async fn fetch_task<'e, E>(executor: E, id: i64) -> Result<Task, AppError>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_one(executor)
+        .await?;
+
+    Ok(task)
+}
+
+// Refuse to mutate a locked task unless the caller forces it; missing tasks pass through
+async fn ensure_unlocked<'e, E>(executor: E, id: i64, force: bool) -> Result<(), AppError>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    if force {
+        return Ok(());
+    }
+
+    let locked: Option<(bool,)> = sqlx::query_as("SELECT is_locked FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+
+    if matches!(locked, Some((true,))) {
+        return Err(AppError::ValidationError("task is locked".to_string()));
+    }
+    Ok(())
+}
+
+// Validate and insert a task at the end of its sibling group
+pub(crate) async fn insert_task(
+    conn: &mut SqliteConnection,
+    input: &CreateTaskInput,
+) -> Result<Task, AppError> {
+    // Validate input
+    validate_task_fields(input)?;
+    let priority = resolve_priority(input.priority.as_deref())?;
+
+    let category_id = match input.category_id {
+        Some(category_id) => Some(category_id),
+        None => default_category_id(&mut *conn).await?,
+    };
+    if let Some(category_id) = category_id {
+        ensure_category_active(&mut *conn, category_id).await?;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    compact_sparse_group(&mut *conn, input.parent_id, category_id).await?;
+    let position = get_next_position(&mut *conn, input.parent_id, category_id).await?;
+    let title_trimmed = input.title.trim();
+
+    let query = sqlx::query_scalar::<_, i64>(
+        r#"
+        INSERT INTO tasks (title, description, category_id, priority, parent_id, position, due_date, effort, reminder_offset_minutes, color, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(title_trimmed)
+    .bind(&input.description)
+    .bind(category_id)
+    .bind(priority)
+    .bind(input.parent_id)
+    .bind(position)
+    .bind(input.due_date)
+    .bind(input.effort)
+    .bind(input.reminder_offset_minutes)
+    .bind(&input.color)
+    .bind(now)
+    .bind(now);
+    let id = timed("insert_task", query.fetch_one(&mut *conn)).await?;
+    let task = fetch_task(&mut *conn, id).await?;
+
+    log_activity(&mut *conn, task.id, "created", None).await?;
+
+    Ok(task)
+}
+
+pub(crate) async fn create_task_impl(
+    pool: &SqlitePool,
+    input: CreateTaskInput,
+) -> Result<Task, AppError> {
+    let mut tx = pool.begin().await?;
+    let task = insert_task(&mut tx, &input).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn create_task(
+    pool: State<'_, SqlitePool>,
+    input: CreateTaskInput,
+) -> Result<Task, AppError> {
+    with_retry(|| create_task_impl(pool.inner(), input.clone())).await
+}
+
+/// Creates every task in `inputs` in one transaction, in order, so tasks sharing a
+/// sibling group get consecutive positions. Every input is validated before anything
+/// is written; an invalid one fails the whole batch, naming which input it was.
+pub(crate) async fn create_tasks_impl(
+    pool: &SqlitePool,
+    inputs: Vec<CreateTaskInput>,
+) -> Result<Vec<Task>, AppError> {
+    for (index, input) in inputs.iter().enumerate() {
+        validate_task_fields(input).map_err(|err| match err {
+            AppError::ValidationError(message) => {
+                AppError::ValidationError(format!("Task {}: {}", index + 1, message))
+            }
+            other => other,
+        })?;
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut tasks = Vec::with_capacity(inputs.len());
+    for input in &inputs {
+        tasks.push(insert_task(&mut tx, input).await?);
+    }
+    tx.commit().await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn create_tasks(
+    pool: State<'_, SqlitePool>,
+    inputs: Vec<CreateTaskInput>,
+) -> Result<Vec<Task>, AppError> {
+    with_retry(|| create_tasks_impl(pool.inner(), inputs.clone())).await
+}
+
+/// Creates a task from quick-add text like `Buy milk !high #Personal tomorrow`.
+///
+/// `tz_offset_minutes` (east of UTC) decides which day "today" is; defaults to UTC.
+pub(crate) async fn quick_add_impl(
+    pool: &SqlitePool,
+    text: &str,
+    tz_offset_minutes: i32,
+) -> Result<Task, AppError> {
+    let offset = FixedOffset::east_opt(tz_offset_minutes.saturating_mul(60)).ok_or_else(|| {
+        AppError::ValidationError(format!("Invalid timezone offset {}", tz_offset_minutes))
+    })?;
+    let now = chrono::Utc::now().with_timezone(&offset);
+
+    let categories =
+        sqlx::query_as::<_, Category>("SELECT * FROM categories WHERE archived_at IS NULL")
+            .fetch_all(pool)
+            .await?;
+    let input = parse_quick_add(text, &categories, now);
+
+    create_task_impl(pool, input).await
+}
+
+#[tauri::command]
+pub async fn quick_add(
+    pool: State<'_, SqlitePool>,
+    text: String,
+    tz_offset_minutes: Option<i32>,
+) -> Result<Task, AppError> {
+    with_retry(|| quick_add_impl(pool.inner(), &text, tz_offset_minutes.unwrap_or(0))).await
+}
+
+/// Creates a task tree from an indented markdown checklist (`- [ ]` / `- [x]` lines).
+///
+/// Every task goes into `category_id` (or the default category). Nothing is created
+/// unless the whole list parses. Returns the tasks in document order.
+pub(crate) async fn import_markdown_impl(
+    pool: &SqlitePool,
+    text: &str,
+    category_id: Option<i64>,
+) -> Result<Vec<Task>, AppError> {
+    let items = parse_markdown_checklist(text)?;
+    if items.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No checklist items found".to_string(),
+        ));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
+    let mut tasks = Vec::with_capacity(items.len());
+    // Ids of the most recent task at each depth above the current line
+    let mut ancestors: Vec<i64> = Vec::new();
+
+    for item in items {
+        ancestors.truncate(item.depth);
+        let input = CreateTaskInput {
+            title: item.title,
+            description: None,
+            category_id,
+            priority: None,
+            parent_id: ancestors.last().copied(),
+            due_date: None,
+            effort: None,
+            reminder_offset_minutes: None,
+            color: None,
+        };
+        let mut task = insert_task(&mut tx, &input).await?;
+
+        if item.is_done {
+            task = sqlx::query_as::<_, Task>(
+                "UPDATE tasks SET is_done = 1, completed_at = ? WHERE id = ? RETURNING *",
+            )
+            .bind(now)
+            .bind(task.id)
+            .fetch_one(&mut *tx)
+            .await?;
+        }
+
+        ancestors.push(task.id);
+        tasks.push(task);
+    }
+
+    tx.commit().await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn import_markdown(
+    pool: State<'_, SqlitePool>,
+    text: String,
+    category_id: Option<i64>,
+) -> Result<Vec<Task>, AppError> {
+    with_retry(|| import_markdown_impl(pool.inner(), &text, category_id)).await
+}
+
+pub(crate) async fn upsert_task_impl(
+    pool: &SqlitePool,
+    external_id: &str,
+    input: CreateTaskInput,
+) -> Result<Task, AppError> {
+    if external_id.trim().is_empty() {
+        return Err(AppError::ValidationError(
+            "External id cannot be empty".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let existing: Option<Task> = sqlx::query_as("SELECT * FROM tasks WHERE external_id = ?")
+        .bind(external_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let task = match existing {
+        Some(existing) => {
+            validate_task_fields(&input)?;
+            let priority = resolve_priority(input.priority.as_deref())?;
+            let category_id = match input.category_id {
+                Some(category_id) => Some(category_id),
+                None => default_category_id(&mut *tx).await?,
+            };
+
+            // Keep the current position unless the task lands in a different sibling group
+            let moved = existing.parent_id != input.parent_id || existing.category_id != category_id;
+            let position = if moved {
+                if let Some(category_id) = category_id {
+                    ensure_category_active(&mut tx, category_id).await?;
+                }
+                Some(get_next_position(&mut tx, input.parent_id, category_id).await?)
+            } else {
+                None
+            };
+
+            let update = UpdateTaskInput {
+                title: Some(input.title.trim().to_string()),
+                description: Some(input.description),
+                category_id: Some(category_id),
+                priority: Some(priority.to_string()),
+                parent_id: moved.then_some(input.parent_id),
+                is_done: None,
+                position,
+                due_date: Some(input.due_date),
+                effort: Some(input.effort),
+                reminder_offset_minutes: Some(input.reminder_offset_minutes),
+                color: Some(input.color),
+            };
+            let task = apply_task_update(&mut tx, existing.id, update, false).await?;
+            // Close the gap left in the old group
+            if moved {
+                normalize_group(&mut tx, existing.parent_id, existing.category_id).await?;
+            }
+            task
+        }
+        None => {
+            let task = insert_task(&mut tx, &input).await?;
+            sqlx::query_as::<_, Task>("UPDATE tasks SET external_id = ? WHERE id = ? RETURNING *")
+                .bind(external_id)
+                .bind(task.id)
+                .fetch_one(&mut *tx)
+                .await?
+        }
+    };
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn upsert_task(
+    pool: State<'_, SqlitePool>,
+    external_id: String,
+    input: CreateTaskInput,
+) -> Result<Task, AppError> {
+    with_retry(|| upsert_task_impl(pool.inner(), &external_id, input.clone())).await
+}
+
+pub(crate) async fn get_all_tasks_impl(
+    pool: &SqlitePool,
+    roots_only: bool,
+) -> Result<Vec<Task>, AppError> {
+    if roots_only {
+        return get_root_tasks_impl(pool).await;
+    }
+
+    let tasks = timed(
+        "get_all_tasks",
+        sqlx::query_as::<_, Task>("SELECT * FROM tasks ORDER BY position ASC").fetch_all(pool),
+    )
+    .await?;
+
+    Ok(tasks)
+}
+
+/// The first `limit` tasks of `get_all_tasks_impl`'s listing, plus the full count.
+pub(crate) async fn get_all_tasks_capped_impl(
+    pool: &SqlitePool,
+    roots_only: bool,
+    limit: i64,
+) -> Result<CappedTasks, AppError> {
+    let condition = if roots_only {
+        " WHERE parent_id IS NULL"
+    } else {
+        ""
+    };
+
+    // Both in one transaction so the total describes the same rows as the items
+    let mut tx = pool.begin().await?;
+    let items = sqlx::query_as::<_, Task>(&format!(
+        "SELECT * FROM tasks{} ORDER BY position ASC LIMIT ?",
+        condition
+    ))
+    .bind(limit.max(0))
+    .fetch_all(&mut *tx)
+    .await?;
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM tasks{}", condition))
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(CappedTasks { items, total })
+}
+
+/// Every task, or only top-level ones when `roots_only` is true. With a `limit` only
+/// that many are returned, as `{ items, total }`.
+#[tauri::command]
+pub async fn get_all_tasks(
+    pool: State<'_, SqlitePool>,
+    roots_only: Option<bool>,
+    limit: Option<i64>,
+) -> Result<TaskListing, AppError> {
+    let roots_only = roots_only.unwrap_or(false);
+    match limit {
+        Some(limit) => get_all_tasks_capped_impl(pool.inner(), roots_only, limit)
+            .await
+            .map(TaskListing::Capped),
+        None => get_all_tasks_impl(pool.inner(), roots_only)
+            .await
+            .map(TaskListing::All),
+    }
+}
+
+/// Tasks with the given ids, in the order requested; unknown ids are skipped.
+pub(crate) async fn get_tasks_by_ids_impl(
+    pool: &SqlitePool,
+    ids: &[i64],
+) -> Result<Vec<Task>, AppError> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::<Sqlite>::new("SELECT * FROM tasks WHERE id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
+    }
+    separated.push_unseparated(")");
+
+    let tasks = timed(
+        "get_tasks_by_ids",
+        builder.build_query_as::<Task>().fetch_all(pool),
+    )
+    .await?;
+
+    let mut by_id: HashMap<i64, Task> = tasks.into_iter().map(|t| (t.id, t)).collect();
+    Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
+}
+
+#[tauri::command]
+pub async fn get_tasks_by_ids(
+    pool: State<'_, SqlitePool>,
+    ids: Vec<i64>,
+) -> Result<Vec<Task>, AppError> {
+    get_tasks_by_ids_impl(pool.inner(), &ids).await
+}
+
+pub(crate) async fn get_tasks_with_category_impl(
+    pool: &SqlitePool,
+) -> Result<Vec<TaskWithCategory>, AppError> {
+    let tasks = timed(
+        "get_tasks_with_category",
+        sqlx::query_as::<_, TaskWithCategory>(
+            r#"
+            SELECT
+                t.*,
+                c.name AS category_name,
+                c.color AS category_color,
+                c.created_at AS category_created_at,
+                c.updated_at AS category_updated_at,
+                c.archived_at AS category_archived_at
+            FROM tasks t
+            LEFT JOIN categories c ON c.id = t.category_id
+            ORDER BY t.position ASC
+            "#,
+        )
+        .fetch_all(pool),
+    )
+    .await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_tasks_with_category(
+    pool: State<'_, SqlitePool>,
+) -> Result<Vec<TaskWithCategory>, AppError> {
+    get_tasks_with_category_impl(pool.inner()).await
+}
+
+pub(crate) async fn count_tasks_impl(
+    pool: &SqlitePool,
+    filter: Option<TaskFilter>,
+) -> Result<i64, AppError> {
+    let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM tasks");
+    if let Some(ref filter) = filter {
+        push_task_filter(&mut builder, filter);
+    }
+
+    let (count,) = timed(
+        "count_tasks",
+        builder.build_query_as::<(i64,)>().fetch_one(pool),
+    )
+    .await?;
+
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn count_tasks(
+    pool: State<'_, SqlitePool>,
+    filter: Option<TaskFilter>,
+) -> Result<i64, AppError> {
+    count_tasks_impl(pool.inner(), filter).await
+}
+
+/// Open tasks without a category, for the "Inbox (N)" label.
+pub(crate) async fn get_inbox_count_impl(pool: &SqlitePool) -> Result<i64, AppError> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM tasks WHERE category_id IS NULL AND is_done = 0")
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn get_inbox_count(pool: State<'_, SqlitePool>) -> Result<i64, AppError> {
+    get_inbox_count_impl(pool.inner()).await
+}
+
+/// Tasks whose title or description contains `query`, optionally limited to the
+/// descendants of `root_id` (the root itself excluded) and to one category.
+///
+/// Results are ordered by (position, id), so equal positions always come back in the
+/// same order. With a `limit`, pages continue after the task `after_id`; without one
+/// every match is returned on a single page.
+pub(crate) async fn search_tasks_impl(
+    pool: &SqlitePool,
+    query: &str,
+    root_id: Option<i64>,
+    category_id: Option<i64>,
+    after_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<TaskPage, AppError> {
+    // The cursor is the last task of the previous page; its sort key is the boundary
+    let after: Option<(f64, i64)> = match after_id {
+        Some(after_id) => Some(
+            sqlx::query_as("SELECT position, id FROM tasks WHERE id = ?")
+                .bind(after_id)
+                .fetch_optional(pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Cursor task {} not found", after_id)))?,
+        ),
+        None => None,
+    };
+    let limit = limit.map(|limit| limit.clamp(1, MAX_FEED_LIMIT));
+
+    let mut builder = QueryBuilder::new("");
+    if let Some(root_id) = root_id {
+        builder.push("WITH RECURSIVE scope(id) AS (SELECT id FROM tasks WHERE parent_id = ");
+        builder.push_bind(root_id);
+        builder.push(" UNION ALL SELECT t.id FROM tasks t JOIN scope ON t.parent_id = scope.id) ");
+    }
+    builder.push("SELECT * FROM tasks");
+
+    let filter = TaskFilter {
+        search: Some(query.to_string()),
+        ..Default::default()
+    };
+    let mut separator = push_task_filter(&mut builder, &filter);
+    if root_id.is_some() {
+        builder.push(separator);
+        builder.push("id IN (SELECT id FROM scope)");
+        separator = " AND ";
+    }
+    if let Some(category_id) = category_id {
+        builder.push(separator);
+        builder.push("category_id = ");
+        builder.push_bind(category_id);
+        separator = " AND ";
+    }
+    if let Some((position, id)) = after {
+        builder.push(separator);
+        builder.push("(position, id) > (");
+        builder.push_bind(position);
+        builder.push(", ");
+        builder.push_bind(id);
+        builder.push(")");
+    }
+    builder.push(" ORDER BY position ASC, id ASC");
+    if let Some(limit) = limit {
+        // Fetch one extra row to learn whether another page follows
+        builder.push(" LIMIT ");
+        builder.push_bind(limit + 1);
+    }
+
+    let mut items = timed(
+        "search_tasks",
+        builder.build_query_as::<Task>().fetch_all(pool),
+    )
+    .await?;
+
+    let next_cursor = match limit {
+        Some(limit) if items.len() as i64 > limit => {
+            items.truncate(limit as usize);
+            items.last().map(|task| task.id)
+        }
+        _ => None,
+    };
+
+    Ok(TaskPage { items, next_cursor })
+}
+
+#[tauri::command]
+pub async fn search_tasks(
+    pool: State<'_, SqlitePool>,
+    query: String,
+    root_id: Option<i64>,
+    category_id: Option<i64>,
+    after_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<TaskPage, AppError> {
+    search_tasks_impl(pool.inner(), &query, root_id, category_id, after_id, limit).await
+}
+
+#[tauri::command]
+pub async fn get_all_tasks_iso(pool: State<'_, SqlitePool>) -> Result<Vec<TaskDto>, AppError> {
+    let tasks = get_all_tasks_impl(pool.inner(), false).await?;
+    Ok(tasks.into_iter().map(TaskDto::from).collect())
+}
+
+/// Top-level tasks in display order.
+pub(crate) async fn get_root_tasks_impl(pool: &SqlitePool) -> Result<Vec<Task>, AppError> {
+    let tasks = timed(
+        "get_root_tasks",
+        sqlx::query_as::<_, Task>(
+            "SELECT * FROM tasks WHERE parent_id IS NULL ORDER BY position ASC",
+        )
+        .fetch_all(pool),
+    )
+    .await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_root_tasks(pool: State<'_, SqlitePool>) -> Result<Vec<Task>, AppError> {
+    get_root_tasks_impl(pool.inner()).await
+}
+
+// Children of `parent_id` (roots when None) in position order, each with its own
+// child count so the UI knows which nodes can be expanded
+async fn get_task_nodes(
+    pool: &SqlitePool,
+    parent_id: Option<i64>,
+) -> Result<Vec<TaskNode>, AppError> {
+    let nodes = sqlx::query_as::<_, TaskNode>(
+        r#"
+        SELECT t.*,
+            COALESCE(c.child_count, 0) > 0 AS has_children,
+            COALESCE(c.child_count, 0) AS child_count
+        FROM tasks t
+        LEFT JOIN (
+            SELECT parent_id, COUNT(*) AS child_count
+            FROM tasks
+            WHERE parent_id IS NOT NULL
+            GROUP BY parent_id
+        ) c ON c.parent_id = t.id
+        WHERE t.parent_id IS ?
+        ORDER BY t.position ASC, t.id ASC
+        "#,
+    )
+    .bind(parent_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(nodes)
+}
+
+/// Root tasks marked with whether they have subtasks, for a tree that loads children
+/// on expand through `get_child_tasks`.
+pub(crate) async fn get_root_tasks_with_markers_impl(
+    pool: &SqlitePool,
+) -> Result<Vec<TaskNode>, AppError> {
+    get_task_nodes(pool, None).await
+}
+
+#[tauri::command]
+pub async fn get_root_tasks_with_markers(
+    pool: State<'_, SqlitePool>,
+) -> Result<Vec<TaskNode>, AppError> {
+    get_root_tasks_with_markers_impl(pool.inner()).await
+}
+
+/// Direct subtasks of `parent_id`, marked the same way as `get_root_tasks_with_markers`.
+pub(crate) async fn get_child_tasks_impl(
+    pool: &SqlitePool,
+    parent_id: i64,
+) -> Result<Vec<TaskNode>, AppError> {
+    get_task_nodes(pool, Some(parent_id)).await
+}
+
+#[tauri::command]
+pub async fn get_child_tasks(
+    pool: State<'_, SqlitePool>,
+    parent_id: i64,
+) -> Result<Vec<TaskNode>, AppError> {
+    get_child_tasks_impl(pool.inner(), parent_id).await
+}
+
+pub(crate) async fn get_tasks_in_tree_order_impl(pool: &SqlitePool) -> Result<Vec<Task>, AppError> {
+    // Each row's sort key is its ancestors' keys plus its own zero-padded rank among its
+    // siblings, so ordering by the key yields a depth-first pre-order walk. Ranking first
+    // keeps fractional and negative positions sortable as text.
+    let query = sqlx::query_as::<_, Task>(
+        r#"
+        WITH RECURSIVE ranked(id, parent_id, sibling_key) AS (
+            SELECT id, parent_id, printf('%010d', ROW_NUMBER() OVER (
+                PARTITION BY parent_id ORDER BY position ASC, id ASC
+            ))
+            FROM tasks
+        ),
+        tree(id, sort_path) AS (
+            SELECT id, sibling_key
+            FROM ranked
+            WHERE parent_id IS NULL
+            UNION ALL
+            SELECT r.id, tree.sort_path || '/' || r.sibling_key
+            FROM ranked r
+            JOIN tree ON r.parent_id = tree.id
+        )
+        SELECT tasks.*
+        FROM tasks
+        JOIN tree ON tasks.id = tree.id
+        ORDER BY tree.sort_path ASC
+        "#,
+    );
+    let tasks = timed("get_tasks_in_tree_order", query.fetch_all(pool)).await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_tasks_in_tree_order(pool: State<'_, SqlitePool>) -> Result<Vec<Task>, AppError> {
+    get_tasks_in_tree_order_impl(pool.inner()).await
+}
+
+/// The task `id` with all of its descendants nested under it.
+pub(crate) async fn get_subtree_impl(pool: &SqlitePool, id: i64) -> Result<TaskTree, AppError> {
+    let mut tx = pool.begin().await?;
+
+    // Checked up front so a missing root isn't mistaken for an empty subtree
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound(format!("Task {} not found", id)));
+    }
+
+    let query = sqlx::query_as::<_, Task>(
+        r#"
+        WITH RECURSIVE subtree(id) AS (
+            SELECT ?
+            UNION ALL
+            SELECT t.id FROM tasks t JOIN subtree ON t.parent_id = subtree.id
+        )
+        SELECT tasks.*
+        FROM tasks
+        JOIN subtree ON tasks.id = subtree.id
+        ORDER BY tasks.position ASC
+        "#,
+    )
+    .bind(id);
+    let mut tasks = timed("get_subtree", query.fetch_all(&mut *tx)).await?;
+    tx.commit().await?;
+
+    // build_task_tree roots at parentless tasks, so detach the root while building
+    let root = tasks
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+    let parent_id = root.parent_id.take();
+    let mut tree = build_task_tree(tasks)
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+    tree.task.parent_id = parent_id;
+
+    Ok(tree)
+}
+
+#[tauri::command]
+pub async fn get_subtree(pool: State<'_, SqlitePool>, id: i64) -> Result<TaskTree, AppError> {
+    get_subtree_impl(pool.inner(), id).await
+}
+
+/// Builds the task tree, reusing the cached one when no task has changed since.
+pub(crate) async fn get_task_tree_impl(
+    pool: &SqlitePool,
+    cache: &TreeCache,
+    include_done: bool,
+) -> Result<Vec<TaskTree>, AppError> {
+    // Read the version and rows together so the cached tree is never newer than its tag
+    let mut tx = pool.begin().await?;
+    let (version,): (i64,) = sqlx::query_as("SELECT version FROM data_version WHERE id = 1")
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let tree = match cache.get(version) {
+        Some(tree) => tree,
+        None => {
+            let tasks = timed(
+                "get_task_tree",
+                sqlx::query_as::<_, Task>("SELECT * FROM tasks ORDER BY position ASC")
+                    .fetch_all(&mut *tx),
+            )
+            .await?;
+            let tree = build_task_tree(tasks);
+            cache.store(version, tree.clone());
+            tree
+        }
+    };
+    tx.commit().await?;
+
+    Ok(if include_done {
+        tree
+    } else {
+        remove_done_tasks(tree)
+    })
+}
+
+/// Completed tasks are included unless `include_done` is explicitly false.
+#[tauri::command]
+pub async fn get_task_tree(
+    pool: State<'_, SqlitePool>,
+    cache: State<'_, TreeCache>,
+    include_done: Option<bool>,
+) -> Result<Vec<TaskTree>, AppError> {
+    get_task_tree_impl(pool.inner(), cache.inner(), include_done.unwrap_or(true)).await
+}
+
+/// `get_task_tree`, along with how long building the tree took, for tracing in the UI.
+pub(crate) async fn get_task_tree_timed_impl(
+    pool: &SqlitePool,
+    cache: &TreeCache,
+    include_done: bool,
+) -> Result<Timed<Vec<TaskTree>>, AppError> {
+    let start = std::time::Instant::now();
+    let data = get_task_tree_impl(pool, cache, include_done).await?;
+    let elapsed_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+    Ok(Timed { data, elapsed_ms })
+}
+
+#[tauri::command]
+pub async fn get_task_tree_timed(
+    pool: State<'_, SqlitePool>,
+    cache: State<'_, TreeCache>,
+    include_done: Option<bool>,
+) -> Result<Timed<Vec<TaskTree>>, AppError> {
+    get_task_tree_timed_impl(pool.inner(), cache.inner(), include_done.unwrap_or(true)).await
+}
+
+/// The full task tree with its roots ordered by `sort`, e.g. to float active projects up.
+pub(crate) async fn get_task_tree_sorted_impl(
+    pool: &SqlitePool,
+    cache: &TreeCache,
+    sort: TreeSort,
+) -> Result<Vec<TaskTree>, AppError> {
+    let tree = get_task_tree_impl(pool, cache, true).await?;
+    Ok(sort_task_tree(tree, sort))
+}
+
+#[tauri::command]
+pub async fn get_task_tree_sorted(
+    pool: State<'_, SqlitePool>,
+    cache: State<'_, TreeCache>,
+    sort: Option<TreeSort>,
+) -> Result<Vec<TaskTree>, AppError> {
+    get_task_tree_sorted_impl(pool.inner(), cache.inner(), sort.unwrap_or_default()).await
+}
+
+/// Same tree as `get_task_tree`, already serialized, so the frontend can `JSON.parse`
+/// it in one go instead of going through Tauri's own serialization for large trees.
+pub(crate) async fn get_tasks_tree_json_string_impl(
+    pool: &SqlitePool,
+    cache: &TreeCache,
+    include_done: bool,
+) -> Result<String, AppError> {
+    let tree = get_task_tree_impl(pool, cache, include_done).await?;
+    serde_json::to_string(&tree)
+        .map_err(|err| AppError::DatabaseError(format!("Failed to serialize task tree: {}", err)))
+}
+
+#[tauri::command]
+pub async fn get_tasks_tree_json_string(
+    pool: State<'_, SqlitePool>,
+    cache: State<'_, TreeCache>,
+    include_done: Option<bool>,
+) -> Result<String, AppError> {
+    get_tasks_tree_json_string_impl(pool.inner(), cache.inner(), include_done.unwrap_or(true)).await
+}
+
+pub(crate) async fn get_task_tree_filtered_impl(
+    pool: &SqlitePool,
+    filter: TaskFilter,
+) -> Result<Vec<FilteredTaskTree>, AppError> {
+    let tasks = timed(
+        "get_task_tree_filtered",
+        sqlx::query_as::<_, Task>("SELECT * FROM tasks ORDER BY position ASC").fetch_all(pool),
+    )
+    .await?;
+
+    Ok(filter_task_tree(build_task_tree(tasks), &filter))
+}
+
+#[tauri::command]
+pub async fn get_task_tree_filtered(
+    pool: State<'_, SqlitePool>,
+    filter: TaskFilter,
+) -> Result<Vec<FilteredTaskTree>, AppError> {
+    get_task_tree_filtered_impl(pool.inner(), filter).await
+}
+
+pub(crate) async fn update_task_impl(
+    pool: &SqlitePool,
+    id: i64,
+    input: UpdateTaskInput,
+    force: bool,
+) -> Result<Task, AppError> {
+    // The lock check, the write and its activity entry commit together
+    let mut tx = pool.begin().await?;
+    let task = apply_task_update(&mut tx, id, input, force).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+// Validate and apply a partial update to task `id`, logging it as one activity entry
+async fn apply_task_update(
+    conn: &mut SqliteConnection,
+    id: i64,
+    input: UpdateTaskInput,
+    force: bool,
+) -> Result<Task, AppError> {
+    // Validate title if provided
+    if let Some(ref title) = input.title {
+        validate_task_title(title)?;
+    }
+    let priority = input.priority.as_deref().map(parse_priority).transpose()?;
+    validate_effort(input.effort.flatten())?;
+    validate_reminder_offset(input.reminder_offset_minutes.flatten())?;
+    validate_task_color(input.color.as_ref().and_then(Option::as_deref))?;
+    ensure_unlocked(&mut *conn, id, force).await?;
+
+    let (action, detail) = describe_update(&input);
+    let reparented = input.parent_id.is_some();
+    let now = chrono::Utc::now().timestamp();
+
+    // Use QueryBuilder for safe dynamic query construction
+    let mut builder = sqlx::QueryBuilder::new("UPDATE tasks SET ");
+    push_task_changes(&mut builder, input, priority, now);
+    builder.push(" WHERE id = ");
+    builder.push_bind(id);
+    builder.push(" RETURNING *");
+
+    let task = timed(
+        "update_task",
+        builder.build_query_as::<Task>().fetch_one(&mut *conn),
+    )
+    .await?;
+    // A new parent means the triggers rewrote the path
+    let task = if reparented {
+        fetch_task(&mut *conn, task.id).await?
+    } else {
+        task
+    };
+
+    log_activity(&mut *conn, task.id, action, detail.as_deref()).await?;
+
+    Ok(task)
+}
+
+// Append the SET assignments for every field `input` changes, `updated_at` first.
+// `priority` is `input.priority` already parsed.
+fn push_task_changes(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    input: UpdateTaskInput,
+    priority: Option<Priority>,
+    now: i64,
+) {
+    builder.push("updated_at = ");
+    builder.push_bind(now);
+
+    if let Some(title) = input.title {
+        builder.push(", title = ");
+        builder.push_bind(title);
+    }
+    if let Some(description) = input.description {
+        builder.push(", description = ");
+        builder.push_bind(description);
+    }
+    if let Some(category_id) = input.category_id {
+        builder.push(", category_id = ");
+        builder.push_bind(category_id);
+    }
+    if let Some(priority) = priority {
+        builder.push(", priority = ");
+        builder.push_bind(priority);
+    }
+    if let Some(parent_id) = input.parent_id {
+        builder.push(", parent_id = ");
+        builder.push_bind(parent_id);
+    }
+    if let Some(position) = input.position {
+        builder.push(", position = ");
+        builder.push_bind(position);
+    }
+    if let Some(due_date) = input.due_date {
+        builder.push(", due_date = ");
+        builder.push_bind(due_date);
+    }
+    if let Some(effort) = input.effort {
+        builder.push(", effort = ");
+        builder.push_bind(effort);
+    }
+    if let Some(reminder_offset_minutes) = input.reminder_offset_minutes {
+        builder.push(", reminder_offset_minutes = ");
+        builder.push_bind(reminder_offset_minutes);
+    }
+    if let Some(color) = input.color {
+        builder.push(", color = ");
+        builder.push_bind(color);
+    }
+    if let Some(is_done) = input.is_done {
+        builder.push(", is_done = ");
+        builder.push_bind(is_done);
+        if is_done {
+            builder.push(", completed_at = ");
+            builder.push_bind(now);
+        } else {
+            builder.push(", completed_at = NULL");
+        }
+    }
+}
+
+// Pick the activity log action and a short list of changed fields for an update
+fn describe_update(input: &UpdateTaskInput) -> (&'static str, Option<String>) {
+    let fields: Vec<&str> = [
+        ("title", input.title.is_some()),
+        ("description", input.description.is_some()),
+        ("category_id", input.category_id.is_some()),
+        ("priority", input.priority.is_some()),
+        ("parent_id", input.parent_id.is_some()),
+        ("position", input.position.is_some()),
+        ("due_date", input.due_date.is_some()),
+        ("effort", input.effort.is_some()),
+        (
+            "reminder_offset_minutes",
+            input.reminder_offset_minutes.is_some(),
+        ),
+        ("color", input.color.is_some()),
+        ("is_done", input.is_done.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(field, _)| field)
+    .collect();
+
+    let action = match input.is_done {
+        Some(true) => "completed",
+        Some(false) => "reopened",
+        None if input.parent_id.is_some() || input.position.is_some() => "moved",
+        None => "updated",
+    };
+    let detail = (!fields.is_empty()).then(|| fields.join(", "));
+
+    (action, detail)
+}
+
+/// Locked tasks are only updated when `force` is true.
+#[tauri::command]
+pub async fn update_task(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    input: UpdateTaskInput,
+    force: Option<bool>,
+) -> Result<Task, AppError> {
+    with_retry(|| update_task_impl(pool.inner(), id, input.clone(), force.unwrap_or(false))).await
+}
+
+/// Applies `changes` to every task matching `filter` in one transaction and returns how
+/// many were updated. Locked tasks are skipped. The filter must set at least one field,
+/// so an empty one can't update everything by accident.
+pub(crate) async fn bulk_update_where_impl(
+    pool: &SqlitePool,
+    filter: TaskFilter,
+    changes: UpdateTaskInput,
+) -> Result<u64, AppError> {
+    if filter.is_empty() {
+        return Err(AppError::ValidationError(
+            "Bulk updates need at least one filter field".to_string(),
+        ));
+    }
+    if changes.parent_id.is_some() || changes.position.is_some() {
+        return Err(AppError::ValidationError(
+            "Parent and position cannot be bulk-updated".to_string(),
+        ));
+    }
+    if let Some(ref title) = changes.title {
+        validate_task_title(title)?;
+    }
+    let priority = changes
+        .priority
+        .as_deref()
+        .map(parse_priority)
+        .transpose()?;
+    validate_effort(changes.effort.flatten())?;
+    validate_reminder_offset(changes.reminder_offset_minutes.flatten())?;
+    validate_task_color(changes.color.as_ref().and_then(Option::as_deref))?;
+
+    let (action, detail) = describe_update(&changes);
+    if detail.is_none() {
+        return Err(AppError::ValidationError("No changes given".to_string()));
+    }
+    let now = chrono::Utc::now().timestamp();
+
+    let mut builder = QueryBuilder::new("UPDATE tasks SET ");
+    push_task_changes(&mut builder, changes, priority, now);
+    let separator = push_task_filter(&mut builder, &filter);
+    builder.push(separator);
+    builder.push("is_locked = 0 RETURNING id");
+
+    let mut tx = pool.begin().await?;
+    let ids: Vec<i64> = timed(
+        "bulk_update_where",
+        builder.build_query_scalar().fetch_all(&mut *tx),
+    )
+    .await?;
+    for &id in &ids {
+        log_activity(&mut *tx, id, action, detail.as_deref()).await?;
+    }
+    tx.commit().await?;
+
+    Ok(ids.len() as u64)
+}
+
+#[tauri::command]
+pub async fn bulk_update_where(
+    pool: State<'_, SqlitePool>,
+    filter: TaskFilter,
+    changes: UpdateTaskInput,
+) -> Result<u64, AppError> {
+    with_retry(|| bulk_update_where_impl(pool.inner(), filter.clone(), changes.clone())).await
+}
+
+/// Marks task `id` done and creates `followup` in one transaction, so either both
+/// happen or neither does. The follow-up's parent and category default to the
+/// completed task's.
+pub(crate) async fn complete_and_add_followup_impl(
+    pool: &SqlitePool,
+    id: i64,
+    followup: CreateTaskInput,
+) -> Result<CompletedWithFollowup, AppError> {
+    validate_task_fields(&followup)?;
+
+    let mut tx = pool.begin().await?;
+    ensure_unlocked(&mut *tx, id, false).await?;
+
+    let now = chrono::Utc::now().timestamp();
+    let completed = sqlx::query_as::<_, Task>(
+        "UPDATE tasks SET is_done = 1, completed_at = ?, updated_at = ? WHERE id = ? RETURNING *",
+    )
+    .bind(now)
+    .bind(now)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+    log_activity(&mut *tx, id, "completed", Some("is_done")).await?;
+
+    let input = CreateTaskInput {
+        parent_id: followup.parent_id.or(completed.parent_id),
+        category_id: followup.category_id.or(completed.category_id),
+        ..followup
+    };
+    let followup = insert_task(&mut tx, &input).await?;
+
+    tx.commit().await?;
+
+    Ok(CompletedWithFollowup {
+        completed,
+        followup,
+    })
+}
+
+#[tauri::command]
+pub async fn complete_and_add_followup(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    followup: CreateTaskInput,
+) -> Result<CompletedWithFollowup, AppError> {
+    with_retry(|| complete_and_add_followup_impl(pool.inner(), id, followup.clone())).await
+}
+
+/// Flips `is_done` on task `id`, setting or clearing `completed_at` to match.
+///
+/// A single `UPDATE`, so checkbox toggles skip the dynamic builder `update_task` uses.
+/// Locked tasks are refused. The flip and its activity entry commit together, so a
+/// retried toggle never flips the task twice.
+pub(crate) async fn toggle_task_done_impl(pool: &SqlitePool, id: i64) -> Result<Task, AppError> {
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
+
+    // The right-hand sides all see the row's old values
+    let task = sqlx::query_as::<_, Task>(
+        r#"
+        UPDATE tasks
+        SET is_done = NOT is_done,
+            completed_at = CASE WHEN is_done THEN NULL ELSE ? END,
+            updated_at = ?
+        WHERE id = ? AND is_locked = 0
+        RETURNING *
+        "#,
+    )
+    .bind(now)
+    .bind(now)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(task) = task else {
+        // Only pay for the second lookup to tell a locked task from a missing one
+        ensure_unlocked(&mut *tx, id, false).await?;
+        return Err(AppError::NotFound(format!("Task {} not found", id)));
+    };
+
+    let action = if task.is_done {
+        "completed"
+    } else {
+        "reopened"
+    };
+    log_activity(&mut *tx, id, action, Some("is_done")).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn toggle_task_done(pool: State<'_, SqlitePool>, id: i64) -> Result<Task, AppError> {
+    with_retry(|| toggle_task_done_impl(pool.inner(), id)).await
+}
+
+pub(crate) async fn delete_task_impl(
+    pool: &SqlitePool,
+    id: i64,
+    force: bool,
+    strategy: DeleteStrategy,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+    ensure_unlocked(&mut *tx, id, force).await?;
+
+    let deleted = match strategy {
+        DeleteStrategy::Reparent => promote_children(&mut tx, id).await?,
+        DeleteStrategy::Cascade | DeleteStrategy::Block => {
+            if strategy == DeleteStrategy::Block {
+                let has_children: bool =
+                    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM tasks WHERE parent_id = ?)")
+                        .bind(id)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                if has_children {
+                    return Err(AppError::ValidationError("task has subtasks".to_string()));
+                }
+            }
+            sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+        }
+    };
+
+    // Deleting is recoverable: the task and whatever would cascade with it go to the trash
+    move_to_trash(&mut tx, id).await?;
+
+    let result = timed(
+        "delete_task",
+        sqlx::query("DELETE FROM tasks WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx),
+    )
+    .await?;
+
+    if result.rows_affected() > 0 {
+        log_activity(&mut *tx, id, "deleted", None).await?;
+    }
+
+    // Close the gap the deleted task leaves, so positions stay contiguous among the
+    // tasks still visible and new siblings are numbered from the visible count.
+    // Renumbered rather than shifted, since fractional positions would collide.
+    if let Some(task) = deleted {
+        normalize_group(&mut tx, task.parent_id, task.category_id).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// Move a task's direct children up to its parent (or the root), appended in their
+// current order, so deleting the task doesn't cascade to them. Returns the task, if found.
+async fn promote_children(conn: &mut SqliteConnection, id: i64) -> Result<Option<Task>, AppError> {
+    let Some(task) = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let children: Vec<Task> =
+        sqlx::query_as("SELECT * FROM tasks WHERE parent_id = ? ORDER BY position ASC, id ASC")
+            .bind(id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+    let now = chrono::Utc::now().timestamp();
+    for child in &children {
+        let position = get_next_position(&mut *conn, task.parent_id, child.category_id).await?;
+        sqlx::query("UPDATE tasks SET parent_id = ?, position = ?, updated_at = ? WHERE id = ?")
+            .bind(task.parent_id)
+            .bind(position)
+            .bind(now)
+            .bind(child.id)
+            .execute(&mut *conn)
+            .await?;
+        log_activity(&mut *conn, child.id, "moved", Some("parent deleted")).await?;
+    }
+
+    Ok(Some(task))
+}
+
+/// Moves a task to the trash, from where `restore_from_trash` can bring it back. Locked
+/// tasks are only deleted when `force` is true. `strategy` decides what happens to
+/// subtasks, and defaults to deleting them along with their parent.
+#[tauri::command]
+pub async fn delete_task(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    force: Option<bool>,
+    strategy: Option<DeleteStrategy>,
+) -> Result<(), AppError> {
+    with_retry(|| {
+        delete_task_impl(
+            pool.inner(),
+            id,
+            force.unwrap_or(false),
+            strategy.unwrap_or_default(),
+        )
+    })
+    .await
+}
+
+pub(crate) async fn reorder_task_impl(
+    pool: &SqlitePool,
+    id: i64,
+    new_position: i32,
+    force: bool,
+) -> Result<(), AppError> {
+    // Everything from reading the current position to the write happens in one
+    // transaction, so a concurrent reorder of the same group can't interleave
+    let mut tx = pool.begin().await?;
+
+    // Get the task to know its parent and category
+    let task: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if task.is_locked && !force {
+        return Err(AppError::ValidationError("task is locked".to_string()));
+    }
+
+    // `new_position` is an index among the other siblings; past either end lands at
+    // that end. The task's own rank is how many siblings sort before it.
+    let (siblings, rank): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), COALESCE(SUM(position < ?), 0)
+        FROM tasks
+        WHERE parent_id IS ? AND category_id IS ? AND id != ?
+        "#,
+    )
+    .bind(task.position)
+    .bind(task.parent_id)
+    .bind(task.category_id)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let index = i64::from(new_position.max(0)).min(siblings);
+
+    if index == rank {
+        return Ok(());
+    }
+
+    let old_position = task.position;
+    let new_position = match position_at_index(&mut tx, &task, index).await? {
+        Some(position) => position,
+        None => {
+            log::info!(
+                "positions under parent {:?} in category {:?} ran out of precision, rebalancing",
+                task.parent_id,
+                task.category_id
+            );
+            normalize_group(&mut tx, task.parent_id, task.category_id).await?;
+            position_at_index(&mut tx, &task, index)
+                .await?
+                .expect("a normalized group has room at every index")
+        }
+    };
+
+    // Only the moved task is rewritten
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query("UPDATE tasks SET position = ?, updated_at = ? WHERE id = ?")
+        .bind(new_position)
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    let detail = format!("position {} -> {}", old_position, new_position);
+    log_activity(&mut *tx, id, "moved", Some(&detail)).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Locked tasks are only moved when `force` is true.
+#[tauri::command]
+pub async fn reorder_task(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    new_position: i32,
+    force: Option<bool>,
+) -> Result<(), AppError> {
+    with_retry(|| reorder_task_impl(pool.inner(), id, new_position, force.unwrap_or(false))).await
+}
+
+// Lowest or highest position among the other tasks in `task`'s sibling group
+async fn sibling_end(
+    conn: &mut SqliteConnection,
+    task: &Task,
+    lowest: bool,
+) -> Result<Option<f64>, AppError> {
+    let query = if lowest {
+        "SELECT MIN(position) FROM tasks WHERE parent_id IS ? AND category_id IS ? AND id != ?"
+    } else {
+        "SELECT MAX(position) FROM tasks WHERE parent_id IS ? AND category_id IS ? AND id != ?"
+    };
+    let (end,): (Option<f64>,) = sqlx::query_as(query)
+        .bind(task.parent_id)
+        .bind(task.category_id)
+        .bind(task.id)
+        .fetch_one(conn)
+        .await?;
+
+    Ok(end)
+}
+
+// Moves task `id` one step past the first or last of its siblings, rewriting only
+// that task. The group is renumbered first if the new end would lose precision.
+async fn move_task_to_end(pool: &SqlitePool, id: i64, to_top: bool) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    let task: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if task.is_locked {
+        return Err(AppError::ValidationError("task is locked".to_string()));
+    }
+
+    let step = |end: f64| if to_top { end - 1.0 } else { end + 1.0 };
+
+    // No siblings means the task is already at both ends
+    let Some(end) = sibling_end(&mut tx, &task, to_top).await? else {
+        return Ok(());
+    };
+    if (to_top && task.position < end) || (!to_top && task.position > end) {
+        return Ok(());
+    }
+
+    let mut new_position = step(end);
+    if new_position.abs() >= MAX_EXACT_POSITION {
+        log::warn!(
+            "positions under parent {:?} in category {:?} reached 2^53, normalizing",
+            task.parent_id,
+            task.category_id
+        );
+        normalize_group(&mut tx, task.parent_id, task.category_id).await?;
+        let end = sibling_end(&mut tx, &task, to_top)
+            .await?
+            .expect("the group still has siblings");
+        new_position = step(end);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query("UPDATE tasks SET position = ?, updated_at = ? WHERE id = ?")
+        .bind(new_position)
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    let detail = format!("position {} -> {}", task.position, new_position);
+    log_activity(&mut *tx, id, "moved", Some(&detail)).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Moves task `id` to the front of its sibling group. Locked tasks stay put.
+pub(crate) async fn move_task_to_top_impl(pool: &SqlitePool, id: i64) -> Result<(), AppError> {
+    move_task_to_end(pool, id, true).await
+}
+
+#[tauri::command]
+pub async fn move_task_to_top(pool: State<'_, SqlitePool>, id: i64) -> Result<(), AppError> {
+    with_retry(|| move_task_to_top_impl(pool.inner(), id)).await
+}
+
+/// Moves task `id` to the end of its sibling group. Locked tasks stay put.
+pub(crate) async fn move_task_to_bottom_impl(pool: &SqlitePool, id: i64) -> Result<(), AppError> {
+    move_task_to_end(pool, id, false).await
+}
+
+#[tauri::command]
+pub async fn move_task_to_bottom(pool: State<'_, SqlitePool>, id: i64) -> Result<(), AppError> {
+    with_retry(|| move_task_to_bottom_impl(pool.inner(), id)).await
+}
+
+// Below this two neighbours are too close to keep splitting, so the group is respaced
+const MIN_POSITION_GAP: f64 = 1e-9;
+
+// Sibling of the task being moved that serves as a `move_between` anchor
+async fn fetch_anchor(conn: &mut SqliteConnection, task: &Task, id: i64) -> Result<f64, AppError> {
+    let anchor = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+
+    if anchor.id == task.id {
+        return Err(AppError::ValidationError(
+            "A task can't be moved next to itself".to_string(),
+        ));
+    }
+    if anchor.parent_id != task.parent_id || anchor.category_id != task.category_id {
+        return Err(AppError::ValidationError(format!(
+            "Task {} is not a sibling of task {}",
+            id, task.id
+        )));
+    }
+
+    Ok(anchor.position)
+}
+
+// The nearest sibling position on one side of `position`, ignoring the task being moved
+async fn neighbour_position(
+    conn: &mut SqliteConnection,
+    task: &Task,
+    position: f64,
+    following: bool,
+) -> Result<Option<f64>, AppError> {
+    let sql = if following {
+        "SELECT MIN(position) FROM tasks WHERE parent_id IS ? AND category_id IS ? AND id != ? AND position > ?"
+    } else {
+        "SELECT MAX(position) FROM tasks WHERE parent_id IS ? AND category_id IS ? AND id != ? AND position < ?"
+    };
+    let (neighbour,): (Option<f64>,) = sqlx::query_as(sql)
+        .bind(task.parent_id)
+        .bind(task.category_id)
+        .bind(task.id)
+        .bind(position)
+        .fetch_one(conn)
+        .await?;
+
+    Ok(neighbour)
+}
+
+// Position strictly between the anchors, or `None` when they are too close to split
+async fn position_between(
+    conn: &mut SqliteConnection,
+    task: &Task,
+    before_id: Option<i64>,
+    after_id: Option<i64>,
+) -> Result<Option<f64>, AppError> {
+    let before = match before_id {
+        Some(before_id) => Some(fetch_anchor(&mut *conn, task, before_id).await?),
+        None => None,
+    };
+    let after = match after_id {
+        Some(after_id) => Some(fetch_anchor(&mut *conn, task, after_id).await?),
+        None => None,
+    };
+
+    // A missing anchor is the neighbour on that side, if there is one
+    let (before, after) = match (before, after) {
+        (Some(before), Some(after)) => (before, after),
+        (Some(before), None) => match neighbour_position(&mut *conn, task, before, true).await? {
+            Some(after) => (before, after),
+            None => return Ok(Some(before + 1.0)),
+        },
+        (None, Some(after)) => match neighbour_position(&mut *conn, task, after, false).await? {
+            Some(before) => (before, after),
+            None => return Ok(Some(after - 1.0)),
+        },
+        (None, None) => {
+            return Err(AppError::ValidationError(
+                "Either before_id or after_id is required".to_string(),
+            ))
+        }
+    };
+
+    if before > after {
+        return Err(AppError::ValidationError(format!(
+            "Task {} comes after task {}",
+            before_id.unwrap_or_default(),
+            after_id.unwrap_or_default()
+        )));
+    }
+
+    Ok(midpoint(before, after))
+}
+
+// Position halfway between two siblings, or `None` when they are too close to split
+fn midpoint(before: f64, after: f64) -> Option<f64> {
+    let midpoint = before + (after - before) / 2.0;
+    if after - before < MIN_POSITION_GAP || midpoint <= before || midpoint >= after {
+        return None;
+    }
+    Some(midpoint)
+}
+
+// Position that puts `task` at `index` among its other siblings: between the two that
+// will surround it, or one past the end it moves to. `None` when those neighbours are
+// too close to split or the new end would leave the exactly representable range.
+async fn position_at_index(
+    conn: &mut SqliteConnection,
+    task: &Task,
+    index: i64,
+) -> Result<Option<f64>, AppError> {
+    let neighbours: Vec<(f64,)> = sqlx::query_as(
+        r#"
+        SELECT position FROM tasks
+        WHERE parent_id IS ? AND category_id IS ? AND id != ?
+        ORDER BY position ASC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(task.parent_id)
+    .bind(task.category_id)
+    .bind(task.id)
+    .bind(if index == 0 { 1 } else { 2 })
+    .bind((index - 1).max(0))
+    .fetch_all(conn)
+    .await?;
+    let neighbours: Vec<f64> = neighbours.into_iter().map(|(position,)| position).collect();
+
+    let position = match (index, neighbours.as_slice()) {
+        (0, [after]) => after - 1.0,
+        (_, [before]) => before + 1.0,
+        (_, [before, after]) => return Ok(midpoint(*before, *after)),
+        _ => task.position,
+    };
+    Ok((position.abs() < MAX_EXACT_POSITION).then_some(position))
+}
+
+/// Moves task `id` between two of its siblings by giving it the midpoint of their
+/// positions, so no other task is rewritten. `before_id` is the sibling that should end
+/// up just before it and `after_id` the one just after; either may be omitted to use
+/// that anchor's neighbour, or the end of the group when there is none.
+///
+/// When the anchors are too close to split the group is renumbered to 0..n first.
+pub(crate) async fn move_between_impl(
+    pool: &SqlitePool,
+    id: i64,
+    before_id: Option<i64>,
+    after_id: Option<i64>,
+) -> Result<Task, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+    ensure_unlocked(&mut *tx, id, false).await?;
+
+    let position = match position_between(&mut tx, &task, before_id, after_id).await? {
+        Some(position) => position,
+        None => {
+            log::info!(
+                "positions under parent {:?} in category {:?} ran out of precision, rebalancing",
+                task.parent_id,
+                task.category_id
+            );
+            normalize_group(&mut tx, task.parent_id, task.category_id).await?;
+            position_between(&mut tx, &task, before_id, after_id)
+                .await?
+                .ok_or_else(|| {
+                    AppError::ValidationError("before_id and after_id must differ".to_string())
+                })?
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let moved = sqlx::query_as::<_, Task>(
+        "UPDATE tasks SET position = ?, updated_at = ? WHERE id = ? RETURNING *",
+    )
+    .bind(position)
+    .bind(now)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let detail = format!("position {} -> {}", task.position, position);
+    log_activity(&mut *tx, id, "moved", Some(&detail)).await?;
+
+    tx.commit().await?;
+
+    Ok(moved)
+}
+
+#[tauri::command]
+pub async fn move_between(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    before_id: Option<i64>,
+    after_id: Option<i64>,
+) -> Result<Task, AppError> {
+    with_retry(|| move_between_impl(pool.inner(), id, before_id, after_id)).await
+}
+
+/// Locks or unlocks a task; never blocked by the current lock state.
+pub(crate) async fn set_locked_impl(
+    pool: &SqlitePool,
+    id: i64,
+    locked: bool,
+) -> Result<Task, AppError> {
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
+
+    let task = sqlx::query_as::<_, Task>(
+        "UPDATE tasks SET is_locked = ?, updated_at = ? WHERE id = ? RETURNING *",
+    )
+    .bind(locked)
+    .bind(now)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+
+    let action = if locked { "locked" } else { "unlocked" };
+    log_activity(&mut *tx, id, action, None).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn set_locked(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    locked: bool,
+) -> Result<Task, AppError> {
+    with_retry(|| set_locked_impl(pool.inner(), id, locked)).await
+}
+
+/// Moves task `id` under `new_parent_id` (or to the root), appended to the end of its
+/// new sibling group unless `position` gives the index to insert it at. Indexes past
+/// the end append.
+pub(crate) async fn set_parent_impl(
+    pool: &SqlitePool,
+    id: i64,
+    new_parent_id: Option<i64>,
+    position: Option<i32>,
+) -> Result<Task, AppError> {
+    if new_parent_id == Some(id) {
+        return Err(AppError::ValidationError(
+            "A task cannot be its own parent".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    ensure_unlocked(&mut *tx, id, false).await?;
+
+    let task: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    if task.parent_id == new_parent_id {
+        return Ok(task);
+    }
+
+    // Subtasks follow their parent's category so they stay in the same sibling group
+    let category_id = match new_parent_id {
+        Some(parent_id) => {
+            let parent: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
+                .bind(parent_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| {
+                    AppError::NotFound(format!("Parent task {} not found", parent_id))
+                })?;
+
+            if is_ancestor_or_self(&mut tx, id, parent_id).await? {
+                return Err(AppError::ValidationError(
+                    "Cannot move a task under one of its own subtasks".to_string(),
+                ));
+            }
+
+            parent.category_id
+        }
+        None => task.category_id,
+    };
+
+    let position = match position {
+        Some(index) => {
+            // Lay the new group out as 0..n so the index is a position, then make room
+            normalize_group(&mut tx, new_parent_id, category_id).await?;
+            let count = max_position(&mut tx, new_parent_id, category_id)
+                .await?
+                .map_or(0.0, |max| max + 1.0);
+            let position = f64::from(index.max(0)).min(count);
+            sqlx::query(
+                r#"
+                UPDATE tasks
+                SET position = position + 1
+                WHERE parent_id IS ?
+                AND category_id IS ?
+                AND position >= ?
+                "#,
+            )
+            .bind(new_parent_id)
+            .bind(category_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+            position
+        }
+        // Append to the end of the new sibling group
+        None => get_next_position(&mut tx, new_parent_id, category_id).await?,
+    };
+    let now = chrono::Utc::now().timestamp();
+
+    sqlx::query(
+        r#"
+        UPDATE tasks
+        SET parent_id = ?, category_id = ?, position = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(new_parent_id)
+    .bind(category_id)
+    .bind(position)
+    .bind(now)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    // The rest of the subtree follows too. Each of its sibling groups moves whole, so
+    // their positions are unchanged.
+    if category_id != task.category_id {
+        sqlx::query(
+            r#"
+            WITH root AS (SELECT path FROM tasks WHERE id = ?)
+            UPDATE tasks
+            SET category_id = ?, updated_at = ?
+            WHERE id != ?
+            AND substr(path, 1, length((SELECT path FROM root))) = (SELECT path FROM root)
+            "#,
+        )
+        .bind(id)
+        .bind(category_id)
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Close the gap left in the old sibling group
+    normalize_group(&mut tx, task.parent_id, task.category_id).await?;
+    let moved = fetch_task(&mut *tx, id).await?;
+
+    let detail = match new_parent_id {
+        Some(parent_id) => format!("parent -> {}", parent_id),
+        None => "parent -> root".to_string(),
+    };
+    log_activity(&mut *tx, id, "moved", Some(&detail)).await?;
+
+    tx.commit().await?;
+
+    Ok(moved)
+}
+
+#[tauri::command]
+pub async fn set_parent(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+    new_parent_id: Option<i64>,
+    position: Option<i32>,
+) -> Result<Task, AppError> {
+    with_retry(|| set_parent_impl(pool.inner(), id, new_parent_id, position)).await
+}
+
+/// Copies task `id` without its subtasks, as a sibling placed right after it. The copy
+/// starts out open and unlocked, and doesn't belong to the original's series.
+pub(crate) async fn duplicate_task_shallow_impl(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<Task, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let original = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+
+    // Lay the group out as 0..n first so there is a whole slot to open up after the original
+    normalize_group(&mut tx, original.parent_id, original.category_id).await?;
+    let original = fetch_task(&mut *tx, id).await?;
+    sqlx::query(
+        r#"
+        UPDATE tasks
+        SET position = position + 1
+        WHERE parent_id IS ?
+        AND category_id IS ?
+        AND position > ?
+        "#,
+    )
+    .bind(original.parent_id)
+    .bind(original.category_id)
+    .bind(original.position)
+    .execute(&mut *tx)
+    .await?;
+
+    let now = chrono::Utc::now().timestamp();
+    let copy_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO tasks (title, description, category_id, priority, parent_id, position, due_date, effort, reminder_offset_minutes, color, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(&original.title)
+    .bind(&original.description)
+    .bind(original.category_id)
+    .bind(original.priority)
+    .bind(original.parent_id)
+    .bind(original.position + 1.0)
+    .bind(original.due_date)
+    .bind(original.effort)
+    .bind(original.reminder_offset_minutes)
+    .bind(&original.color)
+    .bind(now)
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await?;
+    let copy = fetch_task(&mut *tx, copy_id).await?;
+
+    let detail = format!("duplicate of {}", id);
+    log_activity(&mut *tx, copy_id, "created", Some(&detail)).await?;
+
+    tx.commit().await?;
+
+    Ok(copy)
+}
+
+#[tauri::command]
+pub async fn duplicate_task_shallow(
+    pool: State<'_, SqlitePool>,
+    id: i64,
+) -> Result<Task, AppError> {
+    with_retry(|| duplicate_task_shallow_impl(pool.inner(), id)).await
+}
+
+pub(crate) async fn merge_tasks_impl(
+    pool: &SqlitePool,
+    keep_id: i64,
+    merge_id: i64,
+) -> Result<Task, AppError> {
+    if keep_id == merge_id {
+        return Err(AppError::ValidationError(
+            "Cannot merge a task into itself".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    ensure_unlocked(&mut *tx, keep_id, false).await?;
+    ensure_unlocked(&mut *tx, merge_id, false).await?;
+
+    let keep: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
+        .bind(keep_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let merge: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
+        .bind(merge_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    // Deleting the merged task would cascade to the kept one
+    if is_ancestor_or_self(&mut tx, merge_id, keep_id).await? {
+        return Err(AppError::ValidationError(
+            "Cannot merge a task into one of its own subtasks".to_string(),
+        ));
+    }
+
+    // Move the merged task's children to the end of the kept task's subtasks
+    let children: Vec<Task> =
+        sqlx::query_as("SELECT * FROM tasks WHERE parent_id = ? ORDER BY position ASC, id ASC")
+            .bind(merge_id)
+            .fetch_all(&mut *tx)
+            .await?;
+    for child in &children {
+        let position = get_next_position(&mut tx, Some(keep_id), child.category_id).await?;
+        sqlx::query("UPDATE tasks SET parent_id = ?, position = ? WHERE id = ?")
+            .bind(keep_id)
+            .bind(position)
+            .bind(child.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let description = match (keep.description, merge.description) {
+        (Some(kept), Some(merged)) => Some(format!("{}\n\n{}", kept, merged)),
+        (kept, merged) => kept.or(merged),
+    };
+
+    // Like a delete, the merged task can be restored from the trash
+    move_to_trash(&mut tx, merge_id).await?;
+    sqlx::query("DELETE FROM tasks WHERE id = ?")
+        .bind(merge_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // Close the gap the merged task leaves among its siblings
+    normalize_group(&mut tx, merge.parent_id, merge.category_id).await?;
+
+    let now = chrono::Utc::now().timestamp();
+    let kept = sqlx::query_as::<_, Task>(
+        "UPDATE tasks SET description = ?, updated_at = ? WHERE id = ? RETURNING *",
+    )
+    .bind(description)
+    .bind(now)
+    .bind(keep_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let detail = format!("merged task {}", merge_id);
+    log_activity(&mut *tx, keep_id, "updated", Some(&detail)).await?;
+    let detail = format!("merged into task {}", keep_id);
+    log_activity(&mut *tx, merge_id, "deleted", Some(&detail)).await?;
+
+    tx.commit().await?;
+
+    Ok(kept)
+}
+
+#[tauri::command]
+pub async fn merge_tasks(
+    pool: State<'_, SqlitePool>,
+    keep_id: i64,
+    merge_id: i64,
+) -> Result<Task, AppError> {
+    with_retry(|| merge_tasks_impl(pool.inner(), keep_id, merge_id)).await
+}
+
+pub(crate) async fn defer_overdue_to_impl(
+    pool: &SqlitePool,
+    new_due_date: i64,
+) -> Result<u64, AppError> {
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
+
+    // Completed and locked tasks keep their original due date
+    let deferred: Vec<(i64,)> = sqlx::query_as(
+        r#"
+        UPDATE tasks
+        SET due_date = ?, updated_at = ?
+        WHERE is_done = 0
+        AND is_locked = 0
+        AND due_date < ?
+        RETURNING id
+        "#,
+    )
+    .bind(new_due_date)
+    .bind(now)
+    .bind(now)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for (id,) in &deferred {
+        log_activity(&mut *tx, *id, "updated", Some("due_date")).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(deferred.len() as u64)
+}
+
+#[tauri::command]
+pub async fn defer_overdue_to(
+    pool: State<'_, SqlitePool>,
+    new_due_date: i64,
+) -> Result<u64, AppError> {
+    with_retry(|| defer_overdue_to_impl(pool.inner(), new_due_date)).await
+}
+
+/// Sets `priority` on every task in `ids` and returns how many were updated. Locked
+/// tasks are skipped.
+pub(crate) async fn set_tasks_priority_impl(
+    pool: &SqlitePool,
+    ids: &[i64],
+    priority: &str,
+) -> Result<u64, AppError> {
+    let priority = parse_priority(priority)?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
+
+    let mut builder = QueryBuilder::<Sqlite>::new("UPDATE tasks SET priority = ");
+    builder.push_bind(priority);
+    builder.push(", updated_at = ");
+    builder.push_bind(now);
+    builder.push(" WHERE id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
+    }
+    separated.push_unseparated(") AND is_locked = 0 RETURNING id");
+
+    let updated: Vec<(i64,)> = builder.build_query_as().fetch_all(&mut *tx).await?;
+
+    for (id,) in &updated {
+        log_activity(&mut *tx, *id, "updated", Some("priority")).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(updated.len() as u64)
+}
+
+#[tauri::command]
+pub async fn set_tasks_priority(
+    pool: State<'_, SqlitePool>,
+    ids: Vec<i64>,
+    priority: String,
+) -> Result<u64, AppError> {
+    with_retry(|| set_tasks_priority_impl(pool.inner(), &ids, &priority)).await
+}
+
+/// Moves every task with priority `from` to `to`, e.g. to fold Urgent into High.
+/// Locked tasks keep their priority.
+pub(crate) async fn remap_priority_impl(
     pool: &SqlitePool,
-    parent_id: Option<i64>,
-    category_id: Option<i64>,
-) -> Result<i32, AppError> {
-    let result: Option<(i32,)> = sqlx::query_as(
-        r#"
-        SELECT COALESCE(MAX(position), -1) + 1 as next_pos
-        FROM tasks
-        WHERE parent_id IS ? AND category_id IS ?
-        "#,
+    from: &str,
+    to: &str,
+) -> Result<u64, AppError> {
+    let from = parse_priority(from)?;
+    let to = parse_priority(to)?;
+    if from == to {
+        return Ok(0);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
+
+    let updated: Vec<(i64,)> = sqlx::query_as(
+        "UPDATE tasks SET priority = ?, updated_at = ? WHERE priority = ? AND is_locked = 0 RETURNING id",
     )
-    .bind(parent_id)
-    .bind(category_id)
-    .fetch_optional(pool)
+    .bind(to)
+    .bind(now)
+    .bind(from)
+    .fetch_all(&mut *tx)
     .await?;
 
-    Ok(result.map(|r| r.0).unwrap_or(0))
+    for (id,) in &updated {
+        log_activity(&mut *tx, *id, "updated", Some("priority")).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(updated.len() as u64)
 }
 
 #[tauri::command]
-pub async fn create_task(
+pub async fn remap_priority(
     pool: State<'_, SqlitePool>,
-    input: CreateTaskInput,
-) -> Result<Task, AppError> {
-    // Validate input
-    validate_task_title(&input.title)?;
+    from: String,
+    to: String,
+) -> Result<u64, AppError> {
+    with_retry(|| remap_priority_impl(pool.inner(), &from, &to)).await
+}
 
-    let now = chrono::Utc::now().timestamp();
-    let position = get_next_position(&pool, input.parent_id, input.category_id).await?;
-    let title_trimmed = input.title.trim();
+// Largest feed the UI can ask for in one call
+const MAX_FEED_LIMIT: i64 = 100;
 
-    let task = sqlx::query_as::<_, Task>(
+/// Completed tasks, newest completion first. `limit` is clamped to `1..=100`.
+pub(crate) async fn get_recently_completed_impl(
+    pool: &SqlitePool,
+    limit: i64,
+) -> Result<Vec<Task>, AppError> {
+    let limit = limit.clamp(1, MAX_FEED_LIMIT);
+
+    let tasks = sqlx::query_as::<_, Task>(
         r#"
-        INSERT INTO tasks (title, description, category_id, priority, parent_id, position, due_date, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-        RETURNING *
+        SELECT * FROM tasks
+        WHERE is_done = 1 AND completed_at IS NOT NULL
+        ORDER BY completed_at DESC, id DESC
+        LIMIT ?
         "#,
     )
-    .bind(title_trimmed)
-    .bind(&input.description)
-    .bind(input.category_id)
-    .bind(&input.priority)
-    .bind(input.parent_id)
-    .bind(position)
-    .bind(input.due_date)
-    .bind(now)
-    .bind(now)
-    .fetch_one(pool.inner())
+    .bind(limit)
+    .fetch_all(pool)
     .await?;
 
-    Ok(task)
+    Ok(tasks)
 }
 
 #[tauri::command]
-pub async fn get_all_tasks(pool: State<'_, SqlitePool>) -> Result<Vec<Task>, AppError> {
-    let tasks = sqlx::query_as::<_, Task>("SELECT * FROM tasks ORDER BY position ASC")
-        .fetch_all(pool.inner())
-        .await?;
+pub async fn get_recently_completed(
+    pool: State<'_, SqlitePool>,
+    limit: i64,
+) -> Result<Vec<Task>, AppError> {
+    get_recently_completed_impl(pool.inner(), limit).await
+}
 
-    Ok(tasks)
+/// The next `limit` tasks after `after_id` in id order, for stable infinite scroll.
+///
+/// Unlike offset paging, rows inserted or deleted between calls never shift the
+/// boundary, so pages neither skip nor repeat tasks.
+pub(crate) async fn get_tasks_after_impl(
+    pool: &SqlitePool,
+    after_id: Option<i64>,
+    limit: i64,
+) -> Result<TaskPage, AppError> {
+    let limit = limit.clamp(1, MAX_FEED_LIMIT);
+
+    // Fetch one extra row to learn whether another page follows
+    let mut items =
+        sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id > ? ORDER BY id ASC LIMIT ?")
+            .bind(after_id.unwrap_or(0))
+            .bind(limit + 1)
+            .fetch_all(pool)
+            .await?;
+
+    let has_more = items.len() as i64 > limit;
+    items.truncate(limit as usize);
+    let next_cursor = if has_more {
+        items.last().map(|task| task.id)
+    } else {
+        None
+    };
+
+    Ok(TaskPage { items, next_cursor })
 }
 
 #[tauri::command]
-pub async fn get_task_tree(pool: State<'_, SqlitePool>) -> Result<Vec<TaskTree>, AppError> {
-    let tasks = get_all_tasks(pool).await?;
-    Ok(build_task_tree(tasks))
+pub async fn get_tasks_after(
+    pool: State<'_, SqlitePool>,
+    after_id: Option<i64>,
+    limit: i64,
+) -> Result<TaskPage, AppError> {
+    get_tasks_after_impl(pool.inner(), after_id, limit).await
 }
 
+/// Event carrying one batch of tasks from `stream_all_tasks`.
+pub const TASKS_CHUNK_EVENT: &str = "tasks-chunk";
+/// Event sent once `stream_all_tasks` has delivered every batch, carrying the total.
+pub const TASKS_DONE_EVENT: &str = "tasks-done";
+
+// Page through every task in id order, handing each page of up to `chunk_size` tasks
+// to `on_chunk`. Returns how many tasks were delivered.
+pub(crate) async fn stream_all_tasks_impl(
+    pool: &SqlitePool,
+    chunk_size: i64,
+    mut on_chunk: impl FnMut(Vec<Task>) -> Result<(), AppError>,
+) -> Result<u64, AppError> {
+    let mut delivered = 0;
+    let mut after_id = None;
+    loop {
+        let page = get_tasks_after_impl(pool, after_id, chunk_size).await?;
+        if !page.items.is_empty() {
+            delivered += page.items.len() as u64;
+            on_chunk(page.items)?;
+        }
+        match page.next_cursor {
+            Some(cursor) => after_id = Some(cursor),
+            None => return Ok(delivered),
+        }
+    }
+}
+
+/// Sends every task to the frontend as a series of `tasks-chunk` events, so a large
+/// list can render while it loads, then a `tasks-done` event with the total.
+/// `chunk_size` is capped like `get_tasks_after`'s limit.
 #[tauri::command]
-pub async fn update_task(
+pub async fn stream_all_tasks(
+    app: AppHandle,
     pool: State<'_, SqlitePool>,
-    id: i64,
-    input: UpdateTaskInput,
-) -> Result<Task, AppError> {
-    // Validate title if provided
-    if let Some(ref title) = input.title {
-        validate_task_title(title)?;
-    }
+    chunk_size: i64,
+) -> Result<u64, AppError> {
+    let emit_failed =
+        |e: tauri::Error| AppError::DatabaseError(format!("Failed to emit tasks: {}", e));
+    let total = stream_all_tasks_impl(pool.inner(), chunk_size, |chunk| {
+        app.emit(TASKS_CHUNK_EVENT, chunk).map_err(emit_failed)
+    })
+    .await?;
+    app.emit(TASKS_DONE_EVENT, total).map_err(emit_failed)?;
 
-    let now = chrono::Utc::now().timestamp();
+    Ok(total)
+}
 
-    // Use QueryBuilder for safe dynamic query construction
-    let mut builder = sqlx::QueryBuilder::new("UPDATE tasks SET updated_at = ");
-    builder.push_bind(now);
+/// Tasks written at or after `since`, most recent first. With `since` at the start of
+/// the local day this is the "today's activity" recap.
+pub(crate) async fn get_tasks_modified_since_impl(
+    pool: &SqlitePool,
+    since: i64,
+) -> Result<Vec<Task>, AppError> {
+    let tasks = sqlx::query_as::<_, Task>(
+        "SELECT * FROM tasks WHERE updated_at >= ? ORDER BY updated_at DESC, id DESC",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
 
-    if let Some(title) = input.title {
-        builder.push(", title = ");
-        builder.push_bind(title);
-    }
-    if let Some(description) = input.description {
-        builder.push(", description = ");
-        builder.push_bind(description);
-    }
-    if let Some(category_id) = input.category_id {
-        builder.push(", category_id = ");
-        builder.push_bind(category_id);
-    }
-    if let Some(priority) = input.priority {
-        builder.push(", priority = ");
-        builder.push_bind(priority);
-    }
-    if let Some(parent_id) = input.parent_id {
-        builder.push(", parent_id = ");
-        builder.push_bind(parent_id);
-    }
-    if let Some(position) = input.position {
-        builder.push(", position = ");
-        builder.push_bind(position);
-    }
-    if let Some(due_date) = input.due_date {
-        builder.push(", due_date = ");
-        builder.push_bind(due_date);
-    }
-    if let Some(is_done) = input.is_done {
-        builder.push(", is_done = ");
-        builder.push_bind(is_done);
-        if is_done {
-            builder.push(", completed_at = ");
-            builder.push_bind(now);
-        } else {
-            builder.push(", completed_at = NULL");
-        }
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_tasks_modified_since(
+    pool: State<'_, SqlitePool>,
+    since: i64,
+) -> Result<Vec<Task>, AppError> {
+    get_tasks_modified_since_impl(pool.inner(), since).await
+}
+
+/// Open tasks due between now and `days` from now, soonest first.
+pub(crate) async fn get_upcoming_tasks_impl(
+    pool: &SqlitePool,
+    days: i64,
+) -> Result<Vec<Task>, AppError> {
+    if days < 0 {
+        return Err(AppError::ValidationError(
+            "Days cannot be negative".to_string(),
+        ));
     }
 
-    builder.push(" WHERE id = ");
-    builder.push_bind(id);
-    builder.push(" RETURNING *");
+    let now = chrono::Utc::now().timestamp();
+    let until = now.saturating_add(days.saturating_mul(86_400));
 
-    let task = builder
-        .build_query_as::<Task>()
-        .fetch_one(pool.inner())
-        .await?;
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"
+        SELECT * FROM tasks
+        WHERE is_done = 0
+        AND due_date BETWEEN ? AND ?
+        ORDER BY due_date ASC, position ASC, id ASC
+        "#,
+    )
+    .bind(now)
+    .bind(until)
+    .fetch_all(pool)
+    .await?;
 
-    Ok(task)
+    Ok(tasks)
 }
 
 #[tauri::command]
-pub async fn delete_task(pool: State<'_, SqlitePool>, id: i64) -> Result<(), AppError> {
-    sqlx::query("DELETE FROM tasks WHERE id = ?")
-        .bind(id)
-        .execute(pool.inner())
-        .await?;
+pub async fn get_upcoming_tasks(
+    pool: State<'_, SqlitePool>,
+    days: i64,
+) -> Result<Vec<Task>, AppError> {
+    get_upcoming_tasks_impl(pool.inner(), days).await
+}
 
-    Ok(())
+/// Tasks due within [day_start, day_end], for a day picked on the calendar. The caller
+/// passes the local day's boundaries. Open tasks come first, then by priority and due time.
+pub(crate) async fn get_tasks_for_day_impl(
+    pool: &SqlitePool,
+    day_start: i64,
+    day_end: i64,
+) -> Result<Vec<Task>, AppError> {
+    if day_end < day_start {
+        return Err(AppError::ValidationError(
+            "Day end cannot be before day start".to_string(),
+        ));
+    }
+
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"
+        SELECT * FROM tasks
+        WHERE due_date BETWEEN ? AND ?
+        ORDER BY is_done ASC, priority ASC, due_date ASC, id ASC
+        "#,
+    )
+    .bind(day_start)
+    .bind(day_end)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tasks)
 }
 
 #[tauri::command]
-pub async fn reorder_task(
+pub async fn get_tasks_for_day(
     pool: State<'_, SqlitePool>,
-    id: i64,
-    new_position: i32,
-) -> Result<(), AppError> {
-    // Get the task to know its parent and category
-    let task: Task = sqlx::query_as("SELECT * FROM tasks WHERE id = ?")
-        .bind(id)
-        .fetch_one(pool.inner())
-        .await?;
+    day_start: i64,
+    day_end: i64,
+) -> Result<Vec<Task>, AppError> {
+    get_tasks_for_day_impl(pool.inner(), day_start, day_end).await
+}
 
-    let old_position = task.position;
+/// Tasks created within [from, to], oldest first, for the journal view.
+pub(crate) async fn get_tasks_created_between_impl(
+    pool: &SqlitePool,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Task>, AppError> {
+    if from > to {
+        return Err(AppError::ValidationError(
+            "Start cannot be after end".to_string(),
+        ));
+    }
 
-    if old_position == new_position {
-        return Ok(());
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"
+        SELECT * FROM tasks
+        WHERE created_at BETWEEN ? AND ?
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_tasks_created_between(
+    pool: State<'_, SqlitePool>,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Task>, AppError> {
+    get_tasks_created_between_impl(pool.inner(), from, to).await
+}
+
+/// Open tasks whose reminder falls within [from, to], earliest reminder first.
+///
+/// A task's reminder fires `reminder_offset_minutes` before its due date, or at the
+/// due date itself when no offset is set. Tasks already marked as notified for their
+/// current due date are left out.
+pub(crate) async fn get_due_reminders_impl(
+    pool: &SqlitePool,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Task>, AppError> {
+    if from > to {
+        return Err(AppError::ValidationError(
+            "Range start must not be after its end".to_string(),
+        ));
     }
 
-    // Shift other tasks in the same group
-    if old_position < new_position {
-        // Moving down: shift tasks between old and new position up
-        sqlx::query(
-            r#"
-            UPDATE tasks
-            SET position = position - 1
-            WHERE parent_id IS ?
-            AND category_id IS ?
-            AND position > ?
-            AND position <= ?
-            "#,
-        )
-        .bind(task.parent_id)
-        .bind(task.category_id)
-        .bind(old_position)
-        .bind(new_position)
-        .execute(pool.inner())
-        .await?;
-    } else {
-        // Moving up: shift tasks between new and old position down
-        sqlx::query(
-            r#"
-            UPDATE tasks
-            SET position = position + 1
-            WHERE parent_id IS ?
-            AND category_id IS ?
-            AND position >= ?
-            AND position < ?
-            "#,
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"
+        SELECT * FROM (
+            SELECT *, due_date - COALESCE(reminder_offset_minutes, 0) * 60 AS remind_at
+            FROM tasks t
+            WHERE is_done = 0 AND due_date IS NOT NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM notified_reminders n
+                WHERE n.task_id = t.id AND n.due_date = t.due_date
+            )
         )
-        .bind(task.parent_id)
-        .bind(task.category_id)
-        .bind(new_position)
-        .bind(old_position)
-        .execute(pool.inner())
-        .await?;
-    }
+        WHERE remind_at BETWEEN ? AND ?
+        ORDER BY remind_at ASC, id ASC
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
 
-    // Update the task's position
-    sqlx::query("UPDATE tasks SET position = ? WHERE id = ?")
-        .bind(new_position)
+    Ok(tasks)
+}
+
+#[tauri::command]
+pub async fn get_due_reminders(
+    pool: State<'_, SqlitePool>,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Task>, AppError> {
+    get_due_reminders_impl(pool.inner(), from, to).await
+}
+
+/// Records that the reminder for task `id`'s current due date was shown, so
+/// `get_due_reminders` skips it until the due date changes.
+pub(crate) async fn mark_reminder_sent_impl(pool: &SqlitePool, id: i64) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    let due_date: Option<(Option<i64>,)> =
+        sqlx::query_as("SELECT due_date FROM tasks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let due_date = match due_date {
+        Some((Some(due_date),)) => due_date,
+        Some((None,)) => {
+            return Err(AppError::ValidationError(format!(
+                "Task {} has no due date",
+                id
+            )))
+        }
+        None => return Err(AppError::NotFound(format!("Task {} not found", id))),
+    };
+
+    // Entries for earlier due dates can never match again
+    sqlx::query("DELETE FROM notified_reminders WHERE task_id = ? AND due_date != ?")
         .bind(id)
-        .execute(pool.inner())
+        .bind(due_date)
+        .execute(&mut *tx)
         .await?;
+    sqlx::query(
+        "INSERT OR REPLACE INTO notified_reminders (task_id, due_date, notified_at) VALUES (?, ?, ?)",
+    )
+    .bind(id)
+    .bind(due_date)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
 
     Ok(())
 }
 
+#[tauri::command]
+pub async fn mark_reminder_sent(pool: State<'_, SqlitePool>, id: i64) -> Result<(), AppError> {
+    with_retry(|| mark_reminder_sent_impl(pool.inner(), id)).await
+}
+
+/// The single most urgent actionable task, for the "what next" focus mode.
+///
+/// Tasks with open subtasks count as blocked. Ranks by priority, then by
+/// earliest due date with undated tasks last.
+pub(crate) async fn get_next_due_task_impl(pool: &SqlitePool) -> Result<Option<Task>, AppError> {
+    let task = sqlx::query_as::<_, Task>(
+        r#"
+        SELECT * FROM tasks t
+        WHERE t.is_done = 0
+          AND NOT EXISTS (
+              SELECT 1 FROM tasks sub WHERE sub.parent_id = t.id AND sub.is_done = 0
+          )
+        ORDER BY t.priority,
+            t.due_date IS NULL,
+            t.due_date,
+            t.position,
+            t.id
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn get_next_due_task(pool: State<'_, SqlitePool>) -> Result<Option<Task>, AppError> {
+    get_next_due_task_impl(pool.inner()).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -283,4 +2853,29 @@ mod tests {
         let max_title = "a".repeat(500);
         assert!(validate_task_title(&max_title).is_ok());
     }
+
+    #[test]
+    fn test_like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("milk"), "%milk%");
+        assert_eq!(like_pattern("100%"), "%100\\%%");
+        assert_eq!(like_pattern("a_b"), "%a\\_b%");
+    }
+
+    #[test]
+    fn test_parse_priority_valid() {
+        for (priority, name) in Priority::ALL.into_iter().zip(PRIORITIES) {
+            assert_eq!(parse_priority(name).unwrap(), priority);
+        }
+        assert_eq!(resolve_priority(None).unwrap(), DEFAULT_PRIORITY);
+    }
+
+    #[test]
+    fn test_parse_priority_invalid() {
+        assert!(matches!(
+            parse_priority("Critical"),
+            Err(AppError::ValidationError(_))
+        ));
+        // Matching is case-sensitive
+        assert!(parse_priority("high").is_err());
+    }
 }
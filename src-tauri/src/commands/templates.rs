@@ -0,0 +1,128 @@
+use crate::commands::tasks::{get_subtree_impl, insert_task};
+use crate::db::with_retry;
+use crate::error::AppError;
+use crate::models::{CreateTaskInput, TaskTemplate, TaskTree, TemplateNode};
+use sqlx::SqlitePool;
+use tauri::State;
+
+// Validation function for template names
+fn validate_template_name(name: &str) -> Result<(), AppError> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::ValidationError(
+            "Template name cannot be empty".to_string(),
+        ));
+    }
+    if trimmed.chars().count() > 100 {
+        return Err(AppError::ValidationError(
+            "Template name is too long (max 100 characters)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Saves task `root_id` and its descendants as a template called `name`.
+pub(crate) async fn save_template_impl(
+    pool: &SqlitePool,
+    name: &str,
+    root_id: i64,
+) -> Result<TaskTemplate, AppError> {
+    validate_template_name(name)?;
+    let name = name.trim();
+
+    // Check up front so the user gets a readable error instead of a UNIQUE violation
+    let conflict: Option<(i64,)> = sqlx::query_as("SELECT id FROM task_templates WHERE name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+    if conflict.is_some() {
+        return Err(AppError::ValidationError("name already in use".to_string()));
+    }
+
+    let root = TemplateNode::from_tree(&get_subtree_impl(pool, root_id).await?);
+    let tree = serde_json::to_string(&root)
+        .map_err(|e| AppError::DatabaseError(format!("Failed to serialize template: {}", e)))?;
+
+    let now = chrono::Utc::now().timestamp();
+    let (id,): (i64,) = sqlx::query_as(
+        "INSERT INTO task_templates (name, tree, created_at) VALUES (?, ?, ?) RETURNING id",
+    )
+    .bind(name)
+    .bind(&tree)
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(TaskTemplate {
+        id,
+        name: name.to_string(),
+        root,
+        created_at: now,
+    })
+}
+
+#[tauri::command]
+pub async fn save_template(
+    pool: State<'_, SqlitePool>,
+    name: String,
+    root_id: i64,
+) -> Result<TaskTemplate, AppError> {
+    with_retry(|| save_template_impl(pool.inner(), &name, root_id)).await
+}
+
+/// Creates a fresh task tree from template `template_id` as a new root in
+/// `category_id`, and returns it.
+pub(crate) async fn instantiate_template_impl(
+    pool: &SqlitePool,
+    template_id: i64,
+    category_id: Option<i64>,
+) -> Result<TaskTree, AppError> {
+    let (tree,): (String,) = sqlx::query_as("SELECT tree FROM task_templates WHERE id = ?")
+        .bind(template_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Template {} not found", template_id)))?;
+    let root: TemplateNode = serde_json::from_str(&tree)
+        .map_err(|e| AppError::DatabaseError(format!("Failed to read template: {}", e)))?;
+
+    let mut tx = pool.begin().await?;
+
+    // Depth-first, children pushed in reverse so siblings are appended in order
+    let mut root_id = None;
+    let mut pending = vec![(&root, None)];
+    while let Some((node, parent_id)) = pending.pop() {
+        let input = CreateTaskInput {
+            title: node.title.clone(),
+            description: node.description.clone(),
+            category_id,
+            priority: Some(node.priority.as_str().to_string()),
+            parent_id,
+            due_date: None,
+            effort: node.effort,
+            reminder_offset_minutes: None,
+            color: None,
+        };
+        let task = insert_task(&mut tx, &input).await?;
+        root_id.get_or_insert(task.id);
+        pending.extend(
+            node.children
+                .iter()
+                .rev()
+                .map(|child| (child, Some(task.id))),
+        );
+    }
+
+    tx.commit().await?;
+
+    let root_id = root_id.expect("a template always has a root");
+    get_subtree_impl(pool, root_id).await
+}
+
+#[tauri::command]
+pub async fn instantiate_template(
+    pool: State<'_, SqlitePool>,
+    template_id: i64,
+    category_id: Option<i64>,
+) -> Result<TaskTree, AppError> {
+    with_retry(|| instantiate_template_impl(pool.inner(), template_id, category_id)).await
+}
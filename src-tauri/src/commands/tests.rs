@@ -1,5 +1,55 @@
-use crate::db::run_migrations;
-use crate::models::{build_task_tree, CreateTaskInput, Task, UpdateTaskInput};
+use crate::cache::TreeCache;
+use crate::commands::activity::get_task_activity_impl;
+use crate::commands::categories::{
+    create_category_impl, delete_category_impl, get_all_categories_impl,
+    get_archived_categories_impl, get_categories_paged_impl, get_categories_with_counts_impl,
+    get_category_colors_impl, get_category_usage_impl, prune_empty_categories_impl,
+    rename_category_impl, set_category_archived_impl, update_category_impl,
+    validate_category_input,
+};
+use crate::commands::export::{export_category_impl, import_data_impl};
+use crate::commands::maintenance::{
+    check_foreign_keys_impl, find_orphaned_tasks_impl, get_storage_info_impl,
+    normalize_all_positions_impl, normalize_positions_impl, reattach_orphans_impl,
+    repair_foreign_keys_impl,
+};
+use crate::commands::recurrence::{
+    create_task_series_impl, reschedule_series_impl, validate_recurrence,
+};
+use crate::commands::settings::{get_setting_impl, set_setting_impl, DEFAULT_CATEGORY_KEY};
+use crate::commands::stats::{
+    get_completion_streak_impl, get_completions_by_day_impl, get_cycle_time_stats_impl,
+    get_due_buckets_impl, get_effort_summary_impl, get_priority_distribution_impl,
+    get_task_stats_impl, get_tree_metrics_impl,
+};
+use crate::commands::sync::{get_change_summary_since_impl, get_state_fingerprint_impl};
+use crate::commands::tasks::{
+    bulk_update_where_impl, complete_and_add_followup_impl, count_tasks_impl, create_task_impl,
+    create_tasks_impl, defer_overdue_to_impl, delete_task_impl, duplicate_task_shallow_impl,
+    get_all_tasks_capped_impl, get_all_tasks_impl, get_child_tasks_impl, get_due_reminders_impl,
+    get_inbox_count_impl, get_next_due_task_impl, get_next_position, get_recently_completed_impl,
+    get_root_tasks_impl, get_root_tasks_with_markers_impl, get_subtree_impl,
+    get_task_tree_filtered_impl, get_task_tree_impl, get_task_tree_sorted_impl,
+    get_task_tree_timed_impl, get_tasks_after_impl, get_tasks_by_ids_impl,
+    get_tasks_created_between_impl, get_tasks_for_day_impl, get_tasks_in_tree_order_impl,
+    get_tasks_modified_since_impl, get_tasks_tree_json_string_impl, get_tasks_with_category_impl,
+    get_upcoming_tasks_impl, import_markdown_impl, mark_reminder_sent_impl, merge_tasks_impl,
+    move_between_impl, move_task_to_bottom_impl, move_task_to_top_impl, quick_add_impl,
+    remap_priority_impl, reorder_task_impl, search_tasks_impl, set_locked_impl, set_parent_impl,
+    set_tasks_priority_impl, stream_all_tasks_impl, toggle_task_done_impl, update_task_impl,
+    upsert_task_impl, validate_task_input,
+};
+use crate::commands::templates::{instantiate_template_impl, save_template_impl};
+use crate::commands::trash::{
+    archive_completed_before_impl, empty_trash_impl, list_trash_impl, restore_from_trash_impl,
+};
+use crate::db::{run_migrations, with_retry};
+use crate::error::AppError;
+use crate::models::{
+    build_task_tree, CreateCategoryInput, CreateTaskInput, DeleteStrategy, ExportBundle, Priority,
+    Task, TaskFilter, TaskNode, TaskPage, TaskTree, TreeSort, UpdateCategoryInput, UpdateTaskInput,
+    DEFAULT_PRIORITY,
+};
 use sqlx::SqlitePool;
 
 async fn setup_test_db() -> SqlitePool {
@@ -15,6 +65,21 @@ async fn setup_test_db() -> SqlitePool {
     pool
 }
 
+// Minimal root-level input; tests override fields as needed
+fn task_input(title: &str) -> CreateTaskInput {
+    CreateTaskInput {
+        title: title.to_string(),
+        description: None,
+        category_id: None,
+        priority: Some("Medium".to_string()),
+        parent_id: None,
+        due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
+    }
+}
+
 // Helper function to create a task directly (mimicking the command logic)
 async fn create_task_helper(
     pool: &SqlitePool,
@@ -23,9 +88,9 @@ async fn create_task_helper(
     let now = chrono::Utc::now().timestamp();
 
     // Get next position
-    let result: Option<(i32,)> = sqlx::query_as(
+    let result: Option<(f64,)> = sqlx::query_as(
         r#"
-        SELECT COALESCE(MAX(position), -1) + 1 as next_pos
+        SELECT COALESCE(MAX(position), -1.0) + 1 as next_pos
         FROM tasks
         WHERE parent_id IS ? AND category_id IS ?
         "#,
@@ -35,23 +100,24 @@ async fn create_task_helper(
     .fetch_optional(pool)
     .await?;
 
-    let position = result.map(|r| r.0).unwrap_or(0);
+    let position = result.map(|r| r.0).unwrap_or(0.0);
     let title_trimmed = input.title.trim();
 
     let task = sqlx::query_as::<_, Task>(
         r#"
-        INSERT INTO tasks (title, description, category_id, priority, parent_id, position, due_date, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO tasks (title, description, category_id, priority, parent_id, position, due_date, effort, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         RETURNING *
         "#,
     )
     .bind(title_trimmed)
     .bind(&input.description)
     .bind(input.category_id)
-    .bind(&input.priority)
+    .bind(input.priority.as_deref().map_or(Ok(DEFAULT_PRIORITY), str::parse::<Priority>)?)
     .bind(input.parent_id)
     .bind(position)
     .bind(input.due_date)
+    .bind(input.effort)
     .bind(now)
     .bind(now)
     .fetch_one(pool)
@@ -99,7 +165,7 @@ async fn update_task_helper(
     }
     if let Some(priority) = input.priority {
         builder.push(", priority = ");
-        builder.push_bind(priority);
+        builder.push_bind(priority.parse::<Priority>()?);
     }
     if let Some(parent_id) = input.parent_id {
         builder.push(", parent_id = ");
@@ -113,6 +179,10 @@ async fn update_task_helper(
         builder.push(", due_date = ");
         builder.push_bind(due_date);
     }
+    if let Some(effort) = input.effort {
+        builder.push(", effort = ");
+        builder.push_bind(effort);
+    }
     if let Some(is_done) = input.is_done {
         builder.push(", is_done = ");
         builder.push_bind(is_done);
@@ -141,9 +211,12 @@ async fn test_create_task_success() {
         title: "Test Task".to_string(),
         description: Some("Test description".to_string()),
         category_id: None,
-        priority: "High".to_string(),
+        priority: Some("High".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
 
     let result = create_task_helper(&pool, input).await;
@@ -152,9 +225,9 @@ async fn test_create_task_success() {
     let task = result.unwrap();
     assert_eq!(task.title, "Test Task");
     assert_eq!(task.description, Some("Test description".to_string()));
-    assert_eq!(task.priority, "High");
+    assert_eq!(task.priority, Priority::High);
     assert_eq!(task.is_done, false);
-    assert_eq!(task.position, 0);
+    assert_eq!(task.position, 0.0);
 }
 
 #[tokio::test]
@@ -165,9 +238,12 @@ async fn test_create_task_empty_title_creates_empty_task() {
         title: "   ".to_string(), // Only whitespace - trimming makes it empty
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
 
     // Without validation in helper, this will create a task with empty title
@@ -188,9 +264,12 @@ async fn test_create_task_long_title() {
         title: long_title.clone(),
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
 
     // DB will accept this; validation happens at command level
@@ -206,9 +285,12 @@ async fn test_create_task_trims_whitespace() {
         title: "  Task with spaces  ".to_string(),
         description: None,
         category_id: None,
-        priority: "Low".to_string(),
+        priority: Some("Low".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
 
     let result = create_task_helper(&pool, input).await;
@@ -218,6 +300,43 @@ async fn test_create_task_trims_whitespace() {
     assert_eq!(task.title, "Task with spaces");
 }
 
+#[tokio::test]
+async fn test_create_tasks_batch() {
+    let pool = setup_test_db().await;
+    create_task_helper(&pool, task_input("Existing"))
+        .await
+        .unwrap();
+
+    let tasks = create_tasks_impl(
+        &pool,
+        vec![task_input("Milk"), task_input("Eggs"), task_input("Bread")],
+    )
+    .await
+    .unwrap();
+    let created: Vec<_> = tasks
+        .iter()
+        .map(|t| (t.title.as_str(), t.position))
+        .collect();
+    assert_eq!(created, vec![("Milk", 1.0), ("Eggs", 2.0), ("Bread", 3.0)]);
+    assert_eq!(get_all_tasks_helper(&pool).await.unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_create_tasks_invalid_title_aborts_batch() {
+    let pool = setup_test_db().await;
+
+    let result = create_tasks_impl(
+        &pool,
+        vec![task_input("Milk"), task_input("   "), task_input("Bread")],
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(AppError::ValidationError(ref msg)) if msg == "Task 2: Title cannot be empty"
+    ));
+    assert!(get_all_tasks_helper(&pool).await.unwrap().is_empty());
+}
+
 #[tokio::test]
 async fn test_get_all_tasks() {
     let pool = setup_test_db().await;
@@ -227,9 +346,12 @@ async fn test_get_all_tasks() {
         title: "Task 1".to_string(),
         description: None,
         category_id: None,
-        priority: "High".to_string(),
+        priority: Some("High".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     create_task_helper(&pool, input1)
         .await
@@ -239,9 +361,12 @@ async fn test_get_all_tasks() {
         title: "Task 2".to_string(),
         description: None,
         category_id: None,
-        priority: "Low".to_string(),
+        priority: Some("Low".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     create_task_helper(&pool, input2)
         .await
@@ -263,22 +388,28 @@ async fn test_update_task() {
         title: "Original Title".to_string(),
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     let task = create_task_helper(&pool, input).await.unwrap();
 
     // Update task
     let update_input = UpdateTaskInput {
         title: Some("Updated Title".to_string()),
-        description: Some("New description".to_string()),
+        description: Some(Some("New description".to_string())),
         category_id: None,
         priority: Some("Urgent".to_string()),
         parent_id: None,
         is_done: Some(true),
         position: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
 
     let updated = update_task_helper(&pool, task.id, update_input)
@@ -287,7 +418,7 @@ async fn test_update_task() {
 
     assert_eq!(updated.title, "Updated Title");
     assert_eq!(updated.description, Some("New description".to_string()));
-    assert_eq!(updated.priority, "Urgent");
+    assert_eq!(updated.priority, Priority::Urgent);
     assert_eq!(updated.is_done, true);
     assert!(updated.completed_at.is_some());
 }
@@ -301,9 +432,12 @@ async fn test_update_task_mark_undone() {
         title: "Task".to_string(),
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     let task = create_task_helper(&pool, input).await.unwrap();
 
@@ -317,6 +451,9 @@ async fn test_update_task_mark_undone() {
         is_done: Some(true),
         position: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     update_task_helper(&pool, task.id, mark_done)
         .await
@@ -332,6 +469,9 @@ async fn test_update_task_mark_undone() {
         is_done: Some(false),
         position: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     let updated = update_task_helper(&pool, task.id, mark_undone)
         .await
@@ -341,6 +481,83 @@ async fn test_update_task_mark_undone() {
     assert!(updated.completed_at.is_none());
 }
 
+#[tokio::test]
+async fn test_bulk_update_where_changes_matching_priority() {
+    let pool = setup_test_db().await;
+    let now = chrono::Utc::now().timestamp();
+    let day = 86_400;
+
+    let mut ids = std::collections::HashMap::new();
+    for (title, priority, due_date) in [
+        ("Low overdue", "Low", now - day),
+        ("Low upcoming", "Low", now + day),
+        ("Urgent overdue", "Urgent", now - day),
+        ("Locked low overdue", "Low", now - day),
+    ] {
+        let task = create_task_helper(
+            &pool,
+            CreateTaskInput {
+                priority: Some(priority.to_string()),
+                due_date: Some(due_date),
+                ..task_input(title)
+            },
+        )
+        .await
+        .unwrap();
+        ids.insert(title, task.id);
+    }
+    set_locked_impl(&pool, ids["Locked low overdue"], true)
+        .await
+        .unwrap();
+
+    let overdue_low = || TaskFilter {
+        priority: Some("Low".to_string()),
+        is_done: Some(false),
+        due_before: Some(now),
+        ..Default::default()
+    };
+    let to_medium = || UpdateTaskInput {
+        priority: Some("Medium".to_string()),
+        ..Default::default()
+    };
+    let updated = bulk_update_where_impl(&pool, overdue_low(), to_medium())
+        .await
+        .unwrap();
+    assert_eq!(updated, 1, "Locked tasks are skipped");
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let priority_of = |title: &str| {
+        tasks
+            .iter()
+            .find(|t| t.id == ids[title])
+            .map(|t| t.priority)
+            .unwrap()
+    };
+    assert_eq!(priority_of("Low overdue"), Priority::Medium);
+    assert_eq!(priority_of("Low upcoming"), Priority::Low);
+    assert_eq!(priority_of("Urgent overdue"), Priority::Urgent);
+    assert_eq!(priority_of("Locked low overdue"), Priority::Low);
+    let entries = get_task_activity_impl(&pool, ids["Low overdue"])
+        .await
+        .unwrap();
+    assert_eq!(entries[0].detail.as_deref(), Some("priority"));
+
+    let result = bulk_update_where_impl(&pool, TaskFilter::default(), to_medium()).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let bogus = UpdateTaskInput {
+        priority: Some("Critical".to_string()),
+        ..Default::default()
+    };
+    let result = bulk_update_where_impl(&pool, overdue_low(), bogus).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let blank = UpdateTaskInput {
+        title: Some("  ".to_string()),
+        ..Default::default()
+    };
+    let result = bulk_update_where_impl(&pool, overdue_low(), blank).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
 #[tokio::test]
 async fn test_delete_task() {
     let pool = setup_test_db().await;
@@ -350,9 +567,12 @@ async fn test_delete_task() {
         title: "Task to delete".to_string(),
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     let task = create_task_helper(&pool, input).await.unwrap();
 
@@ -375,9 +595,12 @@ async fn test_delete_task_cascades_to_children() {
         title: "Parent".to_string(),
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     let parent = create_task_helper(&pool, parent_input)
         .await
@@ -388,9 +611,12 @@ async fn test_delete_task_cascades_to_children() {
         title: "Child".to_string(),
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: Some(parent.id),
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     create_task_helper(&pool, child_input)
         .await
@@ -415,9 +641,12 @@ async fn test_get_task_tree_simple() {
         title: "Parent".to_string(),
         description: None,
         category_id: None,
-        priority: "High".to_string(),
+        priority: Some("High".to_string()),
         parent_id: None,
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     let parent = create_task_helper(&pool, parent_input)
         .await
@@ -428,9 +657,12 @@ async fn test_get_task_tree_simple() {
         title: "Child 1".to_string(),
         description: None,
         category_id: None,
-        priority: "Medium".to_string(),
+        priority: Some("Medium".to_string()),
         parent_id: Some(parent.id),
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     create_task_helper(&pool, child1_input)
         .await
@@ -440,9 +672,12 @@ async fn test_get_task_tree_simple() {
         title: "Child 2".to_string(),
         description: None,
         category_id: None,
-        priority: "Low".to_string(),
+        priority: Some("Low".to_string()),
         parent_id: Some(parent.id),
         due_date: None,
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
     };
     create_task_helper(&pool, child2_input)
         .await
@@ -468,9 +703,12 @@ async fn test_reorder_task() {
             title: "Task 1".to_string(),
             description: None,
             category_id: None,
-            priority: "Medium".to_string(),
+            priority: Some("Medium".to_string()),
             parent_id: None,
             due_date: None,
+            effort: None,
+            reminder_offset_minutes: None,
+            color: None,
         },
     )
     .await
@@ -482,9 +720,12 @@ async fn test_reorder_task() {
             title: "Task 2".to_string(),
             description: None,
             category_id: None,
-            priority: "Medium".to_string(),
+            priority: Some("Medium".to_string()),
             parent_id: None,
             due_date: None,
+            effort: None,
+            reminder_offset_minutes: None,
+            color: None,
         },
     )
     .await
@@ -496,9 +737,12 @@ async fn test_reorder_task() {
             title: "Task 3".to_string(),
             description: None,
             category_id: None,
-            priority: "Medium".to_string(),
+            priority: Some("Medium".to_string()),
             parent_id: None,
             due_date: None,
+            effort: None,
+            reminder_offset_minutes: None,
+            color: None,
         },
     )
     .await
@@ -525,7 +769,4463 @@ async fn test_reorder_task() {
     let task2_updated = tasks.iter().find(|t| t.id == task2.id).unwrap();
     let task3_updated = tasks.iter().find(|t| t.id == task3.id).unwrap();
 
-    assert_eq!(task2_updated.position, 0);
-    assert_eq!(task3_updated.position, 1);
-    assert_eq!(task1_updated.position, 2);
+    assert_eq!(task2_updated.position, 0.0);
+    assert_eq!(task3_updated.position, 1.0);
+    assert_eq!(task1_updated.position, 2.0);
+}
+
+#[tokio::test]
+async fn test_change_summary_counts_updates_after_timestamp() {
+    let pool = setup_test_db().await;
+
+    let task1 = create_task_helper(&pool, task_input("Task 1"))
+        .await
+        .unwrap();
+    create_task_helper(&pool, task_input("Task 2"))
+        .await
+        .unwrap();
+
+    // Push every existing row into the past so only the edit below is "new"
+    sqlx::query("UPDATE tasks SET updated_at = 1000")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE categories SET updated_at = 1000")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    update_task_helper(
+        &pool,
+        task1.id,
+        UpdateTaskInput {
+            title: Some("Task 1 edited".to_string()),
+            description: None,
+            category_id: None,
+            priority: None,
+            parent_id: None,
+            is_done: None,
+            position: None,
+            due_date: None,
+            effort: None,
+            reminder_offset_minutes: None,
+            color: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let summary = get_change_summary_since_impl(&pool, 1000).await.unwrap();
+    assert_eq!(summary.tasks_changed, 1);
+    assert_eq!(summary.categories_changed, 0);
+    assert_eq!(summary.tombstones, 0);
+}
+
+#[tokio::test]
+async fn test_change_summary_counts_tombstones() {
+    let pool = setup_test_db().await;
+
+    let parent = create_task_helper(&pool, task_input("Parent"))
+        .await
+        .unwrap();
+    create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..task_input("Child")
+        },
+    )
+    .await
+    .unwrap();
+
+    delete_task_helper(&pool, parent.id).await.unwrap();
+
+    let summary = get_change_summary_since_impl(&pool, 0).await.unwrap();
+    assert_eq!(summary.tasks_changed, 0);
+    assert_eq!(
+        summary.tombstones, 2,
+        "Cascaded child should leave a tombstone too"
+    );
+}
+
+#[tokio::test]
+async fn test_set_parent_promotes_child_to_root() {
+    let pool = setup_test_db().await;
+
+    let parent = create_task_helper(&pool, task_input("Parent"))
+        .await
+        .unwrap();
+    let child1 = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..task_input("Child 1")
+        },
+    )
+    .await
+    .unwrap();
+    let child2 = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..task_input("Child 2")
+        },
+    )
+    .await
+    .unwrap();
+
+    let moved = set_parent_impl(&pool, child1.id, None, None).await.unwrap();
+    assert_eq!(moved.parent_id, None);
+    assert_eq!(
+        moved.position, 1.0,
+        "Should be appended after the existing root"
+    );
+
+    // The remaining child closes the gap
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let child2_updated = tasks.iter().find(|t| t.id == child2.id).unwrap();
+    assert_eq!(child2_updated.position, 0.0);
+}
+
+#[tokio::test]
+async fn test_set_parent_demotes_root_under_task() {
+    let pool = setup_test_db().await;
+
+    let task_a = create_task_helper(&pool, task_input("A")).await.unwrap();
+    let task_b = create_task_helper(&pool, task_input("B")).await.unwrap();
+    let task_c = create_task_helper(&pool, task_input("C")).await.unwrap();
+
+    let moved = set_parent_impl(&pool, task_b.id, Some(task_a.id), None)
+        .await
+        .unwrap();
+    assert_eq!(moved.parent_id, Some(task_a.id));
+    assert_eq!(moved.position, 0.0);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let task_c_updated = tasks.iter().find(|t| t.id == task_c.id).unwrap();
+    assert_eq!(task_c_updated.position, 1.0, "Roots after B shift up");
+}
+
+#[tokio::test]
+async fn test_set_parent_moves_subtree_into_parent_category() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let personal = category_id_by_name(&pool, "Personal").await;
+
+    let target = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(work),
+            ..task_input("Target")
+        },
+    )
+    .await
+    .unwrap();
+    let moved = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(personal),
+            ..task_input("Moved")
+        },
+    )
+    .await
+    .unwrap();
+    let child = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(moved.id),
+            category_id: Some(personal),
+            ..task_input("Child")
+        },
+    )
+    .await
+    .unwrap();
+
+    set_parent_impl(&pool, moved.id, Some(target.id), None)
+        .await
+        .unwrap();
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let find = |id: i64| tasks.iter().find(|t| t.id == id).unwrap();
+    assert_eq!(find(moved.id).category_id, Some(work));
+    assert_eq!(
+        find(child.id).category_id,
+        Some(work),
+        "Descendants follow the moved task"
+    );
+    assert_eq!(find(child.id).parent_id, Some(moved.id));
+    assert_eq!(find(child.id).position, 0.0);
+}
+
+#[tokio::test]
+async fn test_set_parent_refuses_locked_task() {
+    let pool = setup_test_db().await;
+
+    let parent = create_task_helper(&pool, task_input("Parent"))
+        .await
+        .unwrap();
+    let task = create_task_helper(&pool, task_input("Locked"))
+        .await
+        .unwrap();
+    set_locked_impl(&pool, task.id, true).await.unwrap();
+
+    let result = set_parent_impl(&pool, task.id, Some(parent.id), None).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let after = tasks.iter().find(|t| t.id == task.id).unwrap();
+    assert_eq!(after.parent_id, None);
+}
+
+#[tokio::test]
+async fn test_set_parent_rejects_cycles() {
+    let pool = setup_test_db().await;
+
+    let parent = create_task_helper(&pool, task_input("Parent"))
+        .await
+        .unwrap();
+    let child = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..task_input("Child")
+        },
+    )
+    .await
+    .unwrap();
+    let grandchild = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(child.id),
+            ..task_input("Grandchild")
+        },
+    )
+    .await
+    .unwrap();
+
+    let result = set_parent_impl(&pool, parent.id, Some(grandchild.id), None).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let result = set_parent_impl(&pool, parent.id, Some(parent.id), None).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    // Nothing moved
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let parent_after = tasks.iter().find(|t| t.id == parent.id).unwrap();
+    assert_eq!(parent_after.parent_id, None);
+}
+
+#[tokio::test]
+async fn test_create_task_defaults_priority_to_medium() {
+    let pool = setup_test_db().await;
+
+    let task = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            priority: None,
+            ..task_input("Quick add")
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(task.priority, Priority::Medium);
+}
+
+#[tokio::test]
+async fn test_create_task_rejects_invalid_priority() {
+    let pool = setup_test_db().await;
+
+    let result = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            priority: Some("Critical".to_string()),
+            ..task_input("Bad priority")
+        },
+    )
+    .await;
+
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 0);
+}
+
+#[tokio::test]
+async fn test_get_task_tree_filtered_by_done_state() {
+    let pool = setup_test_db().await;
+
+    let parent = create_task_helper(&pool, task_input("Parent"))
+        .await
+        .unwrap();
+    let child = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..task_input("Child")
+        },
+    )
+    .await
+    .unwrap();
+    create_task_helper(&pool, task_input("Open root"))
+        .await
+        .unwrap();
+
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+        .bind(child.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let filter = TaskFilter {
+        is_done: Some(true),
+        ..Default::default()
+    };
+    let tree = get_task_tree_filtered_impl(&pool, filter).await.unwrap();
+
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree[0].task.id, parent.id);
+    assert!(tree[0].context_only);
+    assert_eq!(tree[0].subtasks[0].task.id, child.id);
+    assert!(!tree[0].subtasks[0].context_only);
+}
+
+#[tokio::test]
+async fn test_rename_category_success() {
+    let pool = setup_test_db().await;
+
+    let (id,): (i64,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Other'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let category = rename_category_impl(&pool, id, "  Side Projects  ")
+        .await
+        .unwrap();
+    assert_eq!(category.id, id);
+    assert_eq!(category.name, "Side Projects");
+
+    // Renaming to its own current name is not a conflict
+    assert!(rename_category_impl(&pool, id, "Side Projects")
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_rename_category_conflict() {
+    let pool = setup_test_db().await;
+
+    let (id,): (i64,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Other'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let result = rename_category_impl(&pool, id, "Work").await;
+    match result {
+        Err(AppError::ValidationError(msg)) => assert_eq!(msg, "name already in use"),
+        other => panic!("Expected ValidationError, got {:?}", other),
+    }
+
+    let result = rename_category_impl(&pool, id, "   ").await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_create_category_trims_name() {
+    let pool = setup_test_db().await;
+    let input = |name: &str| CreateCategoryInput {
+        name: name.to_string(),
+        color: "#9ece6a".to_string(),
+    };
+
+    let garden = create_category_impl(&pool, &input("  Garden ")).await.unwrap();
+    assert_eq!(garden.name, "Garden");
+
+    // Padding doesn't get around the unique name
+    assert!(create_category_impl(&pool, &input(" Work ")).await.is_err());
+    assert!(create_category_impl(&pool, &input("Garden ")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_update_category_is_all_or_nothing() {
+    let pool = setup_test_db().await;
+    let id = category_id_by_name(&pool, "Other").await;
+
+    let bad_color = UpdateCategoryInput {
+        name: Some("Errands".to_string()),
+        color: Some("orange".to_string()),
+    };
+    let result = update_category_impl(&pool, id, bad_color).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let taken = UpdateCategoryInput {
+        name: Some("Work".to_string()),
+        color: Some("#ff9e64".to_string()),
+    };
+    let result = update_category_impl(&pool, id, taken).await;
+    assert!(
+        matches!(result, Err(AppError::ValidationError(ref msg)) if msg == "name already in use")
+    );
+
+    let (name, color): (String, String) =
+        sqlx::query_as("SELECT name, color FROM categories WHERE id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(name, "Other", "Neither change was applied");
+    assert_ne!(color, "#ff9e64");
+
+    let updated = update_category_impl(
+        &pool,
+        id,
+        UpdateCategoryInput {
+            name: Some("  Errands ".to_string()),
+            color: Some("#ff9e64".to_string()),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        (updated.name.as_str(), updated.color.as_str()),
+        ("Errands", "#ff9e64")
+    );
+}
+
+#[tokio::test]
+async fn test_task_activity_records_create_and_complete() {
+    let pool = setup_test_db().await;
+
+    let task = create_task_impl(&pool, task_input("Audited"))
+        .await
+        .unwrap();
+    update_task_impl(
+        &pool,
+        task.id,
+        UpdateTaskInput {
+            title: None,
+            description: None,
+            category_id: None,
+            priority: None,
+            parent_id: None,
+            is_done: Some(true),
+            position: None,
+            due_date: None,
+            effort: None,
+            reminder_offset_minutes: None,
+            color: None,
+        },
+        false,
+    )
+    .await
+    .unwrap();
+
+    let entries = get_task_activity_impl(&pool, task.id).await.unwrap();
+    let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(
+        actions,
+        vec!["completed", "created"],
+        "Newest entry comes first"
+    );
+    assert_eq!(entries[0].detail.as_deref(), Some("is_done"));
+}
+
+#[tokio::test]
+async fn test_task_activity_survives_delete() {
+    let pool = setup_test_db().await;
+
+    let task = create_task_impl(&pool, task_input("Short-lived"))
+        .await
+        .unwrap();
+    delete_task_impl(&pool, task.id, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+
+    let entries = get_task_activity_impl(&pool, task.id).await.unwrap();
+    let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(actions, vec!["deleted", "created"]);
+}
+
+#[tokio::test]
+async fn test_update_task_rolls_back_when_activity_fails() {
+    let pool = setup_test_db().await;
+    let task = create_task_impl(&pool, task_input("Audited"))
+        .await
+        .unwrap();
+
+    // With nowhere to write the activity entry, the edit must not land either
+    sqlx::query("DROP TABLE task_activity")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let update = UpdateTaskInput {
+        title: Some("Unaudited".to_string()),
+        ..Default::default()
+    };
+    assert!(update_task_impl(&pool, task.id, update, false)
+        .await
+        .is_err());
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks[0].title, "Audited");
+}
+
+#[tokio::test]
+async fn test_defer_overdue_moves_only_open_overdue_tasks() {
+    let pool = setup_test_db().await;
+    let now = chrono::Utc::now().timestamp();
+    let yesterday = now - 86_400;
+    let next_week = now + 7 * 86_400;
+
+    let overdue1 = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(yesterday),
+            ..task_input("Overdue 1")
+        },
+    )
+    .await
+    .unwrap();
+    let overdue2 = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(yesterday - 86_400),
+            ..task_input("Overdue 2")
+        },
+    )
+    .await
+    .unwrap();
+    let future = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(next_week),
+            ..task_input("Future")
+        },
+    )
+    .await
+    .unwrap();
+    let done = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(yesterday),
+            ..task_input("Done overdue")
+        },
+    )
+    .await
+    .unwrap();
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+        .bind(done.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let locked = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(yesterday),
+            ..task_input("Locked overdue")
+        },
+    )
+    .await
+    .unwrap();
+    set_locked_impl(&pool, locked.id, true).await.unwrap();
+
+    let today = now + 3600;
+    let count = defer_overdue_to_impl(&pool, today).await.unwrap();
+    assert_eq!(count, 2);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let due = |id: i64| tasks.iter().find(|t| t.id == id).unwrap().due_date;
+    assert_eq!(due(overdue1.id), Some(today));
+    assert_eq!(due(overdue2.id), Some(today));
+    assert_eq!(due(future.id), Some(next_week));
+    assert_eq!(
+        due(done.id),
+        Some(yesterday),
+        "Completed tasks are left alone"
+    );
+    assert_eq!(
+        due(locked.id),
+        Some(yesterday),
+        "Locked tasks are left alone"
+    );
+}
+
+#[tokio::test]
+async fn test_merge_tasks_combines_children_and_descriptions() {
+    let pool = setup_test_db().await;
+
+    let keep = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            description: Some("Original notes".to_string()),
+            ..task_input("Plan trip")
+        },
+    )
+    .await
+    .unwrap();
+    let keep_child = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(keep.id),
+            ..task_input("Book flights")
+        },
+    )
+    .await
+    .unwrap();
+    let merge = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            description: Some("Duplicate notes".to_string()),
+            ..task_input("Plan vacation")
+        },
+    )
+    .await
+    .unwrap();
+    let merge_child = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(merge.id),
+            ..task_input("Book hotel")
+        },
+    )
+    .await
+    .unwrap();
+
+    let kept = merge_tasks_impl(&pool, keep.id, merge.id).await.unwrap();
+    assert_eq!(
+        kept.description.as_deref(),
+        Some("Original notes\n\nDuplicate notes")
+    );
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert!(
+        tasks.iter().all(|t| t.id != merge.id),
+        "Merged task is deleted"
+    );
+
+    let moved = tasks.iter().find(|t| t.id == merge_child.id).unwrap();
+    assert_eq!(moved.parent_id, Some(keep.id));
+    assert_eq!(moved.position, 1.0, "Appended after the existing child");
+    let existing = tasks.iter().find(|t| t.id == keep_child.id).unwrap();
+    assert_eq!(existing.position, 0.0);
+}
+
+#[tokio::test]
+async fn test_merge_tasks_trashes_merged_task_and_respects_locks() {
+    let pool = setup_test_db().await;
+    let keep = insert_task_at(&pool, "Keep", None, None, 0).await;
+    let merge = insert_task_at(&pool, "Merge", None, None, 1).await;
+    let locked = insert_task_at(&pool, "Locked", None, None, 2).await;
+    set_locked_impl(&pool, locked, true).await.unwrap();
+
+    for (keep_id, merge_id) in [(keep, locked), (locked, keep)] {
+        let result = merge_tasks_impl(&pool, keep_id, merge_id).await;
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+    }
+    assert_eq!(get_all_tasks_helper(&pool).await.unwrap().len(), 3);
+
+    merge_tasks_impl(&pool, keep, merge).await.unwrap();
+    let trash = list_trash_impl(&pool).await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].task_id, merge);
+
+    let restored = restore_from_trash_impl(&pool, trash[0].id).await.unwrap();
+    assert_eq!(restored.title, "Merge");
+}
+
+#[tokio::test]
+async fn test_merge_tasks_rejects_descendant() {
+    let pool = setup_test_db().await;
+
+    let parent = create_task_helper(&pool, task_input("Parent"))
+        .await
+        .unwrap();
+    let child = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..task_input("Child")
+        },
+    )
+    .await
+    .unwrap();
+
+    let result = merge_tasks_impl(&pool, child.id, parent.id).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 2, "Nothing should be deleted");
+}
+
+#[tokio::test]
+async fn test_count_tasks_unfiltered_and_done_only() {
+    let pool = setup_test_db().await;
+
+    for title in ["Task 1", "Task 2", "Task 3"] {
+        create_task_helper(&pool, task_input(title)).await.unwrap();
+    }
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE title = 'Task 2'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(count_tasks_impl(&pool, None).await.unwrap(), 3);
+
+    let done_only = TaskFilter {
+        is_done: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(count_tasks_impl(&pool, Some(done_only)).await.unwrap(), 1);
+
+    let search = TaskFilter {
+        search: Some("task".to_string()),
+        is_done: Some(false),
+        ..Default::default()
+    };
+    assert_eq!(count_tasks_impl(&pool, Some(search)).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_inbox_count_only_open_uncategorized() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+
+    insert_task_at(&pool, "Inbox 1", None, None, 0).await;
+    insert_task_at(&pool, "Inbox 2", None, None, 1).await;
+    let done = insert_task_at(&pool, "Inbox done", None, None, 2).await;
+    insert_task_at(&pool, "Filed", None, Some(work), 0).await;
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+        .bind(done)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(get_inbox_count_impl(&pool).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_upsert_task_inserts_then_updates() {
+    let pool = setup_test_db().await;
+
+    let created = upsert_task_impl(&pool, "todoist-42", task_input("Imported"))
+        .await
+        .unwrap();
+    assert_eq!(created.external_id.as_deref(), Some("todoist-42"));
+
+    // Re-running the import updates the same row instead of duplicating it
+    let updated = upsert_task_impl(
+        &pool,
+        "todoist-42",
+        CreateTaskInput {
+            priority: Some("High".to_string()),
+            ..task_input("Imported (renamed)")
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.id, created.id);
+    assert_eq!(updated.title, "Imported (renamed)");
+    assert_eq!(updated.priority, Priority::High);
+    assert_eq!(updated.position, created.position);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 1);
+
+    // Plain creates don't get an external id
+    let plain = create_task_impl(&pool, task_input("Manual")).await.unwrap();
+    assert_eq!(plain.external_id, None);
+}
+
+#[tokio::test]
+async fn test_upsert_task_shares_create_and_update_rules() {
+    let pool = setup_test_db().await;
+    let work_id = category_id_by_name(&pool, "Work").await;
+    set_setting_impl(&pool, DEFAULT_CATEGORY_KEY, Some(&work_id.to_string()))
+        .await
+        .unwrap();
+
+    // Inserts pick up the default category like create_task does
+    let created = upsert_task_impl(&pool, "ext-1", task_input("Imported"))
+        .await
+        .unwrap();
+    assert_eq!(created.category_id, Some(work_id));
+    assert_eq!(created.external_id.as_deref(), Some("ext-1"));
+
+    set_locked_impl(&pool, created.id, true).await.unwrap();
+    let result = upsert_task_impl(&pool, "ext-1", task_input("Renamed")).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks[0].title, "Imported");
+}
+
+#[tokio::test]
+async fn test_get_tasks_in_tree_order_is_depth_first() {
+    let pool = setup_test_db().await;
+
+    let root1 = create_task_helper(&pool, task_input("Root 1"))
+        .await
+        .unwrap();
+    let root2 = create_task_helper(&pool, task_input("Root 2"))
+        .await
+        .unwrap();
+    // Children created out of tree order to make sure insertion order doesn't leak through
+    let child2a = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(root2.id),
+            ..task_input("Root 2 - Child A")
+        },
+    )
+    .await
+    .unwrap();
+    let child1a = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(root1.id),
+            ..task_input("Root 1 - Child A")
+        },
+    )
+    .await
+    .unwrap();
+    let child1b = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(root1.id),
+            ..task_input("Root 1 - Child B")
+        },
+    )
+    .await
+    .unwrap();
+    let grandchild = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(child1a.id),
+            ..task_input("Root 1 - Child A - Grandchild")
+        },
+    )
+    .await
+    .unwrap();
+
+    let tasks = get_tasks_in_tree_order_impl(&pool).await.unwrap();
+    let ids: Vec<i64> = tasks.iter().map(|t| t.id).collect();
+    assert_eq!(
+        ids,
+        vec![
+            root1.id,
+            child1a.id,
+            grandchild.id,
+            child1b.id,
+            root2.id,
+            child2a.id
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_priority_distribution_groups_window() {
+    let pool = setup_test_db().await;
+
+    let seeds = [
+        ("Urgent", 1_000),
+        ("High", 1_500),
+        ("High", 2_000),
+        ("Low", 2_500),
+        ("Urgent", 9_000), // Outside the window
+    ];
+    for (priority, created_at) in seeds {
+        let task = create_task_helper(
+            &pool,
+            CreateTaskInput {
+                priority: Some(priority.to_string()),
+                ..task_input("Seed")
+            },
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE tasks SET created_at = ? WHERE id = ?")
+            .bind(created_at)
+            .bind(task.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let buckets = get_priority_distribution_impl(&pool, 1_000, 3_000)
+        .await
+        .unwrap();
+    let counts: Vec<(&str, i64)> = buckets
+        .iter()
+        .map(|b| (b.priority.as_str(), b.count))
+        .collect();
+    assert_eq!(counts, vec![("Urgent", 1), ("High", 2), ("Low", 1)]);
+
+    let result = get_priority_distribution_impl(&pool, 3_000, 1_000).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+// Insert a task with an explicit position, bypassing next-position bookkeeping
+async fn insert_task_at(
+    pool: &SqlitePool,
+    title: &str,
+    parent_id: Option<i64>,
+    category_id: Option<i64>,
+    position: i32,
+) -> i64 {
+    let (id,): (i64,) = sqlx::query_as(
+        r#"
+        INSERT INTO tasks (title, category_id, priority, parent_id, position, created_at, updated_at)
+        VALUES (?, ?, 2, ?, ?, 0, 0)
+        RETURNING id
+        "#,
+    )
+    .bind(title)
+    .bind(category_id)
+    .bind(parent_id)
+    .bind(position)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    id
+}
+
+async fn positions_by_title(pool: &SqlitePool) -> Vec<(String, f64)> {
+    sqlx::query_as("SELECT title, position FROM tasks ORDER BY position ASC, id ASC")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_normalize_positions_closes_gaps_and_duplicates() {
+    let pool = setup_test_db().await;
+
+    insert_task_at(&pool, "A", None, None, 3).await;
+    insert_task_at(&pool, "B", None, None, 7).await;
+    let c = insert_task_at(&pool, "C", None, None, 8).await;
+    insert_task_at(&pool, "D", None, None, 20).await;
+    // Inserts reject duplicates, but updates can still create them; later id sorts after B
+    sqlx::query("UPDATE tasks SET position = 7 WHERE id = ?")
+        .bind(c)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let changed = normalize_positions_impl(&pool, None, None).await.unwrap();
+    assert_eq!(changed, 4);
+
+    let positions = positions_by_title(&pool).await;
+    assert_eq!(
+        positions,
+        vec![
+            ("A".to_string(), 0.0),
+            ("B".to_string(), 1.0),
+            ("C".to_string(), 2.0),
+            ("D".to_string(), 3.0)
+        ]
+    );
+
+    // Already clean: nothing to do
+    assert_eq!(
+        normalize_positions_impl(&pool, None, None).await.unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_normalize_all_positions_repairs_each_group() {
+    let pool = setup_test_db().await;
+
+    let root = insert_task_at(&pool, "Root", None, None, 5).await;
+    insert_task_at(&pool, "Child 1", Some(root), None, 4).await;
+    insert_task_at(&pool, "Child 2", Some(root), None, 9).await;
+
+    normalize_all_positions_impl(&pool).await.unwrap();
+
+    let positions = positions_by_title(&pool).await;
+    assert_eq!(
+        positions,
+        vec![
+            ("Root".to_string(), 0.0),
+            ("Child 1".to_string(), 0.0),
+            ("Child 2".to_string(), 1.0)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_task_effort_set_and_validated() {
+    let pool = setup_test_db().await;
+
+    let task = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            effort: Some(3),
+            ..task_input("Estimate me")
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(task.effort, Some(3));
+
+    let result = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            effort: Some(-1),
+            ..task_input("Negative")
+        },
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let result = update_task_impl(
+        &pool,
+        task.id,
+        UpdateTaskInput {
+            effort: Some(Some(-5)),
+            ..Default::default()
+        },
+        false,
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_effort_summary_aggregates_category() {
+    let pool = setup_test_db().await;
+
+    let (work_id,): (i64,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Work'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    for (title, effort, done) in [("A", 5, true), ("B", 3, false), ("C", 2, true)] {
+        let task = create_task_impl(
+            &pool,
+            CreateTaskInput {
+                category_id: Some(work_id),
+                effort: Some(effort),
+                ..task_input(title)
+            },
+        )
+        .await
+        .unwrap();
+        if done {
+            update_task_impl(
+                &pool,
+                task.id,
+                UpdateTaskInput {
+                    is_done: Some(true),
+                    ..Default::default()
+                },
+                false,
+            )
+            .await
+            .unwrap();
+        }
+    }
+    // Different category and unestimated tasks don't count towards Work
+    create_task_impl(
+        &pool,
+        CreateTaskInput {
+            effort: Some(8),
+            ..task_input("Elsewhere")
+        },
+    )
+    .await
+    .unwrap();
+    create_task_impl(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(work_id),
+            ..task_input("Unestimated")
+        },
+    )
+    .await
+    .unwrap();
+
+    let summary = get_effort_summary_impl(&pool, Some(work_id)).await.unwrap();
+    assert_eq!(summary.total, 10);
+    assert_eq!(summary.completed, 7);
+
+    let overall = get_effort_summary_impl(&pool, None).await.unwrap();
+    assert_eq!(overall.total, 18);
+}
+
+#[tokio::test]
+async fn test_categories_with_counts_includes_empty_categories() {
+    let pool = setup_test_db().await;
+
+    let (work_id,): (i64,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Work'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    for title in ["A", "B", "C"] {
+        create_task_helper(
+            &pool,
+            CreateTaskInput {
+                category_id: Some(work_id),
+                ..task_input(title)
+            },
+        )
+        .await
+        .unwrap();
+    }
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE title = 'B'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let categories = get_categories_with_counts_impl(&pool, false).await.unwrap();
+    assert_eq!(categories.len(), 4, "Every category is listed");
+
+    let work = categories
+        .iter()
+        .find(|c| c.category.name == "Work")
+        .unwrap();
+    assert_eq!((work.total, work.done), (3, 1));
+
+    let personal = categories
+        .iter()
+        .find(|c| c.category.name == "Personal")
+        .unwrap();
+    assert_eq!((personal.total, personal.done), (0, 0));
+}
+
+#[tokio::test]
+async fn test_get_category_usage_breakdown() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let personal = category_id_by_name(&pool, "Personal").await;
+
+    let root = insert_task_at(&pool, "Root", None, Some(work), 0).await;
+    let done = insert_task_at(&pool, "Done child", Some(root), Some(work), 0).await;
+    insert_task_at(&pool, "Filed elsewhere", Some(done), Some(personal), 0).await;
+    insert_task_at(&pool, "Unrelated", None, Some(personal), 0).await;
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+        .bind(done)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let usage = get_category_usage_impl(&pool, work).await.unwrap();
+    assert_eq!(
+        (usage.total_tasks, usage.open_tasks, usage.done_tasks),
+        (2, 1, 1)
+    );
+    assert_eq!(
+        usage.subtree_tasks, 3,
+        "Counts subtasks in other categories"
+    );
+
+    let other = category_id_by_name(&pool, "Other").await;
+    let usage = get_category_usage_impl(&pool, other).await.unwrap();
+    assert_eq!(
+        (
+            usage.total_tasks,
+            usage.open_tasks,
+            usage.done_tasks,
+            usage.subtree_tasks
+        ),
+        (0, 0, 0, 0)
+    );
+
+    let result = get_category_usage_impl(&pool, 9999).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_get_task_tree_include_done_flag() {
+    let pool = setup_test_db().await;
+
+    let parent = create_task_helper(&pool, task_input("Parent"))
+        .await
+        .unwrap();
+    create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..task_input("Open child")
+        },
+    )
+    .await
+    .unwrap();
+    let done_root = create_task_helper(&pool, task_input("Done root"))
+        .await
+        .unwrap();
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id IN (?, ?)")
+        .bind(parent.id)
+        .bind(done_root.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let all = get_task_tree_impl(&pool, &TreeCache::default(), true)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 2);
+
+    let open_only = get_task_tree_impl(&pool, &TreeCache::default(), false)
+        .await
+        .unwrap();
+    assert_eq!(open_only.len(), 1);
+    assert_eq!(
+        open_only[0].task.id, parent.id,
+        "Done parent keeps its open child visible"
+    );
+    assert_eq!(open_only[0].subtasks.len(), 1);
+}
+
+#[tokio::test]
+async fn test_tree_json_string_matches_structured_tree() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let root = insert_task_at(&pool, "Root", None, Some(work), 0).await;
+    let child = insert_task_at(&pool, "Child", Some(root), Some(work), 0).await;
+    insert_task_at(&pool, "Grandchild", Some(child), Some(work), 0).await;
+
+    let cache = TreeCache::default();
+    let tree = get_task_tree_impl(&pool, &cache, true).await.unwrap();
+    let json = get_tasks_tree_json_string_impl(&pool, &cache, true)
+        .await
+        .unwrap();
+    assert_eq!(json, serde_json::to_string(&tree).unwrap());
+
+    // Task fields stay flattened next to `subtasks`
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed[0]["title"], "Root");
+    assert_eq!(parsed[0]["priority"], "Medium");
+    assert_eq!(
+        parsed[0]["subtasks"][0]["subtasks"][0]["title"],
+        "Grandchild"
+    );
+    assert!(parsed[0].get("task").is_none());
+}
+
+#[tokio::test]
+async fn test_timed_task_tree_matches_untimed() {
+    let pool = setup_test_db().await;
+    let root = insert_task_at(&pool, "Root", None, None, 0).await;
+    insert_task_at(&pool, "Child", Some(root), None, 0).await;
+
+    let timed = get_task_tree_timed_impl(&pool, &TreeCache::default(), true)
+        .await
+        .unwrap();
+    let tree = get_task_tree_impl(&pool, &TreeCache::default(), true)
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::to_value(&timed.data).unwrap(),
+        serde_json::to_value(&tree).unwrap()
+    );
+
+    let json = serde_json::to_value(&timed).unwrap();
+    assert!(
+        json["elapsed_ms"].is_u64(),
+        "Timing is sent alongside the data"
+    );
+    assert!(timed.elapsed_ms < 60_000);
+}
+
+#[tokio::test]
+async fn test_task_tree_sorted_by_recent_activity() {
+    let pool = setup_test_db().await;
+    let idle = insert_task_at(&pool, "Idle", None, None, 0).await;
+    let active = insert_task_at(&pool, "Active", None, None, 1).await;
+    let child = insert_task_at(&pool, "Child", Some(active), None, 0).await;
+    let deep = insert_task_at(&pool, "Deep", Some(child), None, 0).await;
+    let dated = insert_task_at(&pool, "Dated", Some(idle), None, 0).await;
+    sqlx::query("UPDATE tasks SET updated_at = 100")
+        .execute(&pool)
+        .await
+        .unwrap();
+    for (id, column, value) in [
+        (deep, "updated_at", 500),
+        (idle, "updated_at", 200),
+        (dated, "due_date", 1_000),
+    ] {
+        sqlx::query(&format!("UPDATE tasks SET {} = ? WHERE id = ?", column))
+            .bind(value)
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let root_ids = |tree: Vec<TaskTree>| -> Vec<i64> { tree.iter().map(|n| n.task.id).collect() };
+    let cache = TreeCache::default();
+
+    let tree = get_task_tree_sorted_impl(&pool, &cache, TreeSort::RecentActivity)
+        .await
+        .unwrap();
+    assert_eq!(root_ids(tree), vec![active, idle], "Deep update counts");
+    let tree = get_task_tree_sorted_impl(&pool, &cache, TreeSort::Position)
+        .await
+        .unwrap();
+    assert_eq!(root_ids(tree), vec![idle, active]);
+    let tree = get_task_tree_sorted_impl(&pool, &cache, TreeSort::DueSoonest)
+        .await
+        .unwrap();
+    assert_eq!(root_ids(tree), vec![idle, active]);
+}
+
+#[tokio::test]
+async fn test_reschedule_series_moves_only_upcoming_instances() {
+    let pool = setup_test_db().await;
+    let day = 86_400;
+    // 2024-01-01T00:00:00Z, a Monday
+    let monday = 1_704_067_200;
+
+    let series = create_task_series_impl(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(monday),
+            ..task_input("Weekly review")
+        },
+        "FREQ=WEEKLY",
+        4,
+    )
+    .await
+    .unwrap();
+    assert_eq!(series.len(), 4);
+    let series_id = series[0]
+        .series_id
+        .expect("Instances are linked to a series");
+    assert!(series.iter().all(|t| t.series_id == Some(series_id)));
+    let due: Vec<_> = series.iter().map(|t| t.due_date.unwrap()).collect();
+    assert_eq!(
+        due,
+        vec![
+            monday,
+            monday + 7 * day,
+            monday + 14 * day,
+            monday + 21 * day
+        ]
+    );
+
+    // First review is already done
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+        .bind(series[0].id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Move the upcoming reviews to Tuesdays
+    let moved = reschedule_series_impl(&pool, series_id, "FREQ=WEEKLY", Some(monday + 8 * day))
+        .await
+        .unwrap();
+    let moved_due: Vec<_> = moved.iter().map(|t| t.due_date.unwrap()).collect();
+    assert_eq!(
+        moved_due,
+        vec![monday + 8 * day, monday + 15 * day, monday + 22 * day]
+    );
+
+    let done = get_all_tasks_helper(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|t| t.id == series[0].id)
+        .unwrap();
+    assert_eq!(
+        done.due_date,
+        Some(monday),
+        "Completed instance is untouched"
+    );
+
+    let (rule,): (String,) = sqlx::query_as("SELECT rule FROM task_series WHERE id = ?")
+        .bind(series_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rule, "FREQ=WEEKLY;INTERVAL=1");
+}
+
+#[tokio::test]
+async fn test_reschedule_series_errors() {
+    let pool = setup_test_db().await;
+
+    let result = reschedule_series_impl(&pool, 42, "FREQ=DAILY", None).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    let result = create_task_series_impl(&pool, task_input("No due date"), "FREQ=DAILY", 3).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let result = create_task_series_impl(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(0),
+            ..task_input("Bad rule")
+        },
+        "FREQ=YEARLY",
+        3,
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_priority_column_sorts_by_rank() {
+    let pool = setup_test_db().await;
+    for (title, priority) in [
+        ("a", "Low"),
+        ("b", "High"),
+        ("c", "Urgent"),
+        ("d", "Medium"),
+    ] {
+        create_task_impl(
+            &pool,
+            CreateTaskInput {
+                priority: Some(priority.to_string()),
+                ..task_input(title)
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    let tasks = sqlx::query_as::<_, Task>("SELECT * FROM tasks ORDER BY priority, id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    let priorities: Vec<Priority> = tasks.iter().map(|t| t.priority).collect();
+    assert_eq!(priorities, Priority::ALL);
+
+    let (stored,): (i64,) = sqlx::query_as("SELECT priority FROM tasks WHERE title = 'c'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0, "Urgent is stored as rank 0");
+}
+
+#[tokio::test]
+async fn test_get_next_due_task_prefers_urgent_overdue() {
+    let pool = setup_test_db().await;
+    let now = chrono::Utc::now().timestamp();
+
+    create_task_helper(
+        &pool,
+        CreateTaskInput {
+            priority: Some("High".to_string()),
+            due_date: Some(now + 86_400),
+            ..task_input("High future")
+        },
+    )
+    .await
+    .unwrap();
+    let urgent = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            priority: Some("Urgent".to_string()),
+            due_date: Some(now - 86_400),
+            ..task_input("Urgent overdue")
+        },
+    )
+    .await
+    .unwrap();
+    let undated = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            priority: Some("Urgent".to_string()),
+            ..task_input("Urgent undated")
+        },
+    )
+    .await
+    .unwrap();
+
+    let next = get_next_due_task_impl(&pool).await.unwrap().unwrap();
+    assert_eq!(next.id, urgent.id);
+
+    // An open subtask blocks its parent
+    create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(urgent.id),
+            priority: Some("Low".to_string()),
+            ..task_input("Blocker")
+        },
+    )
+    .await
+    .unwrap();
+    let next = get_next_due_task_impl(&pool).await.unwrap().unwrap();
+    assert_eq!(
+        next.id, undated.id,
+        "Undated tasks sort after dated ones of the same priority"
+    );
+}
+
+#[tokio::test]
+async fn test_get_next_due_task_none_when_all_done() {
+    let pool = setup_test_db().await;
+
+    assert!(get_next_due_task_impl(&pool).await.unwrap().is_none());
+
+    create_task_helper(&pool, task_input("A")).await.unwrap();
+    create_task_helper(&pool, task_input("B")).await.unwrap();
+    sqlx::query("UPDATE tasks SET is_done = 1")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(get_next_due_task_impl(&pool).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_get_categories_paged_boundary() {
+    let pool = setup_test_db().await;
+
+    // Seeded: Other, Personal, Tech Guild, Work
+    let first = get_categories_paged_impl(&pool, 3, 0, false).await.unwrap();
+    assert_eq!(first.total, 4);
+    let names: Vec<_> = first.items.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["Other", "Personal", "Tech Guild"]);
+
+    let second = get_categories_paged_impl(&pool, 3, 3, false).await.unwrap();
+    assert_eq!(second.total, 4);
+    let names: Vec<_> = second.items.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["Work"]);
+
+    let past_end = get_categories_paged_impl(&pool, 3, 6, false).await.unwrap();
+    assert!(past_end.items.is_empty());
+
+    let result = get_categories_paged_impl(&pool, 0, 0, false).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_set_tasks_priority_bulk() {
+    let pool = setup_test_db().await;
+
+    let mut ids = Vec::new();
+    for title in ["A", "B", "C", "D"] {
+        ids.push(
+            create_task_helper(&pool, task_input(title))
+                .await
+                .unwrap()
+                .id,
+        );
+    }
+
+    let updated = set_tasks_priority_impl(&pool, &ids[..3], "Urgent")
+        .await
+        .unwrap();
+    assert_eq!(updated, 3);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    for task in &tasks {
+        let expected = if task.title == "D" {
+            DEFAULT_PRIORITY
+        } else {
+            Priority::Urgent
+        };
+        assert_eq!(task.priority, expected, "priority of {}", task.title);
+    }
+
+    assert_eq!(set_tasks_priority_impl(&pool, &[], "Low").await.unwrap(), 0);
+
+    // Locked tasks are skipped and left out of the count
+    set_locked_impl(&pool, ids[0], true).await.unwrap();
+    let updated = set_tasks_priority_impl(&pool, &ids[..2], "Low")
+        .await
+        .unwrap();
+    assert_eq!(updated, 1);
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks[0].priority, Priority::Urgent);
+    assert_eq!(tasks[1].priority, Priority::Low);
+}
+
+#[tokio::test]
+async fn test_set_tasks_priority_rejects_invalid() {
+    let pool = setup_test_db().await;
+    let task = create_task_helper(&pool, task_input("A")).await.unwrap();
+
+    let result = set_tasks_priority_impl(&pool, &[task.id], "Critical").await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks[0].priority, DEFAULT_PRIORITY);
+}
+
+#[tokio::test]
+async fn test_find_and_reattach_orphans() {
+    let pool = setup_test_db().await;
+
+    let root = insert_task_at(&pool, "Root", None, None, 0).await;
+    insert_task_at(&pool, "Child", Some(root), None, 0).await;
+
+    // Simulate an external edit that bypassed the foreign key
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO tasks (title, priority, parent_id, position, created_at, updated_at) VALUES ('Orphan', 2, 999, 0, 0, 0)",
+    )
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    drop(conn);
+
+    let orphans = find_orphaned_tasks_impl(&pool).await.unwrap();
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0].title, "Orphan");
+
+    assert_eq!(reattach_orphans_impl(&pool).await.unwrap(), 1);
+    assert!(find_orphaned_tasks_impl(&pool).await.unwrap().is_empty());
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let promoted = tasks.iter().find(|t| t.title == "Orphan").unwrap();
+    assert_eq!(promoted.parent_id, None);
+    assert_eq!(promoted.position, 1.0, "Appended after the existing root");
+
+    let tree = build_task_tree(tasks);
+    assert_eq!(
+        tree.len(),
+        2,
+        "Promoted orphan no longer vanishes from the tree"
+    );
+}
+
+#[tokio::test]
+async fn test_get_tasks_by_ids_subset_in_request_order() {
+    let pool = setup_test_db().await;
+
+    let mut ids = Vec::new();
+    for title in ["A", "B", "C", "D"] {
+        ids.push(
+            create_task_helper(&pool, task_input(title))
+                .await
+                .unwrap()
+                .id,
+        );
+    }
+
+    let tasks = get_tasks_by_ids_impl(&pool, &[ids[3], ids[0], ids[2]])
+        .await
+        .unwrap();
+    let titles: Vec<_> = tasks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["D", "A", "C"]);
+
+    let tasks = get_tasks_by_ids_impl(&pool, &[ids[1], 9999]).await.unwrap();
+    assert_eq!(tasks.len(), 1, "Missing ids are skipped");
+    assert_eq!(tasks[0].id, ids[1]);
+
+    assert!(get_tasks_by_ids_impl(&pool, &[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_locked_task_rejects_edits_until_unlocked() {
+    let pool = setup_test_db().await;
+
+    let task = create_task_impl(&pool, task_input("Reference"))
+        .await
+        .unwrap();
+    create_task_impl(&pool, task_input("Sibling"))
+        .await
+        .unwrap();
+
+    let locked = set_locked_impl(&pool, task.id, true).await.unwrap();
+    assert!(locked.is_locked);
+
+    let rename = || UpdateTaskInput {
+        title: Some("Renamed".to_string()),
+        ..Default::default()
+    };
+    let result = update_task_impl(&pool, task.id, rename(), false).await;
+    assert!(matches!(result, Err(AppError::ValidationError(ref msg)) if msg == "task is locked"));
+    let result = reorder_task_impl(&pool, task.id, 1, false).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let result = delete_task_impl(&pool, task.id, false, DeleteStrategy::Cascade).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let unchanged = tasks.iter().find(|t| t.id == task.id).unwrap();
+    assert_eq!(
+        (unchanged.title.as_str(), unchanged.position),
+        ("Reference", 0.0)
+    );
+
+    // Forcing bypasses the lock
+    let forced = update_task_impl(&pool, task.id, rename(), true)
+        .await
+        .unwrap();
+    assert_eq!(forced.title, "Renamed");
+
+    let unlocked = set_locked_impl(&pool, task.id, false).await.unwrap();
+    assert!(!unlocked.is_locked);
+    let updated = update_task_impl(
+        &pool,
+        task.id,
+        UpdateTaskInput {
+            title: Some("Editable again".to_string()),
+            ..Default::default()
+        },
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.title, "Editable again");
+    delete_task_impl(&pool, task.id, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+
+    let result = set_locked_impl(&pool, 9999, true).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_duplicate_position_insert_rejected_within_group() {
+    let pool = setup_test_db().await;
+
+    let (work_id,): (i64,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Work'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let root = insert_task_at(&pool, "Root", None, None, 0).await;
+    insert_task_at(&pool, "Child", Some(root), None, 0).await;
+
+    let insert = |title: &'static str, parent_id: Option<i64>, category_id: Option<i64>| {
+        sqlx::query(
+            "INSERT INTO tasks (title, priority, parent_id, category_id, position, created_at, updated_at) VALUES (?, 2, ?, ?, 0, 0, 0)",
+        )
+        .bind(title)
+        .bind(parent_id)
+        .bind(category_id)
+        .execute(&pool)
+    };
+
+    // Same (parent, category) group, including NULLs
+    assert!(insert("Dup root", None, None).await.is_err());
+    assert!(insert("Dup child", Some(root), None).await.is_err());
+
+    // Same position in a different group is fine
+    assert!(insert("Work root", None, Some(work_id)).await.is_ok());
+    assert!(insert("Work child", Some(root), Some(work_id))
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_create_task_inherits_default_category() {
+    let pool = setup_test_db().await;
+
+    let (work_id,): (i64,) = sqlx::query_as("SELECT id FROM categories WHERE name = 'Work'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let before = create_task_impl(&pool, task_input("Before")).await.unwrap();
+    assert_eq!(before.category_id, None);
+
+    set_setting_impl(&pool, DEFAULT_CATEGORY_KEY, Some(&work_id.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_setting_impl(&pool, DEFAULT_CATEGORY_KEY).await.unwrap(),
+        Some(work_id.to_string())
+    );
+
+    let inherited = create_task_impl(&pool, task_input("Inherited"))
+        .await
+        .unwrap();
+    assert_eq!(inherited.category_id, Some(work_id));
+
+    // An explicit category still wins
+    let (personal_id,): (i64,) =
+        sqlx::query_as("SELECT id FROM categories WHERE name = 'Personal'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let explicit = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(personal_id),
+            ..task_input("Explicit")
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(explicit.category_id, Some(personal_id));
+
+    set_setting_impl(&pool, DEFAULT_CATEGORY_KEY, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_setting_impl(&pool, DEFAULT_CATEGORY_KEY).await.unwrap(),
+        None
+    );
+    let after = create_task_impl(&pool, task_input("After")).await.unwrap();
+    assert_eq!(after.category_id, None);
+}
+
+#[tokio::test]
+async fn test_default_category_setting_validated() {
+    let pool = setup_test_db().await;
+
+    let result = set_setting_impl(&pool, DEFAULT_CATEGORY_KEY, Some("9999")).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let result = set_setting_impl(&pool, DEFAULT_CATEGORY_KEY, Some("work")).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    assert_eq!(
+        get_setting_impl(&pool, DEFAULT_CATEGORY_KEY).await.unwrap(),
+        None
+    );
+
+    // Other keys are stored as-is
+    set_setting_impl(&pool, "theme", Some("dark"))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_setting_impl(&pool, "theme").await.unwrap(),
+        Some("dark".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_get_recently_completed_newest_first() {
+    let pool = setup_test_db().await;
+
+    for (title, completed_at) in [("First", 100), ("Third", 300), ("Second", 200)] {
+        let task = create_task_helper(&pool, task_input(title)).await.unwrap();
+        sqlx::query("UPDATE tasks SET is_done = 1, completed_at = ? WHERE id = ?")
+            .bind(completed_at)
+            .bind(task.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    // Done without a completion time, and still open: both excluded
+    let legacy = create_task_helper(&pool, task_input("Legacy"))
+        .await
+        .unwrap();
+    sqlx::query("UPDATE tasks SET is_done = 1, completed_at = NULL WHERE id = ?")
+        .bind(legacy.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    create_task_helper(&pool, task_input("Open")).await.unwrap();
+
+    let feed = get_recently_completed_impl(&pool, 10).await.unwrap();
+    let titles: Vec<_> = feed.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Third", "Second", "First"]);
+
+    let feed = get_recently_completed_impl(&pool, 2).await.unwrap();
+    let titles: Vec<_> = feed.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Third", "Second"]);
+
+    let feed = get_recently_completed_impl(&pool, 0).await.unwrap();
+    assert_eq!(feed.len(), 1, "Limit is clamped to at least one");
+}
+
+#[tokio::test]
+async fn test_delete_task_cascades_by_default() {
+    let pool = setup_test_db().await;
+
+    let parent = insert_task_at(&pool, "Parent", None, None, 0).await;
+    insert_task_at(&pool, "Child 1", Some(parent), None, 0).await;
+    insert_task_at(&pool, "Child 2", Some(parent), None, 1).await;
+
+    delete_task_impl(&pool, parent, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+
+    assert!(get_all_tasks_helper(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_delete_task_reparents_children() {
+    let pool = setup_test_db().await;
+
+    let grandparent = insert_task_at(&pool, "Grandparent", None, None, 0).await;
+    let parent = insert_task_at(&pool, "Parent", Some(grandparent), None, 0).await;
+    insert_task_at(&pool, "Sibling", Some(grandparent), None, 1).await;
+    insert_task_at(&pool, "Child 1", Some(parent), None, 0).await;
+    insert_task_at(&pool, "Child 2", Some(parent), None, 1).await;
+
+    delete_task_impl(&pool, parent, false, DeleteStrategy::Reparent)
+        .await
+        .unwrap();
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 4, "Children survive their parent's deletion");
+    let mut under_grandparent: Vec<_> = tasks
+        .iter()
+        .filter(|t| t.parent_id == Some(grandparent))
+        .map(|t| (t.title.as_str(), t.position))
+        .collect();
+    under_grandparent.sort_by(|a, b| a.1.total_cmp(&b.1));
+    assert_eq!(
+        under_grandparent,
+        vec![("Sibling", 0.0), ("Child 1", 1.0), ("Child 2", 2.0)]
+    );
+
+    // Deleting a root promotes its children to roots
+    delete_task_impl(&pool, grandparent, false, DeleteStrategy::Reparent)
+        .await
+        .unwrap();
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 3);
+    assert!(tasks.iter().all(|t| t.parent_id.is_none()));
+}
+
+#[tokio::test]
+async fn test_delete_task_blocked_by_children() {
+    let pool = setup_test_db().await;
+
+    let parent = insert_task_at(&pool, "Parent", None, None, 0).await;
+    let child = insert_task_at(&pool, "Child", Some(parent), None, 0).await;
+
+    let result = delete_task_impl(&pool, parent, false, DeleteStrategy::Block).await;
+    assert!(
+        matches!(result, Err(AppError::ValidationError(ref msg)) if msg == "task has subtasks")
+    );
+    assert_eq!(get_all_tasks_helper(&pool).await.unwrap().len(), 2);
+
+    // A childless task is deleted as usual
+    delete_task_impl(&pool, child, false, DeleteStrategy::Block)
+        .await
+        .unwrap();
+    delete_task_impl(&pool, parent, false, DeleteStrategy::Block)
+        .await
+        .unwrap();
+    assert!(get_all_tasks_helper(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_next_position_counts_only_visible_siblings() {
+    let pool = setup_test_db().await;
+
+    insert_task_at(&pool, "First", None, None, 0).await;
+    let trashed = insert_task_at(&pool, "Trashed", None, None, 1).await;
+    insert_task_at(&pool, "Last", None, None, 2).await;
+
+    delete_task_impl(&pool, trashed, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+    create_task_helper(&pool, task_input("New")).await.unwrap();
+
+    assert_eq!(
+        positions_by_title(&pool).await,
+        vec![
+            ("First".to_string(), 0.0),
+            ("Last".to_string(), 1.0),
+            ("New".to_string(), 2.0),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_delete_task_keeps_fractional_siblings_distinct() {
+    let pool = setup_test_db().await;
+    let a = insert_task_at(&pool, "A", None, None, 0).await;
+    let b = insert_task_at(&pool, "B", None, None, 1).await;
+    let c = insert_task_at(&pool, "C", None, None, 2).await;
+    let moved = move_between_impl(&pool, c, Some(a), Some(b)).await.unwrap();
+    assert_eq!(moved.position, 0.5);
+
+    delete_task_impl(&pool, c, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        positions_by_title(&pool).await,
+        vec![("A".to_string(), 0.0), ("B".to_string(), 1.0)]
+    );
+}
+
+#[tokio::test]
+async fn test_task_stats_separates_recurring_completions() {
+    let pool = setup_test_db().await;
+
+    let series = create_task_series_impl(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(1_704_067_200),
+            ..task_input("Daily standup")
+        },
+        "FREQ=DAILY",
+        5,
+    )
+    .await
+    .unwrap();
+    let one_off = create_task_helper(&pool, task_input("One-off"))
+        .await
+        .unwrap();
+    create_task_helper(&pool, task_input("Still open"))
+        .await
+        .unwrap();
+
+    // Three standups and the one-off are done
+    for task in series.iter().take(3) {
+        sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+            .bind(task.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+        .bind(one_off.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let stats = get_task_stats_impl(&pool).await.unwrap();
+    assert_eq!(stats.total, 7);
+    assert_eq!(stats.total_completions, 4);
+    assert_eq!(
+        stats.unique_completions, 2,
+        "The series counts once, plus the one-off"
+    );
+}
+
+#[test]
+fn test_validate_task_input_command() {
+    assert!(validate_task_input(task_input("Valid title")).is_ok());
+    assert!(validate_task_input(task_input(&"a".repeat(500))).is_ok());
+
+    for input in [
+        task_input(""),
+        task_input("   "),
+        task_input(&"a".repeat(501)),
+        CreateTaskInput {
+            priority: Some("Critical".to_string()),
+            ..task_input("Bad priority")
+        },
+        CreateTaskInput {
+            effort: Some(-1),
+            ..task_input("Bad effort")
+        },
+    ] {
+        assert!(
+            matches!(
+                validate_task_input(input),
+                Err(AppError::ValidationError(_))
+            ),
+            "expected a validation error"
+        );
+    }
+}
+
+#[test]
+fn test_validate_recurrence_command() {
+    assert!(validate_recurrence("FREQ=DAILY".to_string()).is_ok());
+
+    assert!(matches!(
+        validate_recurrence("FREQ=HOURLY".to_string()),
+        Err(AppError::ValidationError(ref msg)) if msg == "Unknown frequency 'HOURLY'"
+    ));
+    assert!(matches!(
+        validate_recurrence("FREQ=DAILY;INTERVAL=0".to_string()),
+        Err(AppError::ValidationError(ref msg)) if msg == "Invalid interval '0'"
+    ));
+}
+
+#[test]
+fn test_validate_category_input_command() {
+    let category = |name: &str, color: &str| CreateCategoryInput {
+        name: name.to_string(),
+        color: color.to_string(),
+    };
+
+    assert!(validate_category_input(category("Errands", "#FF5733")).is_ok());
+    assert!(validate_category_input(category("Errands", "#9ece6a")).is_ok());
+
+    for input in [
+        category("", "#FF5733"),
+        category("   ", "#FF5733"),
+        category(&"a".repeat(101), "#FF5733"),
+        category("Errands", "FF5733"),
+        category("Errands", "#FF573"),
+        category("Errands", "#GG5733"),
+    ] {
+        assert!(
+            matches!(
+                validate_category_input(input),
+                Err(AppError::ValidationError(_))
+            ),
+            "expected a validation error"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_tasks_after_pages_stably_across_deletes() {
+    let pool = setup_test_db().await;
+
+    let mut ids = Vec::new();
+    for i in 0..7 {
+        let task = create_task_helper(&pool, task_input(&format!("Task {}", i)))
+            .await
+            .unwrap();
+        ids.push(task.id);
+    }
+
+    let first = get_tasks_after_impl(&pool, None, 3).await.unwrap();
+    let first_ids: Vec<_> = first.items.iter().map(|t| t.id).collect();
+    assert_eq!(first_ids, ids[..3]);
+    assert_eq!(first.next_cursor, Some(ids[2]));
+
+    // Deleting a row already seen would shift an offset-based page
+    delete_task_helper(&pool, ids[0]).await.unwrap();
+
+    let second = get_tasks_after_impl(&pool, first.next_cursor, 3)
+        .await
+        .unwrap();
+    let second_ids: Vec<_> = second.items.iter().map(|t| t.id).collect();
+    assert_eq!(second_ids, ids[3..6]);
+
+    let third = get_tasks_after_impl(&pool, second.next_cursor, 3)
+        .await
+        .unwrap();
+    let third_ids: Vec<_> = third.items.iter().map(|t| t.id).collect();
+    assert_eq!(third_ids, ids[6..]);
+    assert_eq!(third.next_cursor, None, "Last page has no cursor");
+
+    let mut seen: Vec<_> = [first_ids, second_ids, third_ids].concat();
+    seen.dedup();
+    assert_eq!(seen, ids, "No skips or duplicates");
+}
+
+#[tokio::test]
+async fn test_stream_all_tasks_delivers_every_task_once() {
+    let pool = setup_test_db().await;
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let task = create_task_helper(&pool, task_input(&format!("Task {}", i)))
+            .await
+            .unwrap();
+        ids.push(task.id);
+    }
+
+    let mut chunks: Vec<Vec<i64>> = Vec::new();
+    let total = stream_all_tasks_impl(&pool, 4, |chunk| {
+        chunks.push(chunk.iter().map(|t| t.id).collect());
+        Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(total, 10);
+    let sizes: Vec<_> = chunks.iter().map(Vec::len).collect();
+    assert_eq!(sizes, vec![4, 4, 2]);
+    assert_eq!(chunks.concat(), ids, "None missing or duplicated");
+
+    // A failing sink stops the stream
+    let mut calls = 0;
+    let result = stream_all_tasks_impl(&pool, 4, |_| {
+        calls += 1;
+        Err(AppError::DatabaseError("closed".to_string()))
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(calls, 1);
+}
+
+#[tokio::test]
+async fn test_get_tree_metrics() {
+    let pool = setup_test_db().await;
+
+    let empty = get_tree_metrics_impl(&pool).await.unwrap();
+    assert_eq!(
+        (empty.max_depth, empty.max_siblings, empty.root_count),
+        (0, 0, 0)
+    );
+
+    // A
+    // ├── A1
+    // │   └── A1a
+    // │       └── A1a-i
+    // ├── A2
+    // └── A3
+    // B
+    let a = insert_task_at(&pool, "A", None, None, 0).await;
+    insert_task_at(&pool, "B", None, None, 1).await;
+    let a1 = insert_task_at(&pool, "A1", Some(a), None, 0).await;
+    insert_task_at(&pool, "A2", Some(a), None, 1).await;
+    insert_task_at(&pool, "A3", Some(a), None, 2).await;
+    let a1a = insert_task_at(&pool, "A1a", Some(a1), None, 0).await;
+    insert_task_at(&pool, "A1a-i", Some(a1a), None, 0).await;
+
+    let metrics = get_tree_metrics_impl(&pool).await.unwrap();
+    assert_eq!(metrics.max_depth, 4);
+    assert_eq!(metrics.max_siblings, 3);
+    assert_eq!(metrics.root_count, 2);
+}
+
+async fn category_id_by_name(pool: &SqlitePool, name: &str) -> i64 {
+    let (id,): (i64,) = sqlx::query_as("SELECT id FROM categories WHERE name = ?")
+        .bind(name)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_delete_category_reassigns_tasks() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let personal = category_id_by_name(&pool, "Personal").await;
+
+    insert_task_at(&pool, "Existing", None, Some(personal), 0).await;
+    insert_task_at(&pool, "Moved 1", None, Some(work), 0).await;
+    insert_task_at(&pool, "Moved 2", None, Some(work), 1).await;
+
+    delete_category_impl(&pool, work, Some(personal))
+        .await
+        .unwrap();
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert!(tasks.iter().all(|t| t.category_id == Some(personal)));
+    assert_eq!(
+        positions_by_title(&pool).await,
+        vec![
+            ("Existing".to_string(), 0.0),
+            ("Moved 1".to_string(), 1.0),
+            ("Moved 2".to_string(), 2.0)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_delete_category_without_target_uncategorizes_tasks() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let task = insert_task_at(&pool, "Task", None, Some(work), 0).await;
+    let uncategorized = insert_task_at(&pool, "Uncategorized", None, None, 0).await;
+
+    delete_category_impl(&pool, work, None).await.unwrap();
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let find = |id: i64| tasks.iter().find(|t| t.id == id).unwrap();
+    assert_eq!(find(task).category_id, None, "FK sets category_id to NULL");
+    assert_ne!(
+        find(task).position,
+        find(uncategorized).position,
+        "Merged group is renumbered"
+    );
+}
+
+#[tokio::test]
+async fn test_delete_missing_category_is_not_found() {
+    let pool = setup_test_db().await;
+
+    let result = delete_category_impl(&pool, 9999, None).await;
+    assert!(matches!(result, Err(AppError::NotFound(ref msg)) if msg == "category 9999 not found"));
+}
+
+#[tokio::test]
+async fn test_delete_category_invalid_target_rolls_back() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    insert_task_at(&pool, "Task", None, Some(work), 0).await;
+
+    let result = delete_category_impl(&pool, work, Some(9999)).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let result = delete_category_impl(&pool, work, Some(work)).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM categories WHERE id = ?")
+        .bind(work)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1, "Category was not deleted");
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks[0].category_id, Some(work));
+}
+
+#[tokio::test]
+async fn test_state_fingerprint_tracks_logical_content() {
+    let first = setup_test_db().await;
+    let second = setup_test_db().await;
+
+    for pool in [&first, &second] {
+        let parent = create_task_helper(pool, task_input("Parent"))
+            .await
+            .unwrap();
+        create_task_helper(
+            pool,
+            CreateTaskInput {
+                parent_id: Some(parent.id),
+                ..task_input("Child")
+            },
+        )
+        .await
+        .unwrap();
+    }
+    // Write times differ, but content doesn't
+    sqlx::query("UPDATE tasks SET updated_at = updated_at + 1000")
+        .execute(&second)
+        .await
+        .unwrap();
+
+    let before = get_state_fingerprint_impl(&first).await.unwrap();
+    assert_eq!(before, get_state_fingerprint_impl(&second).await.unwrap());
+    assert_eq!(before.hash.len(), 64);
+
+    sqlx::query("UPDATE tasks SET title = 'Child (edited)' WHERE title = 'Child'")
+        .execute(&second)
+        .await
+        .unwrap();
+    let after = get_state_fingerprint_impl(&second).await.unwrap();
+    assert_ne!(before.hash, after.hash);
+}
+
+#[tokio::test]
+async fn test_update_task_clears_nullable_fields() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+
+    let parent = create_task_impl(&pool, task_input("Parent")).await.unwrap();
+    let task = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            description: Some("Notes".to_string()),
+            category_id: Some(work),
+            parent_id: Some(parent.id),
+            due_date: Some(1_700_000_000),
+            effort: Some(3),
+            ..task_input("Child")
+        },
+    )
+    .await
+    .unwrap();
+
+    // Omitted fields stay put
+    let unchanged = update_task_impl(
+        &pool,
+        task.id,
+        UpdateTaskInput {
+            title: Some("Renamed".to_string()),
+            ..Default::default()
+        },
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(unchanged.description.as_deref(), Some("Notes"));
+    assert_eq!(unchanged.category_id, Some(work));
+    assert_eq!(unchanged.parent_id, Some(parent.id));
+    assert_eq!(unchanged.due_date, Some(1_700_000_000));
+    assert_eq!(unchanged.effort, Some(3));
+
+    let cleared = update_task_impl(
+        &pool,
+        task.id,
+        UpdateTaskInput {
+            description: Some(None),
+            category_id: Some(None),
+            parent_id: Some(None),
+            due_date: Some(None),
+            effort: Some(None),
+            ..Default::default()
+        },
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(cleared.description, None);
+    assert_eq!(cleared.category_id, None);
+    assert_eq!(cleared.parent_id, None);
+    assert_eq!(cleared.due_date, None);
+    assert_eq!(cleared.effort, None);
+}
+
+#[tokio::test]
+async fn test_get_tasks_with_category_embeds_category() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+
+    insert_task_at(&pool, "Categorized", None, Some(work), 0).await;
+    insert_task_at(&pool, "Uncategorized", None, None, 1).await;
+
+    let tasks = get_tasks_with_category_impl(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 2);
+
+    let categorized = tasks
+        .iter()
+        .find(|t| t.task.title == "Categorized")
+        .unwrap();
+    let category = categorized.category.as_ref().expect("category embedded");
+    assert_eq!(category.id, work);
+    assert_eq!(category.name, "Work");
+    assert_eq!(category.color, "#e0af68");
+    assert_eq!(categorized.resolved_color, "#e0af68");
+
+    let uncategorized = tasks
+        .iter()
+        .find(|t| t.task.title == "Uncategorized")
+        .unwrap();
+    assert!(uncategorized.category.is_none());
+    assert_eq!(uncategorized.resolved_color, "#c0caf5");
+}
+
+#[tokio::test]
+async fn test_task_color_overrides_category_color() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+
+    let invalid = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            color: Some("blue".to_string()),
+            ..task_input("Invalid")
+        },
+    )
+    .await;
+    assert!(matches!(invalid, Err(AppError::ValidationError(_))));
+
+    let task = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(work),
+            color: Some("#7aa2f7".to_string()),
+            ..task_input("Highlighted")
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(task.color.as_deref(), Some("#7aa2f7"));
+    let tasks = get_tasks_with_category_impl(&pool).await.unwrap();
+    assert_eq!(tasks[0].resolved_color, "#7aa2f7");
+
+    // Clearing the override falls back to the category color
+    let update = UpdateTaskInput {
+        color: Some(None),
+        ..Default::default()
+    };
+    let cleared = update_task_impl(&pool, task.id, update, false)
+        .await
+        .unwrap();
+    assert_eq!(cleared.color, None);
+    let tasks = get_tasks_with_category_impl(&pool).await.unwrap();
+    assert_eq!(tasks[0].resolved_color, "#e0af68");
+}
+
+#[tokio::test]
+async fn test_task_tree_cache_reused_until_mutation() {
+    let pool = setup_test_db().await;
+    let cache = TreeCache::default();
+
+    let task = create_task_helper(&pool, task_input("First"))
+        .await
+        .unwrap();
+
+    let tree = get_task_tree_impl(&pool, &cache, true).await.unwrap();
+    assert_eq!(tree.len(), 1);
+    assert_eq!(cache.builds(), 1);
+
+    // Nothing changed: served from the cache, including the filtered variant
+    let again = get_task_tree_impl(&pool, &cache, true).await.unwrap();
+    assert_eq!(again.len(), 1);
+    get_task_tree_impl(&pool, &cache, false).await.unwrap();
+    assert_eq!(cache.builds(), 1);
+
+    // Any task write bumps the data version and invalidates the cache
+    create_task_helper(&pool, task_input("Second"))
+        .await
+        .unwrap();
+    let tree = get_task_tree_impl(&pool, &cache, true).await.unwrap();
+    assert_eq!(tree.len(), 2);
+    assert_eq!(cache.builds(), 2);
+
+    update_task_helper(
+        &pool,
+        task.id,
+        UpdateTaskInput {
+            title: Some("Renamed".to_string()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let tree = get_task_tree_impl(&pool, &cache, true).await.unwrap();
+    assert!(tree.iter().any(|node| node.task.title == "Renamed"));
+    assert_eq!(cache.builds(), 3);
+
+    delete_task_helper(&pool, task.id).await.unwrap();
+    let tree = get_task_tree_impl(&pool, &cache, true).await.unwrap();
+    assert_eq!(tree.len(), 1);
+    assert_eq!(cache.builds(), 4);
+}
+
+#[tokio::test]
+async fn test_get_upcoming_tasks_window() {
+    let pool = setup_test_db().await;
+    let now = chrono::Utc::now().timestamp();
+    let day = 86_400;
+
+    for (title, due_date) in [
+        ("Next week", Some(now + 7 * day)),
+        ("Tomorrow", Some(now + day)),
+        ("Someday", None),
+        ("Overdue", Some(now - day)),
+    ] {
+        create_task_helper(
+            &pool,
+            CreateTaskInput {
+                due_date,
+                ..task_input(title)
+            },
+        )
+        .await
+        .unwrap();
+    }
+    let done = create_task_helper(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(now + day),
+            ..task_input("Done tomorrow")
+        },
+    )
+    .await
+    .unwrap();
+    sqlx::query("UPDATE tasks SET is_done = 1 WHERE id = ?")
+        .bind(done.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let titles = |tasks: Vec<Task>| -> Vec<String> { tasks.into_iter().map(|t| t.title).collect() };
+
+    let within_two_days = get_upcoming_tasks_impl(&pool, 2).await.unwrap();
+    assert_eq!(titles(within_two_days), vec!["Tomorrow"]);
+
+    let within_ten_days = get_upcoming_tasks_impl(&pool, 10).await.unwrap();
+    assert_eq!(titles(within_ten_days), vec!["Tomorrow", "Next week"]);
+
+    let result = get_upcoming_tasks_impl(&pool, -1).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_get_tasks_for_day_respects_boundaries() {
+    let pool = setup_test_db().await;
+    // 2024-03-05 in UTC+02:00
+    let day_start = 1_709_589_600;
+    let day_end = day_start + 86_400 - 1;
+
+    for (title, due_date, priority) in [
+        ("Evening low", day_start + 20 * 3600, "Low"),
+        ("Morning low", day_start + 8 * 3600, "Low"),
+        ("Urgent", day_end, "Urgent"),
+        ("Done urgent", day_start, "Urgent"),
+        ("Previous day", day_start - 1, "Urgent"),
+        ("Next day", day_end + 1, "Urgent"),
+    ] {
+        let task = create_task_helper(
+            &pool,
+            CreateTaskInput {
+                due_date: Some(due_date),
+                priority: Some(priority.to_string()),
+                ..task_input(title)
+            },
+        )
+        .await
+        .unwrap();
+        if title.starts_with("Done") {
+            toggle_task_done_impl(&pool, task.id).await.unwrap();
+        }
+    }
+
+    let tasks = get_tasks_for_day_impl(&pool, day_start, day_end)
+        .await
+        .unwrap();
+    let titles: Vec<_> = tasks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        vec!["Urgent", "Morning low", "Evening low", "Done urgent"]
+    );
+
+    let result = get_tasks_for_day_impl(&pool, day_end, day_start).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_get_tasks_created_between_window() {
+    let pool = setup_test_db().await;
+    let (from, to) = (1_000, 2_000);
+
+    for (title, created_at) in [
+        ("Late", 2_000),
+        ("Before", 999),
+        ("Early", 1_000),
+        ("Middle", 1_500),
+        ("After", 2_001),
+    ] {
+        let id = insert_task_at(&pool, title, None, None, created_at).await;
+        sqlx::query("UPDATE tasks SET created_at = ? WHERE id = ?")
+            .bind(created_at)
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let tasks = get_tasks_created_between_impl(&pool, from, to)
+        .await
+        .unwrap();
+    let titles: Vec<_> = tasks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Early", "Middle", "Late"]);
+
+    let result = get_tasks_created_between_impl(&pool, to, from).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_get_root_tasks_in_position_order() {
+    let pool = setup_test_db().await;
+
+    let second = insert_task_at(&pool, "Second", None, None, 1).await;
+    insert_task_at(&pool, "First", None, None, 0).await;
+    insert_task_at(&pool, "Child", Some(second), None, 0).await;
+
+    let roots = get_root_tasks_impl(&pool).await.unwrap();
+    let titles: Vec<_> = roots.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["First", "Second"]);
+}
+
+#[tokio::test]
+async fn test_root_tasks_with_markers_flag_parents() {
+    let pool = setup_test_db().await;
+
+    let parent = insert_task_at(&pool, "Parent", None, None, 0).await;
+    let leaf = insert_task_at(&pool, "Leaf", None, None, 1).await;
+    let child = insert_task_at(&pool, "Child", Some(parent), None, 0).await;
+    insert_task_at(&pool, "Other child", Some(parent), None, 1).await;
+    insert_task_at(&pool, "Grandchild", Some(child), None, 0).await;
+
+    let markers = |nodes: Vec<TaskNode>| -> Vec<(i64, bool, i64)> {
+        nodes
+            .iter()
+            .map(|n| (n.task.id, n.has_children, n.child_count))
+            .collect()
+    };
+    let roots = get_root_tasks_with_markers_impl(&pool).await.unwrap();
+    assert_eq!(
+        markers(roots),
+        vec![(parent, true, 2), (leaf, false, 0)],
+        "Counts direct children only"
+    );
+
+    let children = get_child_tasks_impl(&pool, parent).await.unwrap();
+    assert_eq!(children.len(), 2);
+    assert_eq!((children[0].task.id, children[0].child_count), (child, 1));
+    assert!(children[0].has_children);
+    assert!(!children[1].has_children);
+    assert!(get_child_tasks_impl(&pool, leaf).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_all_tasks_roots_only() {
+    let pool = setup_test_db().await;
+
+    let root = insert_task_at(&pool, "Root", None, None, 0).await;
+    let child = insert_task_at(&pool, "Child", Some(root), None, 0).await;
+    insert_task_at(&pool, "Grandchild", Some(child), None, 0).await;
+
+    let all = get_all_tasks_impl(&pool, false).await.unwrap();
+    assert_eq!(all.len(), 3, "Default keeps subtasks");
+
+    let roots = get_all_tasks_impl(&pool, true).await.unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].id, root);
+}
+
+#[tokio::test]
+async fn test_get_tasks_modified_since_recap() {
+    let pool = setup_test_db().await;
+
+    // insert_task_at stamps rows at epoch 0, i.e. long before today
+    for (title, position) in [("A", 0), ("B", 1), ("C", 2)] {
+        insert_task_at(&pool, title, None, None, position).await;
+    }
+    let start_of_day = chrono::Utc::now().timestamp() - 60;
+
+    for (title, updated_at) in [("A", start_of_day + 10), ("C", start_of_day + 20)] {
+        sqlx::query("UPDATE tasks SET updated_at = ? WHERE title = ?")
+            .bind(updated_at)
+            .bind(title)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let recap = get_tasks_modified_since_impl(&pool, start_of_day)
+        .await
+        .unwrap();
+    let titles: Vec<_> = recap.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["C", "A"]);
+}
+
+#[tokio::test]
+async fn test_check_and_repair_foreign_keys() {
+    let pool = setup_test_db().await;
+    assert!(check_foreign_keys_impl(&pool).await.unwrap().is_empty());
+
+    insert_task_at(&pool, "Valid root", None, None, 0).await;
+
+    // Simulate an import that ran with enforcement off
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO tasks (title, priority, category_id, parent_id, position, created_at, updated_at)
+        VALUES ('Bad category', 2, 999, NULL, 0, 0, 0),
+               ('Bad parent', 2, NULL, 888, 0, 0, 0)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    drop(conn);
+
+    let violations = check_foreign_keys_impl(&pool).await.unwrap();
+    assert_eq!(violations.len(), 2);
+    assert!(violations.iter().all(|v| v.table == "tasks"));
+    let mut parents: Vec<_> = violations.iter().map(|v| v.parent.as_str()).collect();
+    parents.sort();
+    assert_eq!(parents, vec!["categories", "tasks"]);
+
+    assert_eq!(repair_foreign_keys_impl(&pool).await.unwrap(), 2);
+    assert!(check_foreign_keys_impl(&pool).await.unwrap().is_empty());
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let bad_category = tasks.iter().find(|t| t.title == "Bad category").unwrap();
+    assert_eq!(bad_category.category_id, None);
+    let valid_root = tasks.iter().find(|t| t.title == "Valid root").unwrap();
+    assert_ne!(
+        bad_category.position, valid_root.position,
+        "Cleared categories are renumbered into the uncategorized roots"
+    );
+    let bad_parent = tasks.iter().find(|t| t.title == "Bad parent").unwrap();
+    assert_eq!(bad_parent.parent_id, None);
+    assert_eq!(
+        bad_parent.position, 2.0,
+        "Promoted after the existing roots"
+    );
+}
+
+#[tokio::test]
+async fn test_quick_add_creates_parsed_task() {
+    let pool = setup_test_db().await;
+    let personal = category_id_by_name(&pool, "Personal").await;
+
+    let task = quick_add_impl(&pool, "Buy milk !high #Personal tomorrow", 0)
+        .await
+        .unwrap();
+    assert_eq!(task.title, "Buy milk");
+    assert_eq!(task.priority, Priority::High);
+    assert_eq!(task.category_id, Some(personal));
+    let now = chrono::Utc::now().timestamp();
+    let due = task.due_date.expect("due tomorrow");
+    assert!(due > now && due <= now + 2 * 86_400);
+
+    let result = quick_add_impl(&pool, "!low #work", 0).await;
+    assert!(
+        matches!(result, Err(AppError::ValidationError(_))),
+        "Nothing left for a title"
+    );
+}
+
+async fn complete_days_ago(pool: &SqlitePool, title: &str, days_ago: i64) {
+    let completed_at = chrono::Utc::now().timestamp() - days_ago * 86_400;
+    complete_at(pool, title, completed_at).await;
+}
+
+async fn complete_at(pool: &SqlitePool, title: &str, completed_at: i64) {
+    let id = create_task_helper(pool, task_input(title))
+        .await
+        .unwrap()
+        .id;
+    sqlx::query("UPDATE tasks SET is_done = 1, completed_at = ? WHERE id = ?")
+        .bind(completed_at)
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_completion_streak_empty_history() {
+    let pool = setup_test_db().await;
+    create_task_helper(&pool, task_input("Not done"))
+        .await
+        .unwrap();
+
+    assert_eq!(get_completion_streak_impl(&pool, 0).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_completion_streak_consecutive_days() {
+    let pool = setup_test_db().await;
+    complete_days_ago(&pool, "Today", 0).await;
+    complete_days_ago(&pool, "Today again", 0).await;
+    complete_days_ago(&pool, "Yesterday", 1).await;
+    complete_days_ago(&pool, "Two days ago", 2).await;
+
+    assert_eq!(get_completion_streak_impl(&pool, 0).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_completion_streak_resets_after_gap() {
+    let pool = setup_test_db().await;
+    complete_days_ago(&pool, "Today", 0).await;
+    complete_days_ago(&pool, "Yesterday", 1).await;
+    // Nothing three days ago breaks the streak
+    complete_days_ago(&pool, "Four days ago", 4).await;
+    complete_days_ago(&pool, "Five days ago", 5).await;
+    assert_eq!(get_completion_streak_impl(&pool, 0).await.unwrap(), 2);
+
+    let pool = setup_test_db().await;
+    complete_days_ago(&pool, "Yesterday", 1).await;
+    assert_eq!(
+        get_completion_streak_impl(&pool, 0).await.unwrap(),
+        0,
+        "No completion today means no current streak"
+    );
+
+    assert!(matches!(
+        get_completion_streak_impl(&pool, 24 * 60).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_import_markdown_builds_tree() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let text = "- [ ] Launch\n  - [x] Write docs\n  - [ ] Ship it\n- [x] Retro\n";
+
+    let tasks = import_markdown_impl(&pool, text, Some(work)).await.unwrap();
+    let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Launch", "Write docs", "Ship it", "Retro"]);
+    assert!(tasks.iter().all(|t| t.category_id == Some(work)));
+
+    let (launch, docs, ship, retro) = (&tasks[0], &tasks[1], &tasks[2], &tasks[3]);
+    assert_eq!(launch.parent_id, None);
+    assert_eq!(retro.parent_id, None);
+    assert_eq!(docs.parent_id, Some(launch.id));
+    assert_eq!(ship.parent_id, Some(launch.id));
+    assert_eq!((launch.position, retro.position), (0.0, 1.0));
+    assert_eq!((docs.position, ship.position), (0.0, 1.0));
+
+    assert!(docs.is_done && docs.completed_at.is_some());
+    assert!(retro.is_done);
+    assert!(!launch.is_done && launch.completed_at.is_none());
+    assert!(!ship.is_done);
+}
+
+#[tokio::test]
+async fn test_import_markdown_rejects_malformed_input() {
+    let pool = setup_test_db().await;
+
+    for text in [
+        "",
+        "- [ ] A\n  - [ ] B\n   - [ ] C",
+        "- [ ] A\nnot a checkbox",
+    ] {
+        let result = import_markdown_impl(&pool, text, None).await;
+        assert!(
+            matches!(result, Err(AppError::InvalidInput(_))),
+            "{:?} should be rejected",
+            text
+        );
+    }
+    assert!(
+        get_all_tasks_helper(&pool).await.unwrap().is_empty(),
+        "Nothing imported"
+    );
+}
+
+#[tokio::test]
+async fn test_cycle_time_stats() {
+    let pool = setup_test_db().await;
+    let created_at = 1_000;
+    for (title, duration) in [
+        ("Minute", 60),
+        ("Two minutes", 120),
+        ("Five minutes", 300),
+        ("Hour", 3600),
+        ("Too late", 100_000),
+    ] {
+        let id = create_task_helper(&pool, task_input(title))
+            .await
+            .unwrap()
+            .id;
+        sqlx::query("UPDATE tasks SET is_done = 1, created_at = ?, completed_at = ? WHERE id = ?")
+            .bind(created_at)
+            .bind(created_at + duration)
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    create_task_helper(&pool, task_input("Still open"))
+        .await
+        .unwrap();
+
+    let stats = get_cycle_time_stats_impl(&pool, 0, created_at + 3600)
+        .await
+        .unwrap();
+    assert_eq!(stats.count, 4, "Open and out-of-window tasks are left out");
+    assert_eq!(stats.average, Some(1020.0));
+    assert_eq!(stats.median, Some(210.0), "Midway between the middle two");
+    assert!((stats.p90.unwrap() - 2610.0).abs() < 1e-6);
+
+    let empty = get_cycle_time_stats_impl(&pool, 0, created_at)
+        .await
+        .unwrap();
+    assert_eq!(empty.count, 0);
+    assert_eq!(empty.median, None);
+}
+
+#[tokio::test]
+async fn test_completions_by_day_buckets_and_zero_fills() {
+    use chrono::TimeZone;
+
+    let pool = setup_test_db().await;
+    let day = |d: u32, h: u32| {
+        chrono::Utc
+            .with_ymd_and_hms(2024, 3, d, h, 0, 0)
+            .unwrap()
+            .timestamp()
+    };
+    complete_at(&pool, "Mar 1 morning", day(1, 9)).await;
+    complete_at(&pool, "Mar 1 evening", day(1, 20)).await;
+    complete_at(&pool, "Mar 3 late", day(3, 23)).await;
+    complete_at(&pool, "Out of range", day(6, 12)).await;
+
+    let from = day(1, 0);
+    let to = day(4, 23);
+    let days = get_completions_by_day_impl(&pool, from, to, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        days,
+        vec![
+            ("2024-03-01".to_string(), 2),
+            ("2024-03-02".to_string(), 0),
+            ("2024-03-03".to_string(), 1),
+            ("2024-03-04".to_string(), 0),
+        ]
+    );
+
+    // At UTC+2 the 23:00 completion falls on March 4th
+    let offset = 2 * 3600;
+    let local = get_completions_by_day_impl(&pool, from - offset, to - offset, 120)
+        .await
+        .unwrap();
+    assert_eq!(local[2], ("2024-03-03".to_string(), 0));
+    assert_eq!(local[3], ("2024-03-04".to_string(), 1));
+}
+
+#[tokio::test]
+async fn test_completions_by_day_rejects_bad_range() {
+    let pool = setup_test_db().await;
+
+    let result = get_completions_by_day_impl(&pool, 100, 0, 0).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let result = get_completions_by_day_impl(&pool, 0, 3660 * 86_400, 0).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_get_subtree_nests_descendants() {
+    let pool = setup_test_db().await;
+    let root = insert_task_at(&pool, "Root", None, None, 0).await;
+    let parent = insert_task_at(&pool, "Parent", Some(root), None, 0).await;
+    let child = insert_task_at(&pool, "Child", Some(parent), None, 0).await;
+    let grandchild = insert_task_at(&pool, "Grandchild", Some(child), None, 0).await;
+    insert_task_at(&pool, "Elsewhere", None, None, 1).await;
+
+    let tree = get_subtree_impl(&pool, parent).await.unwrap();
+    assert_eq!(tree.task.id, parent);
+    assert_eq!(
+        tree.task.parent_id,
+        Some(root),
+        "Root keeps its real parent"
+    );
+    assert_eq!(tree.subtasks.len(), 1);
+    assert_eq!(tree.subtasks[0].task.id, child);
+    assert_eq!(tree.subtasks[0].subtasks[0].task.id, grandchild);
+
+    let leaf = get_subtree_impl(&pool, grandchild).await.unwrap();
+    assert!(leaf.subtasks.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_subtree_missing_root_is_not_found() {
+    let pool = setup_test_db().await;
+
+    let result = get_subtree_impl(&pool, 999).await;
+    assert!(matches!(result, Err(AppError::NotFound(msg)) if msg == "Task 999 not found"));
+}
+
+#[tokio::test]
+async fn test_prune_empty_categories() {
+    let pool = setup_test_db().await;
+    let mut added = Vec::new();
+    for name in ["Errands", "Garden", "Hobbies", "Reading"] {
+        let (id,): (i64,) = sqlx::query_as(
+            "INSERT INTO categories (name, color, created_at, updated_at) VALUES (?, '#ff0000', 0, 0) RETURNING id",
+        )
+        .bind(name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        added.push(id);
+    }
+    let (garden, hobbies, reading) = (added[1], added[2], added[3]);
+
+    let mut input = task_input("Keeps Reading");
+    input.category_id = Some(reading);
+    create_task_helper(&pool, input).await.unwrap();
+    set_setting_impl(&pool, DEFAULT_CATEGORY_KEY, Some(&hobbies.to_string()))
+        .await
+        .unwrap();
+
+    let removed = prune_empty_categories_impl(&pool, &[garden]).await.unwrap();
+    assert_eq!(removed, vec!["Errands"]);
+
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT name FROM categories ORDER BY name")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        remaining,
+        vec![
+            "Garden",
+            "Hobbies",
+            "Other",
+            "Personal",
+            "Reading",
+            "Tech Guild",
+            "Work"
+        ]
+    );
+
+    let removed = prune_empty_categories_impl(&pool, &[garden]).await.unwrap();
+    assert!(removed.is_empty());
+}
+
+#[tokio::test]
+async fn test_prune_empty_categories_keeps_seeded_defaults() {
+    let pool = setup_test_db().await;
+    let (tasks,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tasks")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(tasks, 0, "Every seeded default starts out empty");
+
+    let removed = prune_empty_categories_impl(&pool, &[]).await.unwrap();
+    assert!(removed.is_empty());
+
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT name FROM categories ORDER BY name")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec!["Other", "Personal", "Tech Guild", "Work"]);
+}
+
+#[tokio::test]
+async fn test_search_tasks_scoped_to_subtree() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let home = insert_task_at(&pool, "Home", None, None, 0).await;
+    let office = insert_task_at(&pool, "Office", None, None, 1).await;
+    let kitchen = insert_task_at(&pool, "Kitchen report", Some(home), None, 0).await;
+    let nested = insert_task_at(&pool, "Paint report", Some(kitchen), Some(work), 0).await;
+    let outside = insert_task_at(&pool, "Quarterly report", Some(office), None, 0).await;
+
+    let ids = |page: TaskPage| page.items.into_iter().map(|t| t.id).collect::<Vec<_>>();
+
+    let all = search_tasks_impl(&pool, "report", None, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(ids(all), vec![kitchen, nested, outside]);
+
+    let in_home = search_tasks_impl(&pool, "REPORT", Some(home), None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        ids(in_home),
+        vec![kitchen, nested],
+        "Office matches excluded"
+    );
+
+    let in_kitchen = search_tasks_impl(&pool, "report", Some(kitchen), None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(ids(in_kitchen), vec![nested], "Root itself excluded");
+
+    let categorized = search_tasks_impl(&pool, "report", Some(home), Some(work), None, None)
+        .await
+        .unwrap();
+    assert_eq!(ids(categorized), vec![nested]);
+
+    let none = search_tasks_impl(&pool, "report", Some(outside), None, None, None)
+        .await
+        .unwrap();
+    assert!(none.items.is_empty());
+}
+
+#[tokio::test]
+async fn test_storage_info_counts_rows() {
+    let pool = setup_test_db().await;
+    create_task_helper(&pool, task_input("Stored"))
+        .await
+        .unwrap();
+
+    let info = get_storage_info_impl(&pool).await.unwrap();
+    assert!(info.db_bytes > 0);
+    assert_eq!(info.wal_bytes, 0, "In-memory databases have no WAL");
+
+    let rows = |table: &str| {
+        info.tables
+            .iter()
+            .find(|t| t.table == table)
+            .map(|t| t.rows)
+    };
+    assert_eq!(rows("tasks"), Some(1));
+    assert_eq!(rows("categories"), Some(4));
+    assert!(rows("task_activity").is_some());
+    assert!(rows("sqlite_sequence").is_none());
+}
+
+#[tokio::test]
+async fn test_storage_info_reports_wal_size() {
+    let db_path =
+        std::env::temp_dir().join(format!("eventually-storage-{}.db", std::process::id()));
+    let pool = crate::db::create_pool(db_path.clone()).await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    create_task_helper(&pool, task_input("Stored"))
+        .await
+        .unwrap();
+
+    let info = get_storage_info_impl(&pool).await.unwrap();
+    assert!(info.db_bytes > 0);
+    assert!(info.wal_bytes > 0, "Writes land in the WAL first");
+
+    pool.close().await;
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", db_path.display(), suffix));
+    }
+}
+
+#[tokio::test]
+async fn test_search_tasks_pages_are_stable() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let personal = category_id_by_name(&pool, "Personal").await;
+    // Same position in different groups, so the matches tie on the primary sort key
+    let mut expected = Vec::new();
+    for (i, category) in [None, Some(work), Some(personal)].into_iter().enumerate() {
+        for (j, parent_title) in ["A", "B"].into_iter().enumerate() {
+            let position = (i * 2 + j) as i32;
+            let parent = insert_task_at(&pool, parent_title, None, category, position).await;
+            expected.push(insert_task_at(&pool, "Match", Some(parent), category, 0).await);
+        }
+    }
+    expected.sort();
+
+    let mut seen = Vec::new();
+    let mut after_id = None;
+    loop {
+        let page = search_tasks_impl(&pool, "match", None, None, after_id, Some(4))
+            .await
+            .unwrap();
+        assert!(page.items.len() <= 4);
+        seen.extend(page.items.iter().map(|t| t.id));
+        match page.next_cursor {
+            Some(cursor) => after_id = Some(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(seen, expected, "Ties broken by id, each match exactly once");
+
+    for _ in 0..3 {
+        let again = search_tasks_impl(&pool, "match", None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            again.items.iter().map(|t| t.id).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(again.next_cursor, None);
+    }
+
+    let result = search_tasks_impl(&pool, "match", None, None, Some(999), Some(4)).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_remap_priority() {
+    let pool = setup_test_db().await;
+    let with_priority = |title: &str, priority: &str| CreateTaskInput {
+        priority: Some(priority.to_string()),
+        ..task_input(title)
+    };
+    let urgent1 = create_task_impl(&pool, with_priority("Fire", "Urgent"))
+        .await
+        .unwrap();
+    let urgent2 = create_task_impl(&pool, with_priority("Outage", "Urgent"))
+        .await
+        .unwrap();
+    let low = create_task_impl(&pool, with_priority("Someday", "Low"))
+        .await
+        .unwrap();
+
+    let count = remap_priority_impl(&pool, "Urgent", "High").await.unwrap();
+    assert_eq!(count, 2);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let priority_of = |id: i64| tasks.iter().find(|t| t.id == id).unwrap().priority;
+    assert_eq!(priority_of(urgent1.id), Priority::High);
+    assert_eq!(priority_of(urgent2.id), Priority::High);
+    assert_eq!(priority_of(low.id), Priority::Low);
+
+    assert_eq!(
+        remap_priority_impl(&pool, "Urgent", "High").await.unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_remap_priority_skips_locked_tasks() {
+    let pool = setup_test_db().await;
+    let with_priority = |title: &str| CreateTaskInput {
+        priority: Some("Urgent".to_string()),
+        ..task_input(title)
+    };
+    let open = create_task_impl(&pool, with_priority("Open"))
+        .await
+        .unwrap();
+    let locked = create_task_impl(&pool, with_priority("Locked"))
+        .await
+        .unwrap();
+    set_locked_impl(&pool, locked.id, true).await.unwrap();
+
+    let count = remap_priority_impl(&pool, "Urgent", "High").await.unwrap();
+    assert_eq!(count, 1);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let priority_of = |id: i64| tasks.iter().find(|t| t.id == id).unwrap().priority;
+    assert_eq!(priority_of(open.id), Priority::High);
+    assert_eq!(priority_of(locked.id), Priority::Urgent);
+}
+
+#[tokio::test]
+async fn test_remap_priority_rejects_invalid_values() {
+    let pool = setup_test_db().await;
+    create_task_helper(&pool, task_input("Task")).await.unwrap();
+
+    for (from, to) in [
+        ("Medium", "Critical"),
+        ("Critical", "Medium"),
+        ("Medium", "medium"),
+    ] {
+        let result = remap_priority_impl(&pool, from, to).await;
+        assert!(
+            matches!(result, Err(AppError::ValidationError(_))),
+            "{} -> {} should be rejected",
+            from,
+            to
+        );
+    }
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks[0].priority, Priority::Medium);
+}
+
+#[tokio::test]
+async fn test_path_set_on_create() {
+    let pool = setup_test_db().await;
+    let root = create_task_impl(&pool, task_input("Root")).await.unwrap();
+    assert_eq!(root.path, format!("/{}/", root.id));
+
+    let child = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(root.id),
+            ..task_input("Child")
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(child.path, format!("/{}/{}/", root.id, child.id));
+
+    let raw = insert_task_at(&pool, "Raw", Some(child.id), None, 0).await;
+    let paths: Vec<(i64, String)> = sqlx::query_as("SELECT id, path FROM tasks ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        paths,
+        vec![
+            (root.id, format!("/{}/", root.id)),
+            (child.id, format!("/{}/{}/", root.id, child.id)),
+            (raw, format!("/{}/{}/{}/", root.id, child.id, raw)),
+        ],
+        "Rows inserted outside the commands get a path too"
+    );
+}
+
+#[tokio::test]
+async fn test_path_follows_reparent() {
+    let pool = setup_test_db().await;
+    let a = insert_task_at(&pool, "A", None, None, 0).await;
+    let b = insert_task_at(&pool, "B", None, None, 1).await;
+    let child = insert_task_at(&pool, "Child", Some(a), None, 0).await;
+    let grandchild = insert_task_at(&pool, "Grandchild", Some(child), None, 0).await;
+
+    let moved = set_parent_impl(&pool, child, Some(b), None).await.unwrap();
+    assert_eq!(moved.path, format!("/{}/{}/", b, child));
+    let path_of = |id: i64| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query_scalar::<_, String>("SELECT path FROM tasks WHERE id = ?")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        }
+    };
+    assert_eq!(
+        path_of(grandchild).await,
+        format!("/{}/{}/{}/", b, child, grandchild),
+        "Descendants move with their ancestor"
+    );
+    assert_eq!(path_of(a).await, format!("/{}/", a));
+
+    let update = UpdateTaskInput {
+        parent_id: Some(None),
+        ..Default::default()
+    };
+    let promoted = update_task_impl(&pool, child, update, false).await.unwrap();
+    assert_eq!(promoted.path, format!("/{}/", child));
+    assert_eq!(
+        path_of(grandchild).await,
+        format!("/{}/{}/", child, grandchild)
+    );
+}
+
+#[tokio::test]
+async fn test_complete_and_add_followup() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let parent = insert_task_at(&pool, "Project", None, Some(work), 0).await;
+    let step = insert_task_at(&pool, "Draft", Some(parent), Some(work), 0).await;
+
+    let result = complete_and_add_followup_impl(&pool, step, task_input("Review draft"))
+        .await
+        .unwrap();
+    assert_eq!(result.completed.id, step);
+    assert!(result.completed.is_done);
+    assert!(result.completed.completed_at.is_some());
+    assert_eq!(result.followup.title, "Review draft");
+    assert_eq!(
+        result.followup.parent_id,
+        Some(parent),
+        "Inherits the parent"
+    );
+    assert_eq!(
+        result.followup.category_id,
+        Some(work),
+        "Inherits the category"
+    );
+    assert!(!result.followup.is_done);
+
+    let personal = category_id_by_name(&pool, "Personal").await;
+    let explicit = CreateTaskInput {
+        category_id: Some(personal),
+        ..task_input("Celebrate")
+    };
+    let result = complete_and_add_followup_impl(&pool, parent, explicit)
+        .await
+        .unwrap();
+    assert_eq!(result.followup.category_id, Some(personal));
+    assert_eq!(result.followup.parent_id, None);
+}
+
+#[tokio::test]
+async fn test_complete_and_add_followup_rolls_back_together() {
+    let pool = setup_test_db().await;
+    let task = create_task_helper(&pool, task_input("Step")).await.unwrap();
+
+    // Fails validation before anything is written
+    let result = complete_and_add_followup_impl(&pool, task.id, task_input("   ")).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    // Fails inside the transaction, after the completion was written
+    let dangling = CreateTaskInput {
+        parent_id: Some(999),
+        ..task_input("Orphan")
+    };
+    let result = complete_and_add_followup_impl(&pool, task.id, dangling).await;
+    assert!(matches!(result, Err(AppError::DatabaseError(_))));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 1, "No follow-up was created");
+    assert!(!tasks[0].is_done, "Completion was rolled back");
+    assert!(tasks[0].completed_at.is_none());
+
+    let result = complete_and_add_followup_impl(&pool, 999, task_input("Next")).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+    assert_eq!(get_all_tasks_helper(&pool).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_toggle_task_done() {
+    let pool = setup_test_db().await;
+    let task = create_task_helper(&pool, task_input("Water plants"))
+        .await
+        .unwrap();
+
+    let done = toggle_task_done_impl(&pool, task.id).await.unwrap();
+    assert!(done.is_done);
+    assert!(done.completed_at.is_some());
+    assert!(done.updated_at >= task.updated_at);
+
+    let undone = toggle_task_done_impl(&pool, task.id).await.unwrap();
+    assert!(!undone.is_done);
+    assert!(
+        undone.completed_at.is_none(),
+        "Reopening clears completed_at"
+    );
+
+    let result = toggle_task_done_impl(&pool, 9999).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_toggle_task_done_refuses_locked() {
+    let pool = setup_test_db().await;
+    let task = create_task_helper(&pool, task_input("Frozen"))
+        .await
+        .unwrap();
+    set_locked_impl(&pool, task.id, true).await.unwrap();
+
+    let result = toggle_task_done_impl(&pool, task.id).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    assert!(!get_all_tasks_helper(&pool).await.unwrap()[0].is_done);
+}
+
+#[tokio::test]
+async fn test_toggle_task_done_rolls_back_when_activity_fails() {
+    let pool = setup_test_db().await;
+    let task = create_task_helper(&pool, task_input("Checkbox"))
+        .await
+        .unwrap();
+
+    // A retry after a failed activity insert must not find the task already flipped
+    sqlx::query("DROP TABLE task_activity")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(toggle_task_done_impl(&pool, task.id).await.is_err());
+    assert!(!get_all_tasks_helper(&pool).await.unwrap()[0].is_done);
+}
+
+#[tokio::test]
+async fn test_set_locked_rolls_back_when_activity_fails() {
+    let pool = setup_test_db().await;
+    let task = create_task_helper(&pool, task_input("Padlock"))
+        .await
+        .unwrap();
+
+    sqlx::query("DROP TABLE task_activity")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(set_locked_impl(&pool, task.id, true).await.is_err());
+    assert!(!get_all_tasks_helper(&pool).await.unwrap()[0].is_locked);
+}
+
+#[tokio::test]
+async fn test_due_reminders_respect_offset() {
+    let pool = setup_test_db().await;
+    let due = 1_700_000_000;
+    let with_reminder = |title: &str, offset: Option<i32>| CreateTaskInput {
+        due_date: Some(due),
+        reminder_offset_minutes: offset,
+        ..task_input(title)
+    };
+    let early = create_task_impl(&pool, with_reminder("Early", Some(60)))
+        .await
+        .unwrap();
+    let plain = create_task_impl(&pool, with_reminder("Plain", None))
+        .await
+        .unwrap();
+    assert_eq!(early.reminder_offset_minutes, Some(60));
+
+    let ids = |tasks: Vec<Task>| tasks.into_iter().map(|t| t.id).collect::<Vec<_>>();
+
+    // An hour before the due date only the task with the offset is due for a reminder
+    let hour_before = get_due_reminders_impl(&pool, due - 3600 - 60, due - 3600 + 60)
+        .await
+        .unwrap();
+    assert_eq!(ids(hour_before), vec![early.id]);
+
+    let at_due = get_due_reminders_impl(&pool, due - 60, due + 60)
+        .await
+        .unwrap();
+    assert_eq!(ids(at_due), vec![plain.id]);
+
+    let both = get_due_reminders_impl(&pool, due - 7200, due)
+        .await
+        .unwrap();
+    assert_eq!(
+        ids(both),
+        vec![early.id, plain.id],
+        "Earliest reminder first"
+    );
+}
+
+#[tokio::test]
+async fn test_notified_reminders_skipped_until_due_date_changes() {
+    let pool = setup_test_db().await;
+    let due = 1_700_000_000;
+    let task = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            due_date: Some(due),
+            ..task_input("Dentist")
+        },
+    )
+    .await
+    .unwrap();
+    async fn due_ids(pool: &SqlitePool, from: i64, to: i64) -> Vec<i64> {
+        let tasks = get_due_reminders_impl(pool, from, to).await.unwrap();
+        tasks.into_iter().map(|t| t.id).collect()
+    }
+    assert_eq!(due_ids(&pool, due - 60, due + 60).await, vec![task.id]);
+
+    mark_reminder_sent_impl(&pool, task.id).await.unwrap();
+    assert!(due_ids(&pool, due - 60, due + 60).await.is_empty());
+    mark_reminder_sent_impl(&pool, task.id).await.unwrap();
+
+    // Moving the due date makes the task eligible again
+    let later = due + 86_400;
+    let update = UpdateTaskInput {
+        due_date: Some(Some(later)),
+        ..Default::default()
+    };
+    update_task_impl(&pool, task.id, update, false)
+        .await
+        .unwrap();
+    assert_eq!(due_ids(&pool, later - 60, later + 60).await, vec![task.id]);
+
+    let result = mark_reminder_sent_impl(&pool, 9999).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+    let undated = create_task_impl(&pool, task_input("Someday"))
+        .await
+        .unwrap();
+    let result = mark_reminder_sent_impl(&pool, undated.id).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_reminder_offset_validation() {
+    let pool = setup_test_db().await;
+    let input = CreateTaskInput {
+        reminder_offset_minutes: Some(-5),
+        ..task_input("Negative")
+    };
+    let result = create_task_impl(&pool, input).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let task = create_task_impl(&pool, task_input("Task")).await.unwrap();
+    let update = UpdateTaskInput {
+        reminder_offset_minutes: Some(Some(-1)),
+        ..Default::default()
+    };
+    let result = update_task_impl(&pool, task.id, update, false).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let update = UpdateTaskInput {
+        reminder_offset_minutes: Some(Some(30)),
+        ..Default::default()
+    };
+    let updated = update_task_impl(&pool, task.id, update, false)
+        .await
+        .unwrap();
+    assert_eq!(updated.reminder_offset_minutes, Some(30));
+}
+
+#[tokio::test]
+async fn test_archived_category_hidden_and_blocked() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let existing = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(work),
+            ..task_input("Filed before archiving")
+        },
+    )
+    .await
+    .unwrap();
+
+    let archived = set_category_archived_impl(&pool, work, true).await.unwrap();
+    assert!(archived.archived_at.is_some());
+
+    let active: Vec<String> = get_all_categories_impl(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(active, vec!["Other", "Personal", "Tech Guild"]);
+    let archived_list = get_archived_categories_impl(&pool).await.unwrap();
+    assert_eq!(archived_list.len(), 1);
+    assert_eq!(archived_list[0].id, work);
+
+    let result = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(work),
+            ..task_input("New work")
+        },
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(
+        tasks[0].id, existing.id,
+        "Existing tasks keep their category"
+    );
+    assert_eq!(tasks[0].category_id, Some(work));
+
+    let restored = set_category_archived_impl(&pool, work, false)
+        .await
+        .unwrap();
+    assert!(restored.archived_at.is_none());
+    assert!(create_task_impl(
+        &pool,
+        CreateTaskInput {
+            category_id: Some(work),
+            ..task_input("Back to work")
+        },
+    )
+    .await
+    .is_ok());
+
+    let result = set_category_archived_impl(&pool, 999, true).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_archived_categories_hidden_from_listings_and_writes() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let other = category_id_by_name(&pool, "Other").await;
+    let bundle = export_category_impl(&pool, work).await.unwrap();
+    let before = get_state_fingerprint_impl(&pool).await.unwrap();
+
+    set_category_archived_impl(&pool, work, true)
+        .await
+        .unwrap();
+    assert_ne!(
+        get_state_fingerprint_impl(&pool).await.unwrap().hash,
+        before.hash,
+        "Archiving changes the fingerprint"
+    );
+
+    let page = get_categories_paged_impl(&pool, 10, 0, false)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 3);
+    assert!(page.items.iter().all(|c| c.id != work));
+    let page = get_categories_paged_impl(&pool, 10, 0, true)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 4);
+
+    let counts = get_categories_with_counts_impl(&pool, false)
+        .await
+        .unwrap();
+    assert!(counts.iter().all(|c| c.category.id != work));
+    let counts = get_categories_with_counts_impl(&pool, true)
+        .await
+        .unwrap();
+    assert!(counts.iter().any(|c| c.category.id == work));
+
+    // Neither reassigning nor importing may file tasks under it
+    let result = delete_category_impl(&pool, other, Some(work)).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let result = import_data_impl(&pool, bundle).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_export_category_round_trips_into_fresh_db() {
+    let pool = setup_test_db().await;
+    let garden = create_category_impl(
+        &pool,
+        &CreateCategoryInput {
+            name: "Garden".to_string(),
+            color: "#9ece6a".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    let beds = insert_task_at(&pool, "Beds", None, Some(garden.id), 0).await;
+    let weed = insert_task_at(&pool, "Weed", Some(beds), Some(garden.id), 0).await;
+    insert_task_at(&pool, "Mulch", Some(beds), Some(garden.id), 1).await;
+    insert_task_at(&pool, "Dandelions", Some(weed), Some(garden.id), 0).await;
+    let work = category_id_by_name(&pool, "Work").await;
+    insert_task_at(&pool, "Elsewhere", None, Some(work), 0).await;
+
+    let bundle = export_category_impl(&pool, garden.id).await.unwrap();
+    assert_eq!(bundle.categories.len(), 1);
+    assert_eq!(bundle.categories[0].name, "Garden");
+    let titles: Vec<&str> = bundle.tasks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles.len(), 4, "Only the category's tasks");
+    assert!(!titles.contains(&"Elsewhere"));
+
+    // Survives the trip through JSON that the frontend makes
+    let json = serde_json::to_string(&bundle).unwrap();
+    let bundle: ExportBundle = serde_json::from_str(&json).unwrap();
+
+    let fresh = setup_test_db().await;
+    let summary = import_data_impl(&fresh, bundle).await.unwrap();
+    assert_eq!(summary.categories_created, 1);
+    assert_eq!(summary.tasks_created, 4);
+
+    let imported_garden = category_id_by_name(&fresh, "Garden").await;
+    let tasks = get_all_tasks_helper(&fresh).await.unwrap();
+    let by_title = |title: &str| tasks.iter().find(|t| t.title == title).unwrap();
+    let beds = by_title("Beds");
+    assert_eq!(beds.parent_id, None);
+    assert_eq!(by_title("Weed").parent_id, Some(beds.id));
+    assert_eq!(by_title("Mulch").parent_id, Some(beds.id));
+    assert!(by_title("Weed").position < by_title("Mulch").position);
+    assert_eq!(by_title("Dandelions").parent_id, Some(by_title("Weed").id));
+    assert!(tasks.iter().all(|t| t.category_id == Some(imported_garden)));
+}
+
+#[tokio::test]
+async fn test_export_category_edge_cases() {
+    let pool = setup_test_db().await;
+    let result = export_category_impl(&pool, 999).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    // A parent in another category is left behind and its child exported as a root
+    let work = category_id_by_name(&pool, "Work").await;
+    let personal = category_id_by_name(&pool, "Personal").await;
+    let parent = insert_task_at(&pool, "Project", None, Some(work), 0).await;
+    insert_task_at(&pool, "Errand", Some(parent), Some(personal), 0).await;
+
+    let mut bundle = export_category_impl(&pool, personal).await.unwrap();
+    assert_eq!(bundle.tasks.len(), 1);
+    assert_eq!(bundle.tasks[0].parent_id, None);
+
+    // Importing into the same db reuses the category by name
+    let summary = import_data_impl(&pool, bundle.clone()).await.unwrap();
+    assert_eq!(summary.categories_created, 0);
+    assert_eq!(summary.tasks_created, 1);
+
+    bundle.version += 1;
+    let result = import_data_impl(&pool, bundle).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_next_position_normalizes_instead_of_overflowing() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let first = insert_task_at(&pool, "First", None, Some(work), 0).await;
+    let last = insert_task_at(&pool, "Last", None, Some(work), 1).await;
+    // The last whole number a REAL position can step past exactly
+    sqlx::query("UPDATE tasks SET position = ? WHERE id = ?")
+        .bind(2f64.powi(53))
+        .bind(last)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Called directly: creating a task would compact the sparse group before this point
+    let mut conn = pool.acquire().await.unwrap();
+    let position = get_next_position(&mut conn, None, Some(work))
+        .await
+        .unwrap();
+    drop(conn);
+    assert_eq!(position, 2.0, "Group was renumbered before appending");
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let position_of = |id: i64| tasks.iter().find(|t| t.id == id).unwrap().position;
+    assert_eq!(position_of(first), 0.0);
+    assert_eq!(position_of(last), 1.0);
+}
+
+#[tokio::test]
+async fn test_create_compacts_positions_after_delete_churn() {
+    let pool = setup_test_db().await;
+    for i in 0..3 {
+        create_task_impl(&pool, task_input(&format!("Keep {}", i)))
+            .await
+            .unwrap();
+    }
+
+    // Deletes that skip delete_task leave their gaps behind. Each round appends a task
+    // and drops the one before it, so without compaction the end keeps moving out.
+    let mut previous: Option<i64> = None;
+    for i in 0..100 {
+        let task = create_task_impl(&pool, task_input(&format!("Churn {}", i)))
+            .await
+            .unwrap();
+        // Four tasks before each create, so at most 4 * 4 + 1
+        assert!(
+            task.position <= 17.0,
+            "Position {} in round {}",
+            task.position,
+            i
+        );
+        if let Some(id) = previous.replace(task.id) {
+            sqlx::query("DELETE FROM tasks WHERE id = ?")
+                .bind(id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let titles: Vec<_> = tasks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Keep 0", "Keep 1", "Keep 2", "Churn 99"]);
+}
+
+#[tokio::test]
+async fn test_reorder_past_the_end_clamps() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let a = insert_task_at(&pool, "A", None, Some(work), 0).await;
+    let b = insert_task_at(&pool, "B", None, Some(work), 1).await;
+
+    reorder_task_impl(&pool, a, i32::MAX, false).await.unwrap();
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let position_of = |id: i64| tasks.iter().find(|t| t.id == id).unwrap().position;
+    assert_eq!(position_of(b), 1.0, "Siblings are not rewritten");
+    assert_eq!(position_of(a), 2.0, "Lands at the end, not at i32::MAX");
+}
+
+#[tokio::test]
+async fn test_move_task_to_top_and_bottom() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let mut ids = Vec::new();
+    for (position, title) in ["A", "B", "C", "D"].iter().enumerate() {
+        ids.push(insert_task_at(&pool, title, None, Some(work), position as i32).await);
+    }
+    let elsewhere = insert_task_at(&pool, "Elsewhere", None, None, 0).await;
+
+    let order = |tasks: Vec<Task>| -> Vec<(String, f64)> {
+        tasks
+            .into_iter()
+            .filter(|t| t.category_id == Some(work))
+            .map(|t| (t.title, t.position))
+            .collect()
+    };
+
+    // Only the moved task gets a new position
+    move_task_to_top_impl(&pool, ids[2]).await.unwrap();
+    assert_eq!(
+        order(get_all_tasks_helper(&pool).await.unwrap()),
+        vec![
+            ("C".to_string(), -1.0),
+            ("A".to_string(), 0.0),
+            ("B".to_string(), 1.0),
+            ("D".to_string(), 3.0),
+        ]
+    );
+
+    move_task_to_bottom_impl(&pool, ids[0]).await.unwrap();
+    assert_eq!(
+        order(get_all_tasks_helper(&pool).await.unwrap()),
+        vec![
+            ("C".to_string(), -1.0),
+            ("B".to_string(), 1.0),
+            ("D".to_string(), 3.0),
+            ("A".to_string(), 4.0),
+        ]
+    );
+
+    // Already at the front, so nothing moves
+    move_task_to_top_impl(&pool, ids[2]).await.unwrap();
+    set_locked_impl(&pool, ids[1], true).await.unwrap();
+    assert!(move_task_to_top_impl(&pool, ids[1]).await.is_err());
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let position_of = |id: i64| tasks.iter().find(|t| t.id == id).unwrap().position;
+    assert_eq!(position_of(ids[2]), -1.0);
+    assert_eq!(position_of(ids[1]), 1.0, "Locked tasks stay put");
+    assert_eq!(position_of(elsewhere), 0.0, "Other groups are left alone");
+}
+
+#[tokio::test]
+async fn test_concurrent_reorders_keep_positions_distinct() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let mut ids = Vec::new();
+    for (position, title) in ["A", "B", "C", "D", "E"].iter().enumerate() {
+        ids.push(insert_task_at(&pool, title, None, Some(work), position as i32).await);
+    }
+
+    // Retried like the command retries them
+    let (first, second) = tokio::join!(
+        with_retry(|| reorder_task_impl(&pool, ids[0], 4, false)),
+        with_retry(|| reorder_task_impl(&pool, ids[4], 0, false)),
+    );
+    first.unwrap();
+    second.unwrap();
+
+    // Either order of the two moves gives the same result
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["E", "B", "C", "D", "A"]);
+    let mut positions: Vec<f64> = tasks.iter().map(|t| t.position).collect();
+    positions.dedup();
+    assert_eq!(positions.len(), 5, "No duplicate positions");
+}
+
+#[tokio::test]
+async fn test_get_category_colors() {
+    let pool = setup_test_db().await;
+    let other = category_id_by_name(&pool, "Other").await;
+    set_category_archived_impl(&pool, other, true)
+        .await
+        .unwrap();
+
+    let active = get_category_colors_impl(&pool, false).await.unwrap();
+    assert_eq!(active.len(), 3);
+    assert!(!active.contains_key(&other));
+
+    let colors = get_category_colors_impl(&pool, true).await.unwrap();
+    assert_eq!(colors.len(), 4, "Archived categories keep their color");
+    for (name, color) in [
+        ("Personal", "#9ece6a"),
+        ("Tech Guild", "#7aa2f7"),
+        ("Work", "#e0af68"),
+        ("Other", "#414868"),
+    ] {
+        let id = category_id_by_name(&pool, name).await;
+        assert_eq!(colors[&id], color, "color of {}", name);
+    }
+}
+
+#[tokio::test]
+async fn test_move_between_takes_the_midpoint() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let a = insert_task_at(&pool, "A", None, Some(work), 0).await;
+    let b = insert_task_at(&pool, "B", None, Some(work), 1).await;
+    let c = insert_task_at(&pool, "C", None, Some(work), 2).await;
+
+    let moved = move_between_impl(&pool, c, Some(a), Some(b)).await.unwrap();
+    assert_eq!(moved.position, 0.5);
+
+    let titles: Vec<String> = get_all_tasks_helper(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| t.title)
+        .collect();
+    assert_eq!(titles, vec!["A", "C", "B"]);
+
+    // One anchor is enough: its neighbour on the other side fills in
+    let moved = move_between_impl(&pool, a, None, Some(b)).await.unwrap();
+    assert_eq!(moved.position, 0.75);
+    let moved = move_between_impl(&pool, b, None, Some(c)).await.unwrap();
+    assert_eq!(
+        moved.position, -0.5,
+        "Nothing before C, so one step earlier"
+    );
+
+    // Anchors must be siblings of the moved task
+    let child = insert_task_at(&pool, "Child", Some(a), Some(work), 0).await;
+    let result = move_between_impl(&pool, child, Some(a), Some(b)).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let result = move_between_impl(&pool, c, None, None).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_move_between_rebalances_when_precision_runs_out() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let first = insert_task_at(&pool, "First", None, Some(work), 1).await;
+    let last = insert_task_at(&pool, "Last", None, Some(work), 2).await;
+
+    // Every insert lands right after `first`, halving the gap each time
+    let mut expected = vec![last];
+    for i in 0..100 {
+        let id = insert_task_at(&pool, &format!("Task {}", i), None, Some(work), 1000 + i).await;
+        move_between_impl(&pool, id, Some(first), Some(expected[0]))
+            .await
+            .unwrap();
+        expected.insert(0, id);
+    }
+    expected.insert(0, first);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), expected);
+    assert!(
+        tasks
+            .windows(2)
+            .all(|pair| pair[0].position < pair[1].position),
+        "Positions stay distinct"
+    );
+    let last_position = tasks.iter().find(|t| t.id == last).unwrap().position;
+    assert_ne!(last_position, 2.0, "Group was rebalanced along the way");
+}
+
+// Titles of a tree in depth-first position order, indented by depth. Siblings are
+// sorted here since build_task_tree doesn't keep their order.
+fn outline(tree: &TaskTree, depth: usize, lines: &mut Vec<String>) {
+    lines.push(format!("{}{}", "  ".repeat(depth), tree.task.title));
+    let mut subtasks: Vec<&TaskTree> = tree.subtasks.iter().collect();
+    subtasks.sort_by(|a, b| a.task.position.total_cmp(&b.task.position));
+    for subtask in subtasks {
+        outline(subtask, depth + 1, lines);
+    }
+}
+
+#[tokio::test]
+async fn test_save_and_instantiate_template() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+
+    let root = create_task_helper(&pool, task_input("Onboard client"))
+        .await
+        .unwrap();
+    let child = |title: &str, parent_id: i64| CreateTaskInput {
+        parent_id: Some(parent_id),
+        ..task_input(title)
+    };
+    let kickoff = create_task_impl(
+        &pool,
+        CreateTaskInput {
+            effort: Some(3),
+            ..child("Kickoff call", root.id)
+        },
+    )
+    .await
+    .unwrap();
+    create_task_impl(&pool, child("Send agenda", kickoff.id))
+        .await
+        .unwrap();
+    create_task_impl(&pool, child("Set up billing", root.id))
+        .await
+        .unwrap();
+    toggle_task_done_impl(&pool, kickoff.id).await.unwrap();
+
+    let template = save_template_impl(&pool, "  Client onboarding ", root.id)
+        .await
+        .unwrap();
+    assert_eq!(template.name, "Client onboarding");
+    let result = save_template_impl(&pool, "Client onboarding", root.id).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let copy = instantiate_template_impl(&pool, template.id, Some(work))
+        .await
+        .unwrap();
+    let original = get_subtree_impl(&pool, root.id).await.unwrap();
+    let (mut expected, mut actual) = (Vec::new(), Vec::new());
+    outline(&original, 0, &mut expected);
+    outline(&copy, 0, &mut actual);
+    assert_eq!(actual, expected);
+
+    let copied: Vec<Task> = get_all_tasks_helper(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|t| t.category_id == Some(work))
+        .collect();
+    assert_eq!(copied.len(), 4);
+    let original_ids = [root.id, kickoff.id, kickoff.id + 1, kickoff.id + 2];
+    assert!(
+        copied.iter().all(|t| !original_ids.contains(&t.id)),
+        "Fresh ids"
+    );
+    assert!(copied.iter().all(|t| !t.is_done), "Copies start open");
+    let kickoff_copy = copied.iter().find(|t| t.title == "Kickoff call").unwrap();
+    assert_eq!(kickoff_copy.effort, Some(3));
+    assert_eq!(copy.task.parent_id, None);
+
+    let result = instantiate_template_impl(&pool, template.id + 1, None).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_get_all_tasks_capped_reports_full_total() {
+    let pool = setup_test_db().await;
+    let mut roots = Vec::new();
+    for i in 0..5 {
+        roots.push(
+            create_task_helper(&pool, task_input(&format!("Task {}", i)))
+                .await
+                .unwrap(),
+        );
+    }
+    create_task_helper(
+        &pool,
+        CreateTaskInput {
+            parent_id: Some(roots[0].id),
+            ..task_input("Subtask")
+        },
+    )
+    .await
+    .unwrap();
+
+    let capped = get_all_tasks_capped_impl(&pool, false, 2).await.unwrap();
+    assert_eq!(capped.items.len(), 2);
+    assert_eq!(capped.total, 6);
+
+    let capped = get_all_tasks_capped_impl(&pool, true, 3).await.unwrap();
+    assert_eq!(
+        capped.items.iter().map(|t| t.id).collect::<Vec<_>>(),
+        roots[..3].iter().map(|t| t.id).collect::<Vec<_>>()
+    );
+    assert_eq!(capped.total, 5, "Only roots are counted");
+}
+
+#[tokio::test]
+async fn test_due_buckets() {
+    let pool = setup_test_db().await;
+    let now = chrono::Utc::now().timestamp();
+    // Pick the offset that makes it about noon locally, so "later today" always exists
+    let tz_offset_minutes = ((43_200 - now.rem_euclid(86_400)) / 60) as i32;
+    let hour = 3600;
+    let day = 24 * hour;
+
+    for (title, due_date) in [
+        ("Last week", Some(now - 7 * day)),
+        ("This morning", Some(now - 2 * hour)),
+        ("This afternoon", Some(now + 2 * hour)),
+        ("Tomorrow", Some(now + day)),
+        ("In six days", Some(now + 6 * day)),
+        ("In two weeks", Some(now + 14 * day)),
+        ("Someday", None),
+        ("Someday too", None),
+    ] {
+        create_task_helper(
+            &pool,
+            CreateTaskInput {
+                due_date,
+                ..task_input(title)
+            },
+        )
+        .await
+        .unwrap();
+    }
+    complete_at(&pool, "Done", now).await;
+
+    let buckets = get_due_buckets_impl(&pool, tz_offset_minutes)
+        .await
+        .unwrap();
+    assert_eq!(buckets.overdue, 2);
+    assert_eq!(buckets.today, 1);
+    assert_eq!(buckets.this_week, 2);
+    assert_eq!(buckets.later, 1);
+    assert_eq!(buckets.none, 2, "Done tasks aren't counted");
+}
+
+#[tokio::test]
+async fn test_set_parent_inserts_at_position() {
+    let pool = setup_test_db().await;
+    let parent = insert_task_at(&pool, "Parent", None, None, 0).await;
+    let first = insert_task_at(&pool, "First child", Some(parent), None, 0).await;
+    let second = insert_task_at(&pool, "Second child", Some(parent), None, 1).await;
+    let front = insert_task_at(&pool, "Front", None, None, 1).await;
+    let back = insert_task_at(&pool, "Back", None, None, 2).await;
+
+    let moved = set_parent_impl(&pool, front, Some(parent), Some(0))
+        .await
+        .unwrap();
+    assert_eq!(moved.position, 0.0);
+    let moved = set_parent_impl(&pool, back, Some(parent), Some(99))
+        .await
+        .unwrap();
+    assert_eq!(moved.position, 3.0, "Past the end appends");
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let children: Vec<i64> = tasks
+        .iter()
+        .filter(|t| t.parent_id == Some(parent))
+        .map(|t| t.id)
+        .collect();
+    assert_eq!(children, vec![front, first, second, back]);
+}
+
+#[tokio::test]
+async fn test_leaving_a_group_keeps_fractional_siblings_distinct() {
+    let pool = setup_test_db().await;
+    let a = insert_task_at(&pool, "A", None, None, 0).await;
+    let b = insert_task_at(&pool, "B", None, None, 1).await;
+    let c = insert_task_at(&pool, "C", None, None, 2).await;
+    let d = insert_task_at(&pool, "D", None, None, 3).await;
+
+    // Each task leaves from a midpoint, which a shift by one would land on a sibling
+    move_between_impl(&pool, c, Some(a), Some(b)).await.unwrap();
+    set_parent_impl(&pool, c, Some(d), None).await.unwrap();
+    move_between_impl(&pool, d, Some(a), Some(b)).await.unwrap();
+    merge_tasks_impl(&pool, a, d).await.unwrap();
+
+    let roots: Vec<(String, f64)> = get_all_tasks_helper(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|t| t.parent_id.is_none())
+        .map(|t| (t.title, t.position))
+        .collect();
+    assert_eq!(roots, vec![("A".to_string(), 0.0), ("B".to_string(), 1.0)]);
+}
+
+#[tokio::test]
+async fn test_duplicate_task_shallow_copies_as_next_sibling() {
+    let pool = setup_test_db().await;
+    let original = insert_task_at(&pool, "Original", None, None, 0).await;
+    insert_task_at(&pool, "Child", Some(original), None, 0).await;
+    let next = insert_task_at(&pool, "Next", None, None, 1).await;
+    sqlx::query(
+        "UPDATE tasks SET is_done = 1, completed_at = 100, description = 'Notes' WHERE id = ?",
+    )
+    .bind(original)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let copy = duplicate_task_shallow_impl(&pool, original).await.unwrap();
+    assert_ne!(copy.id, original);
+    assert_eq!(copy.title, "Original");
+    assert_eq!(copy.description.as_deref(), Some("Notes"));
+    assert_eq!(copy.parent_id, None);
+    assert_eq!(copy.position, 1.0);
+    assert!(!copy.is_done);
+    assert_eq!(copy.completed_at, None);
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let roots: Vec<i64> = tasks
+        .iter()
+        .filter(|t| t.parent_id.is_none())
+        .map(|t| t.id)
+        .collect();
+    assert_eq!(roots, vec![original, copy.id, next]);
+    assert!(
+        tasks.iter().all(|t| t.parent_id != Some(copy.id)),
+        "Children aren't copied"
+    );
+    assert_eq!(tasks.len(), 4);
+
+    let result = duplicate_task_shallow_impl(&pool, 9999).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_delete_task_moves_subtree_to_trash() {
+    let pool = setup_test_db().await;
+    let parent = insert_task_at(&pool, "Parent", None, None, 0).await;
+    insert_task_at(&pool, "Child", Some(parent), None, 0).await;
+
+    delete_task_impl(&pool, parent, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+    assert!(get_all_tasks_helper(&pool).await.unwrap().is_empty());
+
+    let trash = list_trash_impl(&pool).await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].task_id, parent);
+    assert_eq!(trash[0].title, "Parent");
+    assert_eq!(trash[0].task_count, 2);
+
+    // Reparented children stay behind, so only the task itself is trashed
+    let other = insert_task_at(&pool, "Other", None, None, 1).await;
+    insert_task_at(&pool, "Kept", Some(other), None, 0).await;
+    delete_task_impl(&pool, other, false, DeleteStrategy::Reparent)
+        .await
+        .unwrap();
+    assert_eq!(list_trash_impl(&pool).await.unwrap()[0].task_count, 1);
+}
+
+#[tokio::test]
+async fn test_restore_from_trash() {
+    let pool = setup_test_db().await;
+    let grandparent = insert_task_at(&pool, "Grandparent", None, None, 0).await;
+    let parent = insert_task_at(&pool, "Parent", Some(grandparent), None, 0).await;
+    let child = insert_task_at(&pool, "Child", Some(parent), None, 0).await;
+    insert_task_at(&pool, "Sibling", Some(grandparent), None, 1).await;
+
+    delete_task_impl(&pool, parent, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+    let entry = list_trash_impl(&pool).await.unwrap()[0].id;
+    let restored = restore_from_trash_impl(&pool, entry).await.unwrap();
+    assert_eq!(restored.id, parent, "Original id comes back");
+    assert_eq!(restored.parent_id, Some(grandparent));
+    assert_eq!(restored.position, 1.0, "Appended after the sibling");
+    assert_eq!(restored.path, format!("/{}/{}/", grandparent, parent));
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let restored_child = tasks.iter().find(|t| t.id == child).unwrap();
+    assert_eq!(restored_child.parent_id, Some(parent));
+    assert!(list_trash_impl(&pool).await.unwrap().is_empty());
+    let result = restore_from_trash_impl(&pool, entry).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    // With its parent gone for good, a restored task becomes a root
+    delete_task_impl(&pool, child, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+    let entry = list_trash_impl(&pool).await.unwrap()[0].id;
+    delete_task_impl(&pool, grandparent, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+    let restored = restore_from_trash_impl(&pool, entry).await.unwrap();
+    assert_eq!(restored.id, child);
+    assert_eq!(restored.parent_id, None);
+}
+
+#[tokio::test]
+async fn test_restore_from_trash_renumbers_merged_sibling_groups() {
+    let pool = setup_test_db().await;
+    let work = category_id_by_name(&pool, "Work").await;
+    let personal = category_id_by_name(&pool, "Personal").await;
+    let parent = insert_task_at(&pool, "Parent", None, None, 0).await;
+    let first = insert_task_at(&pool, "First", Some(parent), Some(work), 0).await;
+    let second = insert_task_at(&pool, "Second", Some(parent), Some(personal), 0).await;
+
+    delete_task_impl(&pool, parent, false, DeleteStrategy::Cascade)
+        .await
+        .unwrap();
+    // Both children lose their category, so they come back into one group
+    delete_category_impl(&pool, work, None).await.unwrap();
+    delete_category_impl(&pool, personal, None).await.unwrap();
+
+    let entry = list_trash_impl(&pool).await.unwrap()[0].id;
+    restore_from_trash_impl(&pool, entry).await.unwrap();
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let child = |id: i64| tasks.iter().find(|t| t.id == id).unwrap();
+    assert_eq!(child(first).category_id, None);
+    assert_eq!(child(second).category_id, None);
+    assert_eq!(child(first).position, 0.0);
+    assert_eq!(child(second).position, 1.0);
+}
+
+#[tokio::test]
+async fn test_empty_trash_removes_expired_entries() {
+    let pool = setup_test_db().await;
+    let old = insert_task_at(&pool, "Old", None, None, 0).await;
+    let recent = insert_task_at(&pool, "Recent", None, None, 1).await;
+    for id in [old, recent] {
+        delete_task_impl(&pool, id, false, DeleteStrategy::Cascade)
+            .await
+            .unwrap();
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let day = 86_400;
+    sqlx::query("UPDATE trash SET trashed_at = ? WHERE task_id = ?")
+        .bind(now - 40 * day)
+        .bind(old)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(empty_trash_impl(&pool, now - 30 * day).await.unwrap(), 1);
+    let trash = list_trash_impl(&pool).await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].task_id, recent);
+}
+
+#[tokio::test]
+async fn test_archive_completed_before_trashes_only_stale_done_tasks() {
+    let pool = setup_test_db().await;
+    let cutoff = 1_700_000_000;
+
+    let old = insert_task_at(&pool, "Old done", None, None, 0).await;
+    insert_task_at(&pool, "Open", None, None, 1).await;
+    let recent = insert_task_at(&pool, "Recent done", None, None, 2).await;
+    let old_parent = insert_task_at(&pool, "Old parent", None, None, 3).await;
+    let old_child = insert_task_at(&pool, "Old child", Some(old_parent), None, 0).await;
+    let busy_parent = insert_task_at(&pool, "Old with open child", None, None, 4).await;
+    insert_task_at(&pool, "Open child", Some(busy_parent), None, 0).await;
+    for (id, completed_at) in [
+        (old, cutoff - 100),
+        (recent, cutoff + 100),
+        (old_parent, cutoff - 100),
+        (old_child, cutoff - 50),
+        (busy_parent, cutoff - 100),
+    ] {
+        sqlx::query("UPDATE tasks SET is_done = 1, completed_at = ? WHERE id = ?")
+            .bind(completed_at)
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let archived = archive_completed_before_impl(&pool, cutoff).await.unwrap();
+    assert_eq!(archived, 3, "Old parent goes with its old child");
+
+    let tasks = get_all_tasks_helper(&pool).await.unwrap();
+    let roots: Vec<_> = tasks
+        .iter()
+        .filter(|t| t.parent_id.is_none())
+        .map(|t| (t.title.as_str(), t.position))
+        .collect();
+    assert_eq!(
+        roots,
+        vec![
+            ("Open", 0.0),
+            ("Recent done", 1.0),
+            ("Old with open child", 2.0),
+        ]
+    );
+    assert!(tasks.iter().any(|t| t.title == "Open child"));
+    let trash = list_trash_impl(&pool).await.unwrap();
+    let mut trashed: Vec<_> = trash.iter().map(|e| (e.task_id, e.task_count)).collect();
+    trashed.sort();
+    assert_eq!(trashed, vec![(old, 1), (old_parent, 2)]);
+}
+
+#[tokio::test]
+async fn test_archive_completed_before_skips_locked_tasks() {
+    let pool = setup_test_db().await;
+    let cutoff = 1_700_000_000;
+
+    let locked = insert_task_at(&pool, "Locked done", None, None, 0).await;
+    let parent = insert_task_at(&pool, "Parent", None, None, 1).await;
+    let locked_child = insert_task_at(&pool, "Locked child", Some(parent), None, 0).await;
+    for id in [locked, parent, locked_child] {
+        sqlx::query("UPDATE tasks SET is_done = 1, completed_at = ? WHERE id = ?")
+            .bind(cutoff - 100)
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    set_locked_impl(&pool, locked, true).await.unwrap();
+    set_locked_impl(&pool, locked_child, true).await.unwrap();
+
+    let archived = archive_completed_before_impl(&pool, cutoff).await.unwrap();
+    assert_eq!(archived, 0, "A locked subtask keeps its parent too");
+    assert_eq!(get_all_tasks_helper(&pool).await.unwrap().len(), 3);
+    assert!(list_trash_impl(&pool).await.unwrap().is_empty());
 }
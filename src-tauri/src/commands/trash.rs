@@ -0,0 +1,300 @@
+use crate::commands::activity::log_activity;
+use crate::commands::maintenance::normalize_group;
+use crate::commands::tasks::get_next_position;
+use crate::db::with_retry;
+use crate::error::AppError;
+use crate::models::{Task, TrashEntry};
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::State;
+
+/// How long deleted tasks stay in the trash before `empty_expired_trash` removes them.
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+// Copy task `id` and its descendants into the trash ahead of deleting them. Does
+// nothing if the task doesn't exist.
+pub(crate) async fn move_to_trash(conn: &mut SqliteConnection, id: i64) -> Result<(), AppError> {
+    // A path sorts after every prefix of itself, so parents come first
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"
+        WITH root AS (SELECT path FROM tasks WHERE id = ?)
+        SELECT tasks.* FROM tasks, root
+        WHERE substr(tasks.path, 1, length(root.path)) = root.path
+        ORDER BY tasks.path
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let Some(root) = tasks.first() else {
+        return Ok(());
+    };
+
+    let json = serde_json::to_string(&tasks)
+        .map_err(|e| AppError::DatabaseError(format!("Failed to serialize trash: {}", e)))?;
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query(
+        "INSERT INTO trash (task_id, title, task_count, tasks, trashed_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(root.id)
+    .bind(&root.title)
+    .bind(tasks.len() as i64)
+    .bind(json)
+    .bind(now)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Trashed tasks, most recently deleted first.
+pub(crate) async fn list_trash_impl(pool: &SqlitePool) -> Result<Vec<TrashEntry>, AppError> {
+    let entries = sqlx::query_as::<_, TrashEntry>(
+        r#"
+        SELECT id, task_id, title, task_count, trashed_at
+        FROM trash
+        ORDER BY trashed_at DESC, id DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn list_trash(pool: State<'_, SqlitePool>) -> Result<Vec<TrashEntry>, AppError> {
+    list_trash_impl(pool.inner()).await
+}
+
+// `id` if `sql`, selecting by a single id, still finds its row
+async fn if_exists(
+    conn: &mut SqliteConnection,
+    sql: &str,
+    id: Option<i64>,
+) -> Result<Option<i64>, AppError> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    let found: Option<i64> = sqlx::query_scalar(sql)
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+    Ok(found.map(|_| id))
+}
+
+/// Puts trash entry `id` back with the tasks' original ids and returns the restored
+/// task. It goes back under its original parent if that still exists, else becomes a
+/// root, and is appended to the end of its sibling group either way.
+pub(crate) async fn restore_from_trash_impl(pool: &SqlitePool, id: i64) -> Result<Task, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let json: String = sqlx::query_scalar("SELECT tasks FROM trash WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Trash entry {} not found", id)))?;
+    let tasks: Vec<Task> = serde_json::from_str(&json)
+        .map_err(|e| AppError::DatabaseError(format!("Failed to read trash entry: {}", e)))?;
+
+    // References that went away while the tasks were trashed are dropped
+    let mut category_ids = Vec::with_capacity(tasks.len());
+    for task in &tasks {
+        category_ids.push(
+            if_exists(
+                &mut tx,
+                "SELECT 1 FROM categories WHERE id = ?",
+                task.category_id,
+            )
+            .await?,
+        );
+    }
+    let positions = descendant_positions(&tasks, &category_ids);
+
+    let now = chrono::Utc::now().timestamp();
+    for (index, task) in tasks.iter().enumerate() {
+        let parent_id = if index == 0 {
+            if_exists(&mut tx, "SELECT 1 FROM tasks WHERE id = ?", task.parent_id).await?
+        } else {
+            task.parent_id
+        };
+        let category_id = category_ids[index];
+        let series_id = if_exists(
+            &mut tx,
+            "SELECT 1 FROM task_series WHERE id = ?",
+            task.series_id,
+        )
+        .await?;
+        // Another task may have taken the external id since
+        let external_id = match &task.external_id {
+            Some(external_id) => {
+                let taken: Option<i64> =
+                    sqlx::query_scalar("SELECT 1 FROM tasks WHERE external_id = ?")
+                        .bind(external_id)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                task.external_id.clone().filter(|_| taken.is_none())
+            }
+            None => None,
+        };
+        let position = if index == 0 {
+            get_next_position(&mut tx, parent_id, category_id).await?
+        } else {
+            positions[index]
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (id, title, description, category_id, priority, parent_id, is_done, position, due_date, created_at, updated_at, completed_at, external_id, effort, series_id, is_locked, reminder_offset_minutes, color)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(task.id)
+        .bind(&task.title)
+        .bind(&task.description)
+        .bind(category_id)
+        .bind(task.priority)
+        .bind(parent_id)
+        .bind(task.is_done)
+        .bind(position)
+        .bind(task.due_date)
+        .bind(task.created_at)
+        .bind(now)
+        .bind(task.completed_at)
+        .bind(external_id)
+        .bind(task.effort)
+        .bind(series_id)
+        .bind(task.is_locked)
+        .bind(task.reminder_offset_minutes)
+        .bind(&task.color)
+        .execute(&mut *tx)
+        .await?;
+        log_activity(&mut *tx, task.id, "restored", None).await?;
+    }
+
+    sqlx::query("DELETE FROM trash WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    let root_id = tasks
+        .first()
+        .map(|task| task.id)
+        .ok_or_else(|| AppError::DatabaseError(format!("Trash entry {} is empty", id)))?;
+    let restored = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(root_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(restored)
+}
+
+// Positions for the restored descendants (every task after the first). They return to
+// sibling groups only they occupy, but siblings whose categories were deleted since now
+// share a group, so each group is renumbered 0..n in its old order.
+fn descendant_positions(tasks: &[Task], category_ids: &[Option<i64>]) -> Vec<f64> {
+    let group = |i: usize| (tasks[i].parent_id, category_ids[i]);
+    let mut order: Vec<usize> = (1..tasks.len()).collect();
+    order.sort_by(|&a, &b| {
+        group(a)
+            .cmp(&group(b))
+            .then(tasks[a].position.total_cmp(&tasks[b].position))
+            .then(tasks[a].id.cmp(&tasks[b].id))
+    });
+
+    let mut positions = vec![0.0; tasks.len()];
+    let mut next = 0.0;
+    for (rank, &i) in order.iter().enumerate() {
+        if rank > 0 && group(order[rank - 1]) != group(i) {
+            next = 0.0;
+        }
+        positions[i] = next;
+        next += 1.0;
+    }
+    positions
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(pool: State<'_, SqlitePool>, id: i64) -> Result<Task, AppError> {
+    with_retry(|| restore_from_trash_impl(pool.inner(), id)).await
+}
+
+/// Permanently removes entries trashed before `older_than`. Returns how many were removed.
+pub(crate) async fn empty_trash_impl(pool: &SqlitePool, older_than: i64) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM trash WHERE trashed_at < ?")
+        .bind(older_than)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[tauri::command]
+pub async fn empty_trash(pool: State<'_, SqlitePool>, older_than: i64) -> Result<u64, AppError> {
+    with_retry(|| empty_trash_impl(pool.inner(), older_than)).await
+}
+
+/// Moves done tasks completed before `cutoff` to the trash, all in one transaction, and
+/// returns how many were moved. A task only goes if its whole subtree qualifies, so open,
+/// recently finished or locked subtasks keep their parent in place.
+pub(crate) async fn archive_completed_before_impl(
+    pool: &SqlitePool,
+    cutoff: i64,
+) -> Result<u64, AppError> {
+    let mut tx = pool.begin().await?;
+
+    // Every descendant of a match matches too, and parents sort first by path
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"
+        SELECT * FROM tasks AS t
+        WHERE t.is_done = 1
+        AND t.completed_at < ?
+        AND NOT EXISTS (
+            SELECT 1 FROM tasks AS d
+            WHERE substr(d.path, 1, length(t.path)) = t.path
+            AND (d.is_done = 0 OR d.completed_at IS NULL OR d.completed_at >= ? OR d.is_locked = 1)
+        )
+        ORDER BY t.path
+        "#,
+    )
+    .bind(cutoff)
+    .bind(cutoff)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for task in &tasks {
+        // Already trashed along with an ancestor
+        if task
+            .parent_id
+            .is_some_and(|parent_id| tasks.iter().any(|t| t.id == parent_id))
+        {
+            continue;
+        }
+        move_to_trash(&mut tx, task.id).await?;
+        sqlx::query("DELETE FROM tasks WHERE id = ?")
+            .bind(task.id)
+            .execute(&mut *tx)
+            .await?;
+        log_activity(&mut *tx, task.id, "deleted", None).await?;
+        normalize_group(&mut tx, task.parent_id, task.category_id).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(tasks.len() as u64)
+}
+
+#[tauri::command]
+pub async fn archive_completed_before(
+    pool: State<'_, SqlitePool>,
+    cutoff: i64,
+) -> Result<u64, AppError> {
+    with_retry(|| archive_completed_before_impl(pool.inner(), cutoff)).await
+}
+
+/// Removes entries past the retention window; run at startup.
+pub async fn empty_expired_trash(pool: &SqlitePool) -> Result<u64, AppError> {
+    let cutoff = chrono::Utc::now().timestamp() - TRASH_RETENTION_DAYS * 86_400;
+    empty_trash_impl(pool, cutoff).await
+}
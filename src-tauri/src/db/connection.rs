@@ -2,18 +2,97 @@ use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, S
 use sqlx::SqlitePool;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
+
+/// Tunables for the SQLite connection pool.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    /// How long a connection waits on a locked database before giving up with SQLITE_BUSY
+    pub busy_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            busy_timeout: Duration::from_millis(5000),
+        }
+    }
+}
 
 pub async fn create_pool(db_path: PathBuf) -> Result<SqlitePool, sqlx::Error> {
+    create_pool_with_config(db_path, PoolConfig::default()).await
+}
+
+pub async fn create_pool_with_config(
+    db_path: PathBuf,
+    config: PoolConfig,
+) -> Result<SqlitePool, sqlx::Error> {
     let db_url = format!("sqlite://{}", db_path.display());
 
     let options = SqliteConnectOptions::from_str(&db_url)?
         .create_if_missing(true)
         .journal_mode(SqliteJournalMode::Wal) // Write-Ahead Logging for better concurrency
         .synchronous(SqliteSynchronous::Normal) // Balance between safety and performance
+        .busy_timeout(config.busy_timeout) // Wait for competing writers instead of failing
         .foreign_keys(true); // Enable foreign key constraints
 
     SqlitePoolOptions::new()
-        .max_connections(5)
+        .max_connections(config.max_connections)
         .connect_with(options)
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+
+    #[tokio::test]
+    async fn test_overlapping_writes_wait_for_lock() {
+        let db_path =
+            std::env::temp_dir().join(format!("eventually-busy-{}.db", std::process::id()));
+        let pool = create_pool(db_path.clone()).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        // First writer holds the write lock for a while before committing
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query(
+            "INSERT INTO categories (name, color, created_at, updated_at) VALUES ('A', '#000000', 0, 0)",
+        )
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        // Second writer on another connection has to wait for the first to finish
+        let second_pool = pool.clone();
+        let second = tokio::spawn(async move {
+            sqlx::query(
+                "INSERT INTO categories (name, color, created_at, updated_at) VALUES ('B', '#ffffff', 0, 0)",
+            )
+            .execute(&second_pool)
+            .await
+        });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        tx.commit().await.unwrap();
+
+        assert!(
+            second.await.unwrap().is_ok(),
+            "Second write should not fail with SQLITE_BUSY"
+        );
+
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM categories WHERE name IN ('A', 'B')")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 2);
+
+        pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", db_path.display(), suffix));
+        }
+    }
+}
@@ -1,5 +1,11 @@
 pub mod connection;
+pub mod retry;
 pub mod schema;
+pub mod timing;
+pub mod watch;
 
 pub use connection::create_pool;
+pub(crate) use retry::with_retry;
 pub use schema::run_migrations;
+pub(crate) use timing::timed;
+pub use watch::DataVersionWatcher;
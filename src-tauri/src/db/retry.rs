@@ -0,0 +1,108 @@
+use crate::error::AppError;
+use std::future::Future;
+use std::time::Duration;
+
+// Total tries including the first; with doubling this waits at most 25 + 50 + 100ms
+const MAX_ATTEMPTS: u32 = 4;
+const BASE_DELAY: Duration = Duration::from_millis(25);
+
+/// Runs the write `op`, retrying with exponential backoff while it fails because the
+/// database is busy. Any other error is returned immediately.
+///
+/// `op` is called once per attempt, so it must build a fresh future (and transaction)
+/// each time. Only wrap operations that are safe to run again after a busy error: a
+/// single statement, or one transaction holding every write. An `op` that commits one
+/// write and then fails on the next would apply the first write twice.
+pub(crate) async fn with_retry<T, F, Fut>(op: F) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    with_retry_config(MAX_ATTEMPTS, BASE_DELAY, op).await
+}
+
+async fn with_retry_config<T, F, Fut>(
+    max_attempts: u32,
+    base_delay: Duration,
+    mut op: F,
+) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(err) if err.is_busy() && attempt < max_attempts => {
+                log::debug!(
+                    "database busy on attempt {}/{}, retrying in {}ms",
+                    attempt,
+                    max_attempts,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::BUSY_MESSAGE;
+    use std::cell::Cell;
+
+    fn busy() -> AppError {
+        AppError::DatabaseError(BUSY_MESSAGE.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_retries_busy_then_succeeds() {
+        let attempts = Cell::new(0);
+        let result = with_retry_config(4, Duration::from_millis(1), || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                if attempt == 1 {
+                    Err(busy())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_are_not_retried() {
+        let attempts = Cell::new(0);
+        let result: Result<(), AppError> = with_retry_config(4, Duration::from_millis(1), || {
+            attempts.set(attempts.get() + 1);
+            async { Err(AppError::NotFound("Task 1 not found".to_string())) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let attempts = Cell::new(0);
+        let result: Result<(), AppError> = with_retry_config(3, Duration::from_millis(1), || {
+            attempts.set(attempts.get() + 1);
+            async { Err(busy()) }
+        })
+        .await;
+
+        assert!(result.unwrap_err().is_busy());
+        assert_eq!(attempts.get(), 3);
+    }
+}
@@ -1,4 +1,8 @@
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
+
+/// Number of versioned migrations in `apply_migration`; stored in `PRAGMA user_version`
+/// once they have all been applied.
+const SCHEMA_VERSION: i64 = 14;
 
 pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     // Create categories table
@@ -41,6 +45,174 @@ pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     .execute(pool)
     .await?;
 
+    // Names of the default categories seeded so far
+    sqlx::query("CREATE TABLE IF NOT EXISTS seeded_categories (name TEXT PRIMARY KEY)")
+        .execute(pool)
+        .await?;
+
+    // Free-form app preferences, e.g. the default category for new tasks
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Recurrence rule shared by the generated instances of a series
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_series (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rule TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Audit trail of task changes; no FK so history survives deletion
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_activity (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            detail TEXT,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Reminders already shown, keyed by the due date they were for; moving the due
+    // date makes the task eligible for a new reminder
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS notified_reminders (
+            task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            due_date INTEGER NOT NULL,
+            notified_at INTEGER NOT NULL,
+            PRIMARY KEY (task_id, due_date)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Reusable task structures, stored as a JSON `TemplateNode` tree
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            tree TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Deleted tasks with their descendants, as a JSON array of `Task` rows (parents
+    // first), so they can be restored with their original ids
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS trash (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            task_count INTEGER NOT NULL,
+            tasks TEXT NOT NULL,
+            trashed_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Deletions leave a tombstone so sync clients can tell a row is gone
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tombstones (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL CHECK(entity IN ('task', 'category')),
+            entity_id INTEGER NOT NULL,
+            deleted_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Triggers also catch rows removed by ON DELETE CASCADE
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_tombstone
+        AFTER DELETE ON tasks
+        BEGIN
+            INSERT INTO tombstones (entity, entity_id, deleted_at)
+            VALUES ('task', OLD.id, CAST(strftime('%s', 'now') AS INTEGER));
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS trg_categories_tombstone
+        AFTER DELETE ON categories
+        BEGIN
+            INSERT INTO tombstones (entity, entity_id, deleted_at)
+            VALUES ('category', OLD.id, CAST(strftime('%s', 'now') AS INTEGER));
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Single-row counter bumped by every task write; lets readers cheaply tell whether
+    // anything changed since they last looked
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS data_version (
+            id INTEGER PRIMARY KEY CHECK(id = 1),
+            version INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("INSERT OR IGNORE INTO data_version (id, version) VALUES (1, 0)")
+        .execute(pool)
+        .await?;
+
+    for event in ["INSERT", "UPDATE", "DELETE"] {
+        sqlx::query(&format!(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS trg_tasks_data_version_{}
+            AFTER {} ON tasks
+            BEGIN
+                UPDATE data_version SET version = version + 1 WHERE id = 1;
+            END
+            "#,
+            event.to_lowercase(),
+            event
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    // Bring older databases up to date before creating indexes on new columns
+    run_versioned_migrations(pool).await?;
+
     // Create indexes for performance
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_category ON tasks(category_id)")
         .execute(pool)
@@ -62,34 +234,663 @@ pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         .execute(pool)
         .await?;
 
-    // Insert default categories if they don't exist
-    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM categories")
-        .fetch_one(pool)
+    // Partial index so listing roots in order never scans subtasks. Leading with
+    // parent_id lets the planner prefer it over idx_tasks_parent plus a sort.
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_tasks_roots ON tasks(parent_id, position) WHERE parent_id IS NULL",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_updated ON tasks(updated_at)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_trash_trashed ON trash(trashed_at)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tombstones_deleted ON tombstones(deleted_at)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_task_activity_task ON task_activity(task_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_tasks_group_position ON tasks(parent_id, category_id, position)",
+    )
+    .execute(pool)
+    .await?;
+
+    // Positions must be distinct within a (parent_id, category_id) group. A unique index
+    // can't express this: NULLs never collide in one, and reorders shift positions row
+    // by row, briefly duplicating them mid-statement. New rows are checked instead.
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_unique_position
+        BEFORE INSERT ON tasks
+        WHEN EXISTS (
+            SELECT 1 FROM tasks
+            WHERE parent_id IS NEW.parent_id
+            AND category_id IS NEW.category_id
+            AND position = NEW.position
+        )
+        BEGIN
+            SELECT RAISE(ABORT, 'duplicate position within task group');
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // A task's path is its parent's path plus its own id. Triggers keep it current for
+    // every writer; note that RETURNING clauses don't see what they write.
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_path_insert
+        AFTER INSERT ON tasks
+        BEGIN
+            UPDATE tasks
+            SET path = COALESCE((SELECT path FROM tasks WHERE id = NEW.parent_id), '/') || NEW.id || '/'
+            WHERE id = NEW.id;
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Reparenting rewrites the path prefix of the task and all of its descendants
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS trg_tasks_path_reparent
+        AFTER UPDATE OF parent_id ON tasks
+        WHEN NEW.parent_id IS NOT OLD.parent_id
+        BEGIN
+            UPDATE tasks
+            SET path = COALESCE((SELECT path FROM tasks WHERE id = NEW.parent_id), '/')
+                || NEW.id || '/' || substr(path, length(OLD.path) + 1)
+            WHERE substr(path, 1, length(OLD.path)) = OLD.path;
+        END
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_path ON tasks(path)")
+        .execute(pool)
         .await?;
 
-    if count.0 == 0 {
-        let now = chrono::Utc::now().timestamp();
+    // External ids are optional, but unique when present
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_tasks_external_id ON tasks(external_id) WHERE external_id IS NOT NULL",
+    )
+    .execute(pool)
+    .await?;
+
+    seed_default_categories(pool).await?;
 
+    Ok(())
+}
+
+const DEFAULT_CATEGORIES: [(&str, &str); 4] = [
+    ("Personal", "#9ece6a"),
+    ("Tech Guild", "#7aa2f7"),
+    ("Work", "#e0af68"),
+    ("Other", "#414868"),
+];
+
+// Defaults are seeded by name on every start, so ones added in later versions reach
+// existing databases too. Each is recorded once seeded, so a default the user has
+// since renamed or deleted doesn't come back.
+async fn seed_default_categories(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().timestamp();
+
+    for (name, color) in DEFAULT_CATEGORIES {
         sqlx::query(
             r#"
-            INSERT INTO categories (name, color, created_at, updated_at) VALUES
-                ('Personal', '#9ece6a', ?, ?),
-                ('Tech Guild', '#7aa2f7', ?, ?),
-                ('Work', '#e0af68', ?, ?),
-                ('Other', '#414868', ?, ?)
+            INSERT INTO categories (name, color, created_at, updated_at)
+            SELECT ?1, ?2, ?3, ?3
+            WHERE NOT EXISTS (SELECT 1 FROM seeded_categories WHERE name = ?1)
+            ON CONFLICT(name) DO NOTHING
             "#,
         )
-        .bind(now)
-        .bind(now)
-        .bind(now)
-        .bind(now)
-        .bind(now)
-        .bind(now)
-        .bind(now)
+        .bind(name)
+        .bind(color)
         .bind(now)
         .execute(pool)
         .await?;
+
+        sqlx::query("INSERT OR IGNORE INTO seeded_categories (name) VALUES (?)")
+            .bind(name)
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+// Apply each migration newer than the database's `user_version`, one transaction per step
+async fn run_versioned_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let (mut version,): (i64,) = sqlx::query_as("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+
+    while version < SCHEMA_VERSION {
+        let next = version + 1;
+        let mut tx = pool.begin().await?;
+
+        apply_migration(&mut tx, next).await?;
+
+        // PRAGMA statements can't take bound parameters
+        sqlx::query(&format!("PRAGMA user_version = {}", next))
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        version = next;
     }
 
     Ok(())
 }
+
+async fn apply_migration(conn: &mut SqliteConnection, version: i64) -> Result<(), sqlx::Error> {
+    match version {
+        // 1: client-supplied ids for idempotent imports
+        1 => {
+            sqlx::query("ALTER TABLE tasks ADD COLUMN external_id TEXT")
+                .execute(&mut *conn)
+                .await?;
+        }
+        // 2: rows marked done outside the app may be missing their completion time
+        2 => {
+            sqlx::query(
+                "UPDATE tasks SET completed_at = updated_at WHERE is_done = 1 AND completed_at IS NULL",
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // 3: story-point effort estimates
+        3 => {
+            sqlx::query(
+                "ALTER TABLE tasks ADD COLUMN effort INTEGER CHECK(effort IS NULL OR effort >= 0)",
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // 4: link recurring instances to their series
+        4 => {
+            sqlx::query(
+                "ALTER TABLE tasks ADD COLUMN series_id INTEGER REFERENCES task_series(id) ON DELETE SET NULL",
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // 5: read-only flag for archived reference tasks
+        5 => {
+            sqlx::query("ALTER TABLE tasks ADD COLUMN is_locked BOOLEAN NOT NULL DEFAULT 0")
+                .execute(&mut *conn)
+                .await?;
+        }
+        // 6: compact every sibling group so positions are distinct before the
+        // duplicate-position trigger is installed (order is kept, ties broken by id)
+        6 => {
+            sqlx::query(
+                r#"
+                UPDATE tasks
+                SET position = ranked.new_position
+                FROM (
+                    SELECT
+                        id,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY parent_id, category_id
+                            ORDER BY position ASC, id ASC
+                        ) - 1 AS new_position
+                    FROM tasks
+                ) AS ranked
+                WHERE tasks.id = ranked.id
+                AND tasks.position != ranked.new_position
+                "#,
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // 7: materialized tree path for ordering without recursive queries
+        7 => {
+            sqlx::query("ALTER TABLE tasks ADD COLUMN path TEXT NOT NULL DEFAULT ''")
+                .execute(&mut *conn)
+                .await?;
+        }
+        // 8: fill in paths for existing rows; tasks whose parent is missing count as roots
+        8 => {
+            sqlx::query(
+                r#"
+                WITH RECURSIVE tree(id, path) AS (
+                    SELECT id, '/' || id || '/'
+                    FROM tasks
+                    WHERE parent_id IS NULL OR parent_id NOT IN (SELECT id FROM tasks)
+                    UNION ALL
+                    SELECT t.id, tree.path || t.id || '/'
+                    FROM tasks t
+                    JOIN tree ON t.parent_id = tree.id
+                )
+                UPDATE tasks
+                SET path = tree.path
+                FROM tree
+                WHERE tasks.id = tree.id
+                "#,
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // 9: per-task reminder lead time
+        9 => {
+            sqlx::query(
+                "ALTER TABLE tasks ADD COLUMN reminder_offset_minutes INTEGER CHECK(reminder_offset_minutes IS NULL OR reminder_offset_minutes >= 0)",
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // 10: archiving hides a category without deleting it
+        10 => {
+            sqlx::query("ALTER TABLE categories ADD COLUMN archived_at INTEGER")
+                .execute(&mut *conn)
+                .await?;
+        }
+        // 11: store priority as its rank (0 = Urgent .. 3 = Low) so it sorts natively.
+        // SQLite can't change a column's type, so build the new column alongside, drop
+        // the text one (and its index) and take over its name.
+        11 => {
+            sqlx::query("DROP INDEX IF EXISTS idx_tasks_priority")
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query(
+                "ALTER TABLE tasks ADD COLUMN priority_rank INTEGER NOT NULL DEFAULT 2 CHECK(priority_rank BETWEEN 0 AND 3)",
+            )
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query(
+                r#"
+                UPDATE tasks
+                SET priority_rank = CASE priority
+                    WHEN 'Urgent' THEN 0
+                    WHEN 'High' THEN 1
+                    WHEN 'Medium' THEN 2
+                    ELSE 3
+                END
+                "#,
+            )
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query("ALTER TABLE tasks DROP COLUMN priority")
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query("ALTER TABLE tasks RENAME COLUMN priority_rank TO priority")
+                .execute(&mut *conn)
+                .await?;
+        }
+        // 12: fractional positions, so a task can be placed between two siblings without
+        // shifting the rest. The trigger and indexes on the old column are recreated
+        // after the migrations run.
+        12 => {
+            sqlx::query("DROP TRIGGER IF EXISTS trg_tasks_unique_position")
+                .execute(&mut *conn)
+                .await?;
+            for index in [
+                "idx_tasks_position",
+                "idx_tasks_roots",
+                "idx_tasks_group_position",
+            ] {
+                sqlx::query(&format!("DROP INDEX IF EXISTS {}", index))
+                    .execute(&mut *conn)
+                    .await?;
+            }
+            sqlx::query("ALTER TABLE tasks ADD COLUMN position_real REAL NOT NULL DEFAULT 0")
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query("UPDATE tasks SET position_real = position")
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query("ALTER TABLE tasks DROP COLUMN position")
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query("ALTER TABLE tasks RENAME COLUMN position_real TO position")
+                .execute(&mut *conn)
+                .await?;
+        }
+        // 13: the roots index gains a leading parent_id column; recreated after the
+        // migrations run
+        13 => {
+            sqlx::query("DROP INDEX IF EXISTS idx_tasks_roots")
+                .execute(&mut *conn)
+                .await?;
+        }
+        // 14: per-task color overriding the category's
+        14 => {
+            sqlx::query("ALTER TABLE tasks ADD COLUMN color TEXT")
+                .execute(&mut *conn)
+                .await?;
+        }
+        _ => unreachable!("no migration defined for schema version {}", version),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn user_version(pool: &SqlitePool) -> i64 {
+        let (version,): (i64,) = sqlx::query_as("PRAGMA user_version")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        version
+    }
+
+    #[tokio::test]
+    async fn test_run_migrations_is_idempotent() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+
+        run_migrations(&pool).await.unwrap();
+        assert_eq!(user_version(&pool).await, SCHEMA_VERSION);
+
+        run_migrations(&pool).await.unwrap();
+        assert_eq!(user_version(&pool).await, SCHEMA_VERSION);
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM categories")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 4, "Default categories are only seeded once");
+    }
+
+    async fn category_colors(pool: &SqlitePool) -> Vec<(String, String)> {
+        sqlx::query_as("SELECT name, color FROM categories ORDER BY name")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_missing_default_categories_are_added() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        // A database from before seeding by name, holding only one of the defaults
+        sqlx::query("DELETE FROM categories")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM seeded_categories")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO categories (name, color, created_at, updated_at) VALUES ('Work', '#000000', 0, 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        run_migrations(&pool).await.unwrap();
+        let expected = [
+            ("Other", "#414868"),
+            ("Personal", "#9ece6a"),
+            ("Tech Guild", "#7aa2f7"),
+            ("Work", "#000000"),
+        ];
+        assert_eq!(
+            category_colors(&pool).await,
+            expected
+                .iter()
+                .map(|(n, c)| (n.to_string(), c.to_string()))
+                .collect::<Vec<_>>(),
+            "The existing default is kept as is"
+        );
+
+        // Once seeded, a deleted default stays deleted
+        sqlx::query("DELETE FROM categories WHERE name = 'Other'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        assert_eq!(category_colors(&pool).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_root_query_uses_partial_index() {
+        // The same query get_root_tasks runs, without any index hint
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let plan: Vec<(i64, i64, i64, String)> = sqlx::query_as(
+            "EXPLAIN QUERY PLAN SELECT * FROM tasks WHERE parent_id IS NULL ORDER BY position ASC",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let details: Vec<&str> = plan
+            .iter()
+            .map(|(_, _, _, detail)| detail.as_str())
+            .collect();
+        assert!(
+            details.iter().any(|d| d.contains("idx_tasks_roots")),
+            "expected the roots index in {:?}",
+            details
+        );
+        assert!(
+            !details.iter().any(|d| d.contains("TEMP B-TREE")),
+            "index already yields position order: {:?}",
+            details
+        );
+    }
+
+    #[tokio::test]
+    async fn test_completed_at_backfill_migration() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        // Simulate a row marked done directly in SQL by an older version
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (title, priority, is_done, position, created_at, updated_at)
+            VALUES ('Done elsewhere', 2, 1, 0, 100, 200)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Later migrations add columns, so replay only the backfill step
+        let mut conn = pool.acquire().await.unwrap();
+        apply_migration(&mut conn, 2).await.unwrap();
+        drop(conn);
+
+        let (completed_at,): (Option<i64>,) =
+            sqlx::query_as("SELECT completed_at FROM tasks WHERE title = 'Done elsewhere'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(completed_at, Some(200));
+        assert_eq!(user_version(&pool).await, SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_path_backfill_migration() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        for (title, parent, position) in [
+            ("Root", None, 0),
+            ("Child", Some(1), 0),
+            ("Grandchild", Some(2), 0),
+            ("Other root", None, 1),
+        ] {
+            sqlx::query(
+                "INSERT INTO tasks (title, priority, parent_id, position, created_at, updated_at) VALUES (?, 2, ?, ?, 0, 0)",
+            )
+            .bind(title)
+            .bind(parent)
+            .bind(position)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // Rows written before the column existed have no path yet
+        sqlx::query("UPDATE tasks SET path = ''")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        apply_migration(&mut conn, 8).await.unwrap();
+        drop(conn);
+
+        let paths: Vec<(String, String)> =
+            sqlx::query_as("SELECT title, path FROM tasks ORDER BY path")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let expected = [
+            ("Root", "/1/"),
+            ("Child", "/1/2/"),
+            ("Grandchild", "/1/2/3/"),
+            ("Other root", "/4/"),
+        ];
+        assert_eq!(
+            paths,
+            expected
+                .iter()
+                .map(|(t, p)| (t.to_string(), p.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_priority_rank_migration() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+
+        // Just enough of the pre-migration table: priority stored by name, and indexed
+        sqlx::query(
+            r#"
+            CREATE TABLE tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                priority TEXT NOT NULL CHECK(priority IN ('Urgent', 'High', 'Medium', 'Low'))
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("CREATE INDEX idx_tasks_priority ON tasks(priority)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (title, priority) in [
+            ("a", "Low"),
+            ("b", "Urgent"),
+            ("c", "Medium"),
+            ("d", "High"),
+        ] {
+            sqlx::query("INSERT INTO tasks (title, priority) VALUES (?, ?)")
+                .bind(title)
+                .bind(priority)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let mut conn = pool.acquire().await.unwrap();
+        apply_migration(&mut conn, 11).await.unwrap();
+        drop(conn);
+
+        let rows: Vec<(String, i64, String)> =
+            sqlx::query_as("SELECT title, priority, typeof(priority) FROM tasks ORDER BY priority")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let expected = [("b", 0), ("d", 1), ("c", 2), ("a", 3)];
+        assert_eq!(
+            rows,
+            expected
+                .iter()
+                .map(|(t, p)| (t.to_string(), *p, "integer".to_string()))
+                .collect::<Vec<_>>()
+        );
+
+        let result = sqlx::query("UPDATE tasks SET priority = 4 WHERE title = 'a'")
+            .execute(&pool)
+            .await;
+        assert!(result.is_err(), "Ranks are still constrained");
+    }
+
+    #[tokio::test]
+    async fn test_real_position_migration() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+
+        // The pre-migration table: integer positions, indexed and guarded by a trigger
+        sqlx::query(
+            r#"
+            CREATE TABLE tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                parent_id INTEGER,
+                category_id INTEGER,
+                position INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "CREATE INDEX idx_tasks_group_position ON tasks(parent_id, category_id, position)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TRIGGER trg_tasks_unique_position
+            BEFORE INSERT ON tasks
+            WHEN EXISTS (SELECT 1 FROM tasks WHERE position = NEW.position)
+            BEGIN
+                SELECT RAISE(ABORT, 'duplicate position within task group');
+            END
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (title, position) in [("a", 2), ("b", 0), ("c", 1)] {
+            sqlx::query("INSERT INTO tasks (title, position) VALUES (?, ?)")
+                .bind(title)
+                .bind(position)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let mut conn = pool.acquire().await.unwrap();
+        apply_migration(&mut conn, 12).await.unwrap();
+        drop(conn);
+
+        let rows: Vec<(String, f64, String)> =
+            sqlx::query_as("SELECT title, position, typeof(position) FROM tasks ORDER BY position")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let expected = [("b", 0.0), ("c", 1.0), ("a", 2.0)];
+        assert_eq!(
+            rows,
+            expected
+                .iter()
+                .map(|(t, p)| (t.to_string(), *p, "real".to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Environment variable holding the slow-query threshold in milliseconds; `0` disables logging.
+pub const SLOW_QUERY_ENV: &str = "EVENTUALLY_SLOW_QUERY_MS";
+
+// Debug builds log by default; release builds only when the env var is set
+const DEFAULT_DEBUG_THRESHOLD_MS: u64 = 100;
+
+fn parse_threshold(value: Option<&str>, debug_build: bool) -> Option<Duration> {
+    let millis = match value {
+        Some(raw) => raw.trim().parse::<u64>().ok()?,
+        None if debug_build => DEFAULT_DEBUG_THRESHOLD_MS,
+        None => return None,
+    };
+    (millis > 0).then(|| Duration::from_millis(millis))
+}
+
+fn slow_query_threshold() -> Option<Duration> {
+    static THRESHOLD: OnceLock<Option<Duration>> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| {
+        let value = std::env::var(SLOW_QUERY_ENV).ok();
+        parse_threshold(value.as_deref(), cfg!(debug_assertions))
+    })
+}
+
+/// Awaits `query`, logging a warning if it takes longer than the configured threshold.
+pub(crate) async fn timed<F: Future>(label: &str, query: F) -> F::Output {
+    timed_with(slow_query_threshold(), label, query).await.0
+}
+
+// Returns the elapsed time alongside the output when it was reported as slow
+async fn timed_with<F: Future>(
+    threshold: Option<Duration>,
+    label: &str,
+    query: F,
+) -> (F::Output, Option<Duration>) {
+    let Some(threshold) = threshold else {
+        return (query.await, None);
+    };
+
+    let start = Instant::now();
+    let output = query.await;
+    let elapsed = start.elapsed();
+
+    if elapsed > threshold {
+        log::warn!(
+            "slow query '{}' took {}ms (threshold {}ms)",
+            label,
+            elapsed.as_millis(),
+            threshold.as_millis()
+        );
+        return (output, Some(elapsed));
+    }
+
+    (output, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_threshold() {
+        assert_eq!(
+            parse_threshold(Some("250"), false),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(parse_threshold(Some("0"), true), None, "0 disables logging");
+        assert_eq!(parse_threshold(Some("abc"), true), None);
+        assert_eq!(
+            parse_threshold(None, true),
+            Some(Duration::from_millis(DEFAULT_DEBUG_THRESHOLD_MS))
+        );
+        assert_eq!(
+            parse_threshold(None, false),
+            None,
+            "Quiet in release by default"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timed_reports_slow_query() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+
+        // Counting to a few million in a recursive CTE reliably takes longer than 1ms
+        let slow = sqlx::query_as::<_, (i64,)>(
+            r#"
+            WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 3000000)
+            SELECT COUNT(*) FROM n
+            "#,
+        )
+        .fetch_one(&pool);
+        let (result, reported) = timed_with(Some(Duration::from_millis(1)), "count", slow).await;
+        assert_eq!(result.unwrap().0, 3_000_000);
+        assert!(reported.is_some());
+
+        let fast = sqlx::query_as::<_, (i64,)>("SELECT 1").fetch_one(&pool);
+        let (_, reported) = timed_with(Some(Duration::from_secs(60)), "select", fast).await;
+        assert!(reported.is_none());
+
+        let fast = sqlx::query_as::<_, (i64,)>("SELECT 1").fetch_one(&pool);
+        let (_, reported) = timed_with(None, "select", fast).await;
+        assert!(reported.is_none(), "Disabled threshold never reports");
+    }
+}
@@ -0,0 +1,66 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use tokio::sync::Mutex;
+
+/// A connection set aside for reading `PRAGMA data_version`.
+///
+/// The pragma changes whenever another connection (in this app or another process)
+/// commits, but its value is only comparable between reads on the same connection,
+/// so polling through the pool could report changes that never happened.
+#[derive(Debug)]
+pub struct DataVersionWatcher {
+    conn: Mutex<SqliteConnection>,
+}
+
+impl DataVersionWatcher {
+    /// Takes a connection out of `pool` for good; the pool opens another in its place.
+    pub async fn new(pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let conn = pool.acquire().await?.detach();
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub async fn data_version(&self) -> Result<i64, sqlx::Error> {
+        let mut conn = self.conn.lock().await;
+        let (version,): (i64,) = sqlx::query_as("PRAGMA data_version")
+            .fetch_one(&mut *conn)
+            .await?;
+        Ok(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{create_pool, run_migrations};
+
+    #[tokio::test]
+    async fn test_data_version_changes_on_other_connection_commit() {
+        let db_path =
+            std::env::temp_dir().join(format!("eventually-watch-{}.db", std::process::id()));
+        let pool = create_pool(db_path.clone()).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        let watcher = DataVersionWatcher::new(&pool).await.unwrap();
+
+        let before = watcher.data_version().await.unwrap();
+        assert_eq!(
+            watcher.data_version().await.unwrap(),
+            before,
+            "Unchanged without writes"
+        );
+
+        sqlx::query(
+            "INSERT INTO categories (name, color, created_at, updated_at) VALUES ('Synced', '#000000', 0, 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_ne!(watcher.data_version().await.unwrap(), before);
+
+        pool.close().await;
+        drop(watcher);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", db_path.display(), suffix));
+        }
+    }
+}
@@ -9,10 +9,35 @@ pub enum AppError {
     InvalidInput(String),
 }
 
+/// Message carried by [`AppError::DatabaseError`] when the database was busy.
+pub(crate) const BUSY_MESSAGE: &str = "database is busy, try again";
+
+/// Whether an error means the database was locked by another writer (SQLITE_BUSY/SQLITE_LOCKED)
+/// or no connection freed up in time, i.e. the operation may succeed if retried.
+pub fn is_busy_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_err) => db_err
+            .code()
+            .and_then(|code| code.parse::<i32>().ok())
+            // Extended result codes keep the primary code in the low byte
+            .is_some_and(|code| matches!(code & 0xff, 5 | 6)),
+        _ => false,
+    }
+}
+
+impl AppError {
+    /// Whether this error came from a busy or locked database, so a retry may succeed.
+    pub fn is_busy(&self) -> bool {
+        matches!(self, AppError::DatabaseError(message) if message == BUSY_MESSAGE)
+    }
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
         match err {
             sqlx::Error::RowNotFound => AppError::NotFound("Record not found".to_string()),
+            _ if is_busy_error(&err) => AppError::DatabaseError(BUSY_MESSAGE.to_string()),
             _ => AppError::DatabaseError(err.to_string()),
         }
     }
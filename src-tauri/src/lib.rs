@@ -1,3 +1,4 @@
+mod cache;
 mod commands;
 mod db;
 mod error;
@@ -10,6 +11,12 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        // Sends `log` records, such as slow-query warnings, to stdout and the app log dir
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .level(log::LevelFilter::Info)
+                .build(),
+        )
         .setup(|app| {
             // Get app data directory
             let app_dir = app
@@ -37,22 +44,123 @@ pub fn run() {
                     .map_err(|e| format!("Failed to run migrations: {:?}", e))
             })?;
 
+            // Trash past its retention window is cleared on every start; a failure here
+            // only delays that, so it doesn't stop the app
+            if let Err(e) =
+                tauri::async_runtime::block_on(commands::trash::empty_expired_trash(&pool))
+            {
+                log::warn!("Failed to empty expired trash: {:?}", e);
+            }
+
+            let watcher = tauri::async_runtime::block_on(async {
+                db::DataVersionWatcher::new(&pool)
+                    .await
+                    .map_err(|e| format!("Failed to open data version watcher: {:?}", e))
+            })?;
+
             // Manage state
             app.manage(pool);
+            app.manage(watcher);
+            app.manage(cache::TreeCache::default());
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
+            commands::tasks::validate_task_input,
             commands::tasks::create_task,
+            commands::tasks::create_tasks,
+            commands::tasks::quick_add,
+            commands::tasks::import_markdown,
+            commands::tasks::upsert_task,
             commands::tasks::get_all_tasks,
+            commands::tasks::get_all_tasks_iso,
+            commands::tasks::count_tasks,
+            commands::tasks::get_inbox_count,
+            commands::tasks::search_tasks,
+            commands::tasks::get_tasks_by_ids,
+            commands::tasks::get_tasks_with_category,
+            commands::tasks::get_tasks_after,
+            commands::tasks::stream_all_tasks,
+            commands::tasks::get_root_tasks,
+            commands::tasks::get_root_tasks_with_markers,
+            commands::tasks::get_child_tasks,
+            commands::tasks::get_tasks_in_tree_order,
             commands::tasks::get_task_tree,
+            commands::tasks::get_task_tree_timed,
+            commands::tasks::get_task_tree_sorted,
+            commands::tasks::get_tasks_tree_json_string,
+            commands::tasks::get_subtree,
+            commands::tasks::get_task_tree_filtered,
             commands::tasks::update_task,
+            commands::tasks::bulk_update_where,
+            commands::tasks::complete_and_add_followup,
+            commands::tasks::toggle_task_done,
             commands::tasks::delete_task,
             commands::tasks::reorder_task,
+            commands::tasks::move_task_to_top,
+            commands::tasks::move_task_to_bottom,
+            commands::tasks::move_between,
+            commands::tasks::set_parent,
+            commands::tasks::duplicate_task_shallow,
+            commands::tasks::set_locked,
+            commands::tasks::defer_overdue_to,
+            commands::tasks::set_tasks_priority,
+            commands::tasks::remap_priority,
+            commands::tasks::merge_tasks,
+            commands::tasks::get_next_due_task,
+            commands::tasks::get_recently_completed,
+            commands::tasks::get_upcoming_tasks,
+            commands::tasks::get_tasks_for_day,
+            commands::tasks::get_tasks_created_between,
+            commands::tasks::get_due_reminders,
+            commands::tasks::mark_reminder_sent,
+            commands::tasks::get_tasks_modified_since,
+            commands::activity::get_task_activity,
+            commands::categories::validate_category_input,
+            commands::categories::suggested_text_color,
             commands::categories::create_category,
             commands::categories::get_all_categories,
+            commands::categories::get_archived_categories,
+            commands::categories::get_category_colors,
+            commands::categories::set_category_archived,
+            commands::categories::get_categories_paged,
+            commands::categories::get_categories_with_counts,
+            commands::categories::get_category_usage,
             commands::categories::update_category,
+            commands::categories::rename_category,
             commands::categories::delete_category,
+            commands::categories::prune_empty_categories,
+            commands::export::export_category,
+            commands::export::import_data,
+            commands::maintenance::normalize_positions,
+            commands::maintenance::normalize_all_positions,
+            commands::maintenance::find_orphaned_tasks,
+            commands::maintenance::reattach_orphans,
+            commands::maintenance::check_foreign_keys,
+            commands::maintenance::repair_foreign_keys,
+            commands::maintenance::get_storage_info,
+            commands::recurrence::create_task_series,
+            commands::recurrence::reschedule_series,
+            commands::recurrence::validate_recurrence,
+            commands::settings::get_setting,
+            commands::settings::set_setting,
+            commands::stats::get_priority_distribution,
+            commands::stats::get_effort_summary,
+            commands::stats::get_task_stats,
+            commands::stats::get_tree_metrics,
+            commands::stats::get_completion_streak,
+            commands::stats::get_completions_by_day,
+            commands::stats::get_cycle_time_stats,
+            commands::stats::get_due_buckets,
+            commands::sync::get_change_summary_since,
+            commands::sync::get_state_fingerprint,
+            commands::sync::get_data_version,
+            commands::templates::save_template,
+            commands::templates::instantiate_template,
+            commands::trash::list_trash,
+            commands::trash::restore_from_trash,
+            commands::trash::empty_trash,
+            commands::trash::archive_completed_before,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+/// One entry in a task's audit trail.
+///
+/// Entries are keyed by `task_id` without a foreign key, so a task's history
+/// outlives the task itself and deletions stay auditable.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TaskActivity {
+    pub id: i64,
+    pub task_id: i64,
+    /// One of "created", "updated", "completed", "reopened", "moved", "deleted",
+    /// "restored"
+    pub action: String,
+    pub detail: Option<String>,
+    pub created_at: i64,
+}
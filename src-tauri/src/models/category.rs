@@ -7,6 +7,8 @@ pub struct Category {
     pub color: String,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Set while archived; archived categories are hidden and can't take new tasks
+    pub archived_at: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -15,8 +17,85 @@ pub struct CreateCategoryInput {
     pub color: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct UpdateCategoryInput {
     pub name: Option<String>,
     pub color: Option<String>,
 }
+
+/// A category with its task counts, for sidebars that show progress per category.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CategoryWithCounts {
+    #[sqlx(flatten)]
+    pub category: Category,
+    pub total: i64,
+    pub done: i64,
+}
+
+/// What still depends on a category, for warning before it is deleted.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CategoryUsage {
+    pub total_tasks: i64,
+    pub open_tasks: i64,
+    pub done_tasks: i64,
+    /// The category's tasks plus all their subtasks, including any filed elsewhere
+    pub subtree_tasks: i64,
+}
+
+/// One page of categories plus the total across all pages.
+#[derive(Debug, Clone, Serialize)]
+pub struct PagedCategories {
+    pub items: Vec<Category>,
+    pub total: i64,
+}
+
+/// Black or white, whichever is more readable on top of a `#RRGGBB` background.
+///
+/// Uses WCAG relative luminance; above ~0.179 black text has the higher contrast
+/// ratio. Returns None if `color` isn't a valid hex code.
+pub fn text_color_for(color: &str) -> Option<&'static str> {
+    let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| -> Option<f64> {
+        let value = u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()? as f64 / 255.0;
+        // sRGB gamma expansion
+        Some(if value <= 0.03928 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        })
+    };
+    let luminance = 0.2126 * channel(0)? + 0.7152 * channel(2)? + 0.0722 * channel(4)?;
+
+    Some(if luminance > 0.179 {
+        "#000000"
+    } else {
+        "#FFFFFF"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_color_for_dark_background() {
+        assert_eq!(text_color_for("#000000"), Some("#FFFFFF"));
+        assert_eq!(text_color_for("#414868"), Some("#FFFFFF"));
+        assert_eq!(text_color_for("#1a1b26"), Some("#FFFFFF"));
+    }
+
+    #[test]
+    fn test_text_color_for_light_background() {
+        assert_eq!(text_color_for("#FFFFFF"), Some("#000000"));
+        assert_eq!(text_color_for("#e0af68"), Some("#000000"));
+        assert_eq!(text_color_for("#9ece6a"), Some("#000000"));
+    }
+
+    #[test]
+    fn test_text_color_for_invalid_color() {
+        assert_eq!(text_color_for("white"), None);
+        assert_eq!(text_color_for("#FFF"), None);
+        assert_eq!(text_color_for("#GGGGGG"), None);
+        assert_eq!(text_color_for("#ééé"), None);
+    }
+}
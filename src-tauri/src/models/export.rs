@@ -0,0 +1,25 @@
+use crate::models::{Category, Task};
+use serde::{Deserialize, Serialize};
+
+/// Bump when the bundle layout changes in a way older importers can't read.
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Categories and tasks in a self-contained form that `import_data` can load into
+/// another database. Ids are only meaningful inside the bundle; importing assigns
+/// fresh ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportBundle {
+    pub version: u32,
+    pub exported_at: i64,
+    pub categories: Vec<Category>,
+    /// Parents come before their children
+    pub tasks: Vec<Task>,
+}
+
+/// What an import added.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    /// Categories created; ones matched by name to an existing category aren't counted
+    pub categories_created: u64,
+    pub tasks_created: u64,
+}
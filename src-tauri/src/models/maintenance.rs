@@ -0,0 +1,31 @@
+use serde::Serialize;
+
+/// One row of `PRAGMA foreign_key_check`: a reference to a parent row that doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct ForeignKeyViolation {
+    /// Table holding the dangling reference
+    pub table: String,
+    pub rowid: Option<i64>,
+    /// Table the reference should point into
+    pub parent: String,
+    /// Index of the violated constraint within `table`'s foreign keys
+    pub fkid: i64,
+}
+
+/// Number of rows in one table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableRowCount {
+    pub table: String,
+    pub rows: i64,
+}
+
+/// Disk usage of the database, for the settings screen.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageInfo {
+    /// Size of the main database file (page_count * page_size)
+    pub db_bytes: i64,
+    /// Size of the write-ahead log not yet checkpointed; 0 for in-memory databases
+    pub wal_bytes: i64,
+    /// Every schema table, ordered by name
+    pub tables: Vec<TableRowCount>,
+}
@@ -0,0 +1,177 @@
+/// One `- [ ]` / `- [x]` line of a markdown checklist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecklistItem {
+    pub title: String,
+    pub is_done: bool,
+    /// Nesting level, 0 for top-level items.
+    pub depth: usize,
+}
+
+/// Parses an indented markdown checklist into items in document order.
+///
+/// Blank lines are skipped. The first indented line sets the indentation unit; every
+/// other line must be indented by a multiple of it and at most one level deeper than
+/// the line above. Errors name the offending line, 1-based.
+pub fn parse_markdown_checklist(text: &str) -> Result<Vec<ChecklistItem>, String> {
+    let mut items: Vec<ChecklistItem> = Vec::new();
+    let mut indent_unit: Option<&str> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let content = line.trim_start();
+        let indent = &line[..line.len() - content.len()];
+
+        let depth = if indent.is_empty() {
+            0
+        } else {
+            let unit = *indent_unit.get_or_insert(indent);
+            if !is_repeat_of(indent, unit) {
+                return Err(format!("Line {}: inconsistent indentation", line_number));
+            }
+            indent.len() / unit.len()
+        };
+
+        let max_depth = items
+            .last()
+            .map_or(0, |item: &ChecklistItem| item.depth + 1);
+        if depth > max_depth {
+            return Err(format!("Line {}: indented too deeply", line_number));
+        }
+
+        let (is_done, title) = parse_checkbox(content.trim_end())
+            .ok_or_else(|| format!("Line {}: expected a `- [ ]` checklist item", line_number))?;
+        if title.is_empty() {
+            return Err(format!("Line {}: checklist item has no title", line_number));
+        }
+
+        items.push(ChecklistItem {
+            title: title.to_string(),
+            is_done,
+            depth,
+        });
+    }
+
+    Ok(items)
+}
+
+fn is_repeat_of(indent: &str, unit: &str) -> bool {
+    indent.len().is_multiple_of(unit.len())
+        && indent
+            .as_bytes()
+            .chunks(unit.len())
+            .all(|chunk| chunk == unit.as_bytes())
+}
+
+// "- [ ] title" or "* [x] title" into (is_done, title)
+fn parse_checkbox(content: &str) -> Option<(bool, &str)> {
+    let rest = content
+        .strip_prefix("- ")
+        .or_else(|| content.strip_prefix("* "))?;
+    let (is_done, title) = if let Some(title) = rest.strip_prefix("[ ]") {
+        (false, title)
+    } else if let Some(title) = rest
+        .strip_prefix("[x]")
+        .or_else(|| rest.strip_prefix("[X]"))
+    {
+        (true, title)
+    } else {
+        return None;
+    };
+
+    if !title.is_empty() && !title.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some((is_done, title.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, is_done: bool, depth: usize) -> ChecklistItem {
+        ChecklistItem {
+            title: title.to_string(),
+            is_done,
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_parse_flat_list() {
+        let items =
+            parse_markdown_checklist("- [ ] Buy milk\n- [x] Call mom\n* [X] Pay rent").unwrap();
+        assert_eq!(
+            items,
+            vec![
+                item("Buy milk", false, 0),
+                item("Call mom", true, 0),
+                item("Pay rent", true, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_list() {
+        let text = "- [ ] Trip\n  - [x] Book flights\n  - [ ] Pack\n    - [ ] Socks\n- [ ] Home\n";
+        let items = parse_markdown_checklist(text).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                item("Trip", false, 0),
+                item("Book flights", true, 1),
+                item("Pack", false, 1),
+                item("Socks", false, 2),
+                item("Home", false, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_blank_lines_and_accepts_tabs() {
+        let items = parse_markdown_checklist("\n- [ ] Parent\n\n\t- [ ] Child\n   \n").unwrap();
+        assert_eq!(
+            items,
+            vec![item("Parent", false, 0), item("Child", false, 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_text() {
+        assert_eq!(parse_markdown_checklist("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_parse_rejects_inconsistent_indentation() {
+        let err = parse_markdown_checklist("- [ ] A\n  - [ ] B\n   - [ ] C").unwrap_err();
+        assert!(err.starts_with("Line 3:"), "{}", err);
+
+        let err = parse_markdown_checklist("- [ ] A\n  - [ ] B\n\t\t- [ ] C").unwrap_err();
+        assert!(err.starts_with("Line 3:"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_rejects_skipped_levels() {
+        let err = parse_markdown_checklist("  - [ ] Indented first").unwrap_err();
+        assert!(err.starts_with("Line 1:"), "{}", err);
+
+        let err = parse_markdown_checklist("- [ ] A\n  - [ ] B\n      - [ ] C").unwrap_err();
+        assert!(err.starts_with("Line 3:"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_lines() {
+        for text in [
+            "Just text",
+            "- plain bullet",
+            "- [?] Odd box",
+            "- [x]Glued",
+            "- [ ]   ",
+        ] {
+            assert!(parse_markdown_checklist(text).is_err(), "{:?}", text);
+        }
+    }
+}
@@ -1,5 +1,37 @@
+pub mod activity;
 pub mod category;
+pub mod export;
+pub mod maintenance;
+pub mod markdown;
+pub mod quickadd;
+pub mod recurrence;
+pub mod stats;
+pub mod sync;
 pub mod task;
+pub mod template;
+pub mod timed;
+pub mod trash;
 
-pub use category::{Category, CreateCategoryInput, UpdateCategoryInput};
-pub use task::{build_task_tree, CreateTaskInput, Task, TaskTree, UpdateTaskInput};
+pub use activity::TaskActivity;
+pub use category::{
+    text_color_for, Category, CategoryUsage, CategoryWithCounts, CreateCategoryInput,
+    PagedCategories, UpdateCategoryInput,
+};
+pub use export::{ExportBundle, ImportSummary, EXPORT_FORMAT_VERSION};
+pub use maintenance::{ForeignKeyViolation, StorageInfo, TableRowCount};
+pub use markdown::parse_markdown_checklist;
+pub use quickadd::parse_quick_add;
+pub use recurrence::RecurrenceRule;
+pub use stats::{
+    CycleTimeStats, DueBuckets, EffortSummary, PriorityBucket, TaskStats, TreeMetrics,
+};
+pub use sync::{ChangeSummary, StateFingerprint};
+pub use task::{
+    build_task_tree, filter_task_tree, remove_done_tasks, sort_task_tree, CappedTasks,
+    CompletedWithFollowup, CreateTaskInput, DeleteStrategy, FilteredTaskTree, Priority, Task,
+    TaskDto, TaskFilter, TaskListing, TaskNode, TaskPage, TaskTree, TaskWithCategory, TreeSort,
+    UpdateTaskInput, DEFAULT_PRIORITY, PRIORITIES,
+};
+pub use template::{TaskTemplate, TemplateNode};
+pub use timed::Timed;
+pub use trash::TrashEntry;
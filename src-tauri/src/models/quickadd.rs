@@ -0,0 +1,222 @@
+use crate::models::{Category, CreateTaskInput, PRIORITIES};
+use chrono::{DateTime, Days, FixedOffset, NaiveTime};
+
+/// Parses quick-add text such as `Buy milk !high #Personal tomorrow` into a new task.
+///
+/// Recognized tokens, anywhere in the text:
+/// - `!priority`, matched case-insensitively against [`PRIORITIES`]
+/// - `#category`, matched to an existing category name ignoring case, spaces,
+///   dashes and underscores (so `#tech-guild` finds "Tech Guild")
+/// - `today`, `tomorrow` and `in N days`, due at the end of that local day
+///
+/// Everything else, including tokens that look special but don't match, stays in the
+/// title. When a kind of token repeats, the last one wins.
+pub fn parse_quick_add(
+    text: &str,
+    categories: &[Category],
+    now: DateTime<FixedOffset>,
+) -> CreateTaskInput {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut title = Vec::new();
+    let mut priority = None;
+    let mut category_id = None;
+    let mut due_in_days = None;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let lower = token.to_lowercase();
+
+        if let Some(name) = token.strip_prefix('!') {
+            if let Some(p) = PRIORITIES.iter().find(|p| p.eq_ignore_ascii_case(name)) {
+                priority = Some(p.to_string());
+                i += 1;
+                continue;
+            }
+        } else if let Some(name) = token.strip_prefix('#') {
+            let wanted = normalize_category_name(name);
+            if let Some(category) = categories
+                .iter()
+                .find(|c| !wanted.is_empty() && normalize_category_name(&c.name) == wanted)
+            {
+                category_id = Some(category.id);
+                i += 1;
+                continue;
+            }
+        } else if lower == "today" {
+            due_in_days = Some(0);
+            i += 1;
+            continue;
+        } else if lower == "tomorrow" {
+            due_in_days = Some(1);
+            i += 1;
+            continue;
+        } else if lower == "in" {
+            let count = tokens.get(i + 1).and_then(|n| n.parse::<u64>().ok());
+            let unit = tokens.get(i + 2).map(|u| u.to_lowercase());
+            if let (Some(count), Some("day" | "days")) = (count, unit.as_deref()) {
+                due_in_days = Some(count);
+                i += 3;
+                continue;
+            }
+        }
+
+        title.push(token);
+        i += 1;
+    }
+
+    CreateTaskInput {
+        title: title.join(" "),
+        description: None,
+        category_id,
+        priority,
+        parent_id: None,
+        due_date: due_in_days.and_then(|days| end_of_local_day(now, days)),
+        effort: None,
+        reminder_offset_minutes: None,
+        color: None,
+    }
+}
+
+fn normalize_category_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Last second of the local day `days` after `now`, as a UTC timestamp
+fn end_of_local_day(now: DateTime<FixedOffset>, days: u64) -> Option<i64> {
+    let day = now.date_naive().checked_add_days(Days::new(days))?;
+    let end = day.and_time(NaiveTime::from_hms_opt(23, 59, 59)?);
+    Some(end.and_local_timezone(*now.offset()).single()?.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn categories() -> Vec<Category> {
+        ["Personal", "Tech Guild", "Work"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Category {
+                id: i as i64 + 1,
+                name: name.to_string(),
+                color: "#000000".to_string(),
+                created_at: 0,
+                updated_at: 0,
+                archived_at: None,
+            })
+            .collect()
+    }
+
+    // 2024-01-10 15:30 UTC, a Wednesday
+    fn now_utc() -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, 10, 15, 30, 0)
+            .unwrap()
+    }
+
+    fn end_of_day_utc(day: u32) -> i64 {
+        chrono::Utc
+            .with_ymd_and_hms(2024, 1, day, 23, 59, 59)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn test_plain_title() {
+        let input = parse_quick_add("  Buy   milk ", &categories(), now_utc());
+        assert_eq!(input.title, "Buy milk");
+        assert_eq!(input.priority, None);
+        assert_eq!(input.category_id, None);
+        assert_eq!(input.due_date, None);
+    }
+
+    #[test]
+    fn test_priority_token() {
+        let input = parse_quick_add("Fix bug !URGENT", &categories(), now_utc());
+        assert_eq!(input.title, "Fix bug");
+        assert_eq!(input.priority.as_deref(), Some("Urgent"));
+
+        let input = parse_quick_add("Wow !important", &categories(), now_utc());
+        assert_eq!(
+            input.title, "Wow !important",
+            "Unknown priority stays in title"
+        );
+        assert_eq!(input.priority, None);
+    }
+
+    #[test]
+    fn test_category_token() {
+        let input = parse_quick_add("Call mom #personal", &categories(), now_utc());
+        assert_eq!(input.title, "Call mom");
+        assert_eq!(input.category_id, Some(1));
+
+        let input = parse_quick_add("Talk #tech-guild", &categories(), now_utc());
+        assert_eq!(input.category_id, Some(2));
+
+        let input = parse_quick_add("Issue #42", &categories(), now_utc());
+        assert_eq!(input.title, "Issue #42", "Unknown category stays in title");
+        assert_eq!(input.category_id, None);
+    }
+
+    #[test]
+    fn test_date_phrases() {
+        let input = parse_quick_add("Pay rent today", &categories(), now_utc());
+        assert_eq!(input.title, "Pay rent");
+        assert_eq!(input.due_date, Some(end_of_day_utc(10)));
+
+        let input = parse_quick_add("Pay rent Tomorrow", &categories(), now_utc());
+        assert_eq!(input.due_date, Some(end_of_day_utc(11)));
+
+        let input = parse_quick_add("Renew passport in 3 days", &categories(), now_utc());
+        assert_eq!(input.title, "Renew passport");
+        assert_eq!(input.due_date, Some(end_of_day_utc(13)));
+
+        let input = parse_quick_add("Meet in person", &categories(), now_utc());
+        assert_eq!(
+            input.title, "Meet in person",
+            "Incomplete phrase stays in title"
+        );
+        assert_eq!(input.due_date, None);
+    }
+
+    #[test]
+    fn test_date_uses_local_day() {
+        // 15:30 UTC is already the next day at UTC+10
+        let sydney = now_utc().with_timezone(&FixedOffset::east_opt(10 * 3600).unwrap());
+        let input = parse_quick_add("Call today", &categories(), sydney);
+        let expected = FixedOffset::east_opt(10 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 1, 11, 23, 59, 59)
+            .unwrap()
+            .timestamp();
+        assert_eq!(input.due_date, Some(expected));
+    }
+
+    #[test]
+    fn test_combined_tokens() {
+        let input = parse_quick_add(
+            "Buy milk !high #Personal tomorrow",
+            &categories(),
+            now_utc(),
+        );
+        assert_eq!(input.title, "Buy milk");
+        assert_eq!(input.priority.as_deref(), Some("High"));
+        assert_eq!(input.category_id, Some(1));
+        assert_eq!(input.due_date, Some(end_of_day_utc(11)));
+
+        let input = parse_quick_add("!low Review #work PR !high", &categories(), now_utc());
+        assert_eq!(input.title, "Review PR");
+        assert_eq!(
+            input.priority.as_deref(),
+            Some("High"),
+            "Last priority wins"
+        );
+        assert_eq!(input.category_id, Some(3));
+    }
+}
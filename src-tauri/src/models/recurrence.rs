@@ -0,0 +1,207 @@
+use chrono::{DateTime, Datelike, Months, TimeDelta, Utc, Weekday};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// How often a recurring task repeats.
+///
+/// Rules use a small subset of iCalendar RRULE syntax, e.g. `FREQ=WEEKLY;INTERVAL=2`.
+/// `INTERVAL` defaults to 1. The non-standard `SKIP=WEEKENDS` part rolls occurrences
+/// that land on a Saturday or Sunday forward to the following Monday.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub skip_weekends: bool,
+}
+
+impl RecurrenceRule {
+    /// Due date of the occurrence after `due_date`, or None if it would overflow.
+    pub fn next_due(&self, due_date: i64) -> Option<i64> {
+        let current = DateTime::<Utc>::from_timestamp(due_date, 0)?;
+        let next = match self.frequency {
+            Frequency::Daily => current.checked_add_signed(TimeDelta::days(self.interval as i64)),
+            Frequency::Weekly => current.checked_add_signed(TimeDelta::weeks(self.interval as i64)),
+            // Clamps to the end of shorter months (Jan 31 -> Feb 28)
+            Frequency::Monthly => current.checked_add_months(Months::new(self.interval)),
+        }?;
+
+        let next = match next.weekday() {
+            Weekday::Sat if self.skip_weekends => next.checked_add_signed(TimeDelta::days(2))?,
+            Weekday::Sun if self.skip_weekends => next.checked_add_signed(TimeDelta::days(1))?,
+            _ => next,
+        };
+        Some(next.timestamp())
+    }
+
+    /// The first `count` due dates of a series starting at `start`.
+    pub fn occurrences(&self, start: i64, count: usize) -> Vec<i64> {
+        let mut dates = Vec::with_capacity(count);
+        let mut next = Some(start);
+        while dates.len() < count {
+            match next {
+                Some(due_date) => {
+                    dates.push(due_date);
+                    next = self.next_due(due_date);
+                }
+                None => break,
+            }
+        }
+        dates
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut skip_weekends = false;
+
+        for part in rule.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Malformed rule part '{}'", part))?;
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.trim().to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(format!("Unknown frequency '{}'", other)),
+                    });
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .trim()
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("Invalid interval '{}'", value.trim()))?;
+                }
+                "SKIP" => match value.trim().to_ascii_uppercase().as_str() {
+                    "WEEKENDS" => skip_weekends = true,
+                    other => return Err(format!("Unknown skip option '{}'", other)),
+                },
+                other => return Err(format!("Unknown rule part '{}'", other)),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| "Rule is missing FREQ".to_string())?;
+        Ok(Self {
+            frequency,
+            interval,
+            skip_weekends,
+        })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={};INTERVAL={}", frequency, self.interval)?;
+        if self.skip_weekends {
+            write!(f, ";SKIP=WEEKENDS")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01T00:00:00Z, a Monday
+    const JAN_1_2024: i64 = 1_704_067_200;
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_parse_rule() {
+        let rule: RecurrenceRule = "FREQ=WEEKLY;INTERVAL=2".parse().unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+
+        let rule: RecurrenceRule = "freq=daily".parse().unwrap();
+        assert_eq!(rule.frequency, Frequency::Daily);
+        assert_eq!(rule.interval, 1, "INTERVAL defaults to 1");
+    }
+
+    #[test]
+    fn test_parse_rule_errors() {
+        assert!("FREQ=HOURLY".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;INTERVAL=0".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;INTERVAL=abc".parse::<RecurrenceRule>().is_err());
+        assert!("INTERVAL=2".parse::<RecurrenceRule>().is_err());
+        assert!("FREQ=DAILY;BYDAY=MO".parse::<RecurrenceRule>().is_err());
+    }
+
+    #[test]
+    fn test_rule_display_round_trips() {
+        let rule: RecurrenceRule = "freq=monthly;interval=3".parse().unwrap();
+        assert_eq!(rule.to_string(), "FREQ=MONTHLY;INTERVAL=3");
+        assert_eq!(rule.to_string().parse::<RecurrenceRule>().unwrap(), rule);
+    }
+
+    #[test]
+    fn test_next_due() {
+        let daily: RecurrenceRule = "FREQ=DAILY;INTERVAL=3".parse().unwrap();
+        assert_eq!(daily.next_due(JAN_1_2024), Some(JAN_1_2024 + 3 * DAY));
+
+        let weekly: RecurrenceRule = "FREQ=WEEKLY".parse().unwrap();
+        assert_eq!(weekly.next_due(JAN_1_2024), Some(JAN_1_2024 + 7 * DAY));
+
+        // Jan 31 + 1 month clamps to Feb 29 in a leap year
+        let monthly: RecurrenceRule = "FREQ=MONTHLY".parse().unwrap();
+        let jan_31 = JAN_1_2024 + 30 * DAY;
+        assert_eq!(monthly.next_due(jan_31), Some(JAN_1_2024 + 59 * DAY));
+    }
+
+    #[test]
+    fn test_skip_weekends_rolls_to_monday() {
+        let friday = JAN_1_2024 + 4 * DAY;
+        let monday = JAN_1_2024 + 7 * DAY;
+
+        let workdays: RecurrenceRule = "FREQ=DAILY;SKIP=WEEKENDS".parse().unwrap();
+        assert!(workdays.skip_weekends);
+        assert_eq!(workdays.next_due(friday), Some(monday));
+        assert_eq!(workdays.next_due(monday), Some(monday + DAY));
+
+        // Without the flag a daily task still lands on Saturday
+        let daily: RecurrenceRule = "FREQ=DAILY".parse().unwrap();
+        assert!(!daily.skip_weekends);
+        assert_eq!(daily.next_due(friday), Some(friday + DAY));
+    }
+
+    #[test]
+    fn test_skip_weekends_leaves_weekday_occurrences_alone() {
+        let weekly: RecurrenceRule = "FREQ=WEEKLY;SKIP=WEEKENDS".parse().unwrap();
+        assert_eq!(weekly.next_due(JAN_1_2024), Some(JAN_1_2024 + 7 * DAY));
+        assert_eq!(weekly.to_string(), "FREQ=WEEKLY;INTERVAL=1;SKIP=WEEKENDS");
+
+        assert!("FREQ=DAILY;SKIP=HOLIDAYS"
+            .parse::<RecurrenceRule>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let weekly: RecurrenceRule = "FREQ=WEEKLY".parse().unwrap();
+        assert_eq!(
+            weekly.occurrences(JAN_1_2024, 3),
+            vec![JAN_1_2024, JAN_1_2024 + 7 * DAY, JAN_1_2024 + 14 * DAY]
+        );
+        assert!(weekly.occurrences(JAN_1_2024, 0).is_empty());
+    }
+}
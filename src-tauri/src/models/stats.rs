@@ -0,0 +1,61 @@
+use crate::models::Priority;
+use serde::Serialize;
+
+/// Number of tasks with a given priority.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct PriorityBucket {
+    pub priority: Priority,
+    pub count: i64,
+}
+
+/// Effort points planned and completed, for showing progress as points done.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct EffortSummary {
+    pub total: i64,
+    pub completed: i64,
+}
+
+/// Overall task counts for the stats view.
+///
+/// Each done instance of a recurring series counts towards `total_completions`, but a
+/// series counts only once towards `unique_completions`, so repeats don't inflate it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct TaskStats {
+    pub total: i64,
+    pub total_completions: i64,
+    pub unique_completions: i64,
+}
+
+/// Shape of the task tree, so the UI can plan its layout before rendering.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct TreeMetrics {
+    /// Levels in the deepest branch; a lone root is depth 1
+    pub max_depth: i64,
+    /// Most tasks sharing one parent, with all roots counting as one group
+    pub max_siblings: i64,
+    pub root_count: i64,
+}
+
+/// Seconds from creation to completion for tasks completed in a window. The figures
+/// are None when nothing was completed in it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CycleTimeStats {
+    pub count: i64,
+    pub average: Option<f64>,
+    pub median: Option<f64>,
+    pub p90: Option<f64>,
+}
+
+/// Open tasks per due-date bucket, for visualizing workload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct DueBuckets {
+    /// Due before now
+    pub overdue: i64,
+    /// Due later today
+    pub today: i64,
+    /// Due in the six days after today
+    pub this_week: i64,
+    pub later: i64,
+    /// No due date
+    pub none: i64,
+}
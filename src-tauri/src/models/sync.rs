@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+/// Row counts touched after a given timestamp.
+///
+/// Sync clients poll this before pulling a full delta; if every count is zero
+/// there is nothing to fetch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct ChangeSummary {
+    pub tasks_changed: i64,
+    pub categories_changed: i64,
+    pub tombstones: i64,
+}
+
+/// Digest of every task and category row's logical content.
+///
+/// Two databases holding the same tasks and categories produce the same `hash`,
+/// regardless of when rows were written. `version` changes whenever the set of
+/// hashed fields does, so clients never compare digests built differently.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateFingerprint {
+    pub version: u32,
+    pub hash: String,
+}
@@ -1,42 +1,169 @@
-use serde::{Deserialize, Serialize};
+use crate::models::Category;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row};
 use std::collections::HashMap;
 
+/// Names accepted for `Task.priority`, most urgent first.
+pub const PRIORITIES: [&str; 4] = ["Urgent", "High", "Medium", "Low"];
+
+/// How urgent a task is.
+///
+/// Stored as its rank (0 = Urgent .. 3 = Low) so `ORDER BY priority` sorts most urgent
+/// first and the index stays small; serialized by name, so the JSON API is unchanged.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type,
+)]
+#[repr(i32)]
+pub enum Priority {
+    Urgent = 0,
+    High = 1,
+    Medium = 2,
+    Low = 3,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [
+        Priority::Urgent,
+        Priority::High,
+        Priority::Medium,
+        Priority::Low,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        PRIORITIES[self as usize]
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    /// Exact, case-sensitive match against [`PRIORITIES`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Priority::ALL
+            .into_iter()
+            .find(|priority| priority.as_str() == s)
+            .ok_or_else(|| format!("Unknown priority '{}'", s))
+    }
+}
+
+/// What deleting a task does to its subtasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DeleteStrategy {
+    /// Delete them along with the task
+    #[default]
+    Cascade,
+    /// Move them up to the task's parent, or to the root
+    Reparent,
+    /// Refuse to delete a task that has any
+    Block,
+}
+
+/// Order for the root tasks of a tree; subtasks always keep their position order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TreeSort {
+    /// By position, as arranged by the user
+    #[default]
+    Position,
+    /// Most recently updated first, counting updates anywhere in the subtree
+    RecentActivity,
+    /// Earliest due date among the subtree's open tasks first; undated roots last
+    DueSoonest,
+}
+
+/// Priority assigned when a task is created without one.
+pub const DEFAULT_PRIORITY: Priority = Priority::Medium;
+/// Color for tasks with neither their own color nor a category, matching the frontend's
+/// foreground color.
+pub const DEFAULT_TASK_COLOR: &str = "#c0caf5";
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Task {
     pub id: i64,
     pub title: String,
     pub description: Option<String>,
     pub category_id: Option<i64>,
-    pub priority: String,
+    pub priority: Priority,
     pub parent_id: Option<i64>,
     pub is_done: bool,
-    pub position: i32,
+    pub position: f64,
     pub due_date: Option<i64>,
     pub created_at: i64,
     pub updated_at: i64,
     pub completed_at: Option<i64>,
+    /// Client-supplied id used by importers to upsert idempotently
+    pub external_id: Option<String>,
+    /// Story-point style estimate, never negative
+    pub effort: Option<i32>,
+    /// Recurring series this task was generated from
+    pub series_id: Option<i64>,
+    /// Locked tasks refuse edits, moves and deletion unless forced
+    pub is_locked: bool,
+    /// Ids from the root down to this task, e.g. "/1/4/9/"; sorting by it gives a
+    /// depth-first walk. Kept up to date by triggers.
+    pub path: String,
+    /// Remind this many minutes before `due_date`; None reminds at the due time
+    pub reminder_offset_minutes: Option<i32>,
+    /// `#RRGGBB` override for the category color; None uses the category's
+    pub color: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct CreateTaskInput {
     pub title: String,
     pub description: Option<String>,
     pub category_id: Option<i64>,
-    pub priority: String,
+    /// Falls back to [`DEFAULT_PRIORITY`] when omitted.
+    pub priority: Option<String>,
     pub parent_id: Option<i64>,
     pub due_date: Option<i64>,
+    pub effort: Option<i32>,
+    /// Minutes before `due_date` to remind, never negative
+    pub reminder_offset_minutes: Option<i32>,
+    /// `#RRGGBB`, overriding the category color
+    pub color: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Partial update; omitted fields are left unchanged.
+///
+/// Nullable columns use a double `Option`: `None` leaves the field alone, while
+/// `Some(None)` (an explicit `null` in JSON) clears it.
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct UpdateTaskInput {
     pub title: Option<String>,
-    pub description: Option<String>,
-    pub category_id: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub category_id: Option<Option<i64>>,
     pub priority: Option<String>,
-    pub parent_id: Option<i64>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub parent_id: Option<Option<i64>>,
     pub is_done: Option<bool>,
-    pub position: Option<i32>,
-    pub due_date: Option<i64>,
+    pub position: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub due_date: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub effort: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub reminder_offset_minutes: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub color: Option<Option<String>>,
+}
+
+// A field that is present deserializes to `Some`, even when its value is null;
+// `#[serde(default)]` covers the absent case
+fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 /// Tree structure for frontend consumption with hierarchical subtasks.
@@ -50,6 +177,314 @@ pub struct TaskTree {
     pub subtasks: Vec<TaskTree>,
 }
 
+/// A task without its subtasks, but saying whether it has any, for trees that load
+/// children only when a node is expanded.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TaskNode {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub task: Task,
+    pub has_children: bool,
+    /// Number of direct subtasks
+    pub child_count: i64,
+}
+
+/// A task with its category embedded, so one fetch has everything needed to render it.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskWithCategory {
+    #[serde(flatten)]
+    pub task: Task,
+    pub category: Option<Category>,
+    /// Color to render the task with: its own color, else its category's color, else
+    /// [`DEFAULT_TASK_COLOR`].
+    pub resolved_color: String,
+}
+
+impl TaskWithCategory {
+    pub fn new(task: Task, category: Option<Category>) -> Self {
+        let resolved_color = task
+            .color
+            .as_deref()
+            .or(category.as_ref().map(|c| c.color.as_str()))
+            .unwrap_or(DEFAULT_TASK_COLOR)
+            .to_string();
+        Self {
+            task,
+            category,
+            resolved_color,
+        }
+    }
+}
+
+// Expects `tasks.*` plus the category's columns aliased with a `category_` prefix,
+// all NULL when the task has no category
+impl<'r> FromRow<'r, SqliteRow> for TaskWithCategory {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let task = Task::from_row(row)?;
+        let name: Option<String> = row.try_get("category_name")?;
+        let category = match (task.category_id, name) {
+            (Some(id), Some(name)) => Some(Category {
+                id,
+                name,
+                color: row.try_get("category_color")?,
+                created_at: row.try_get("category_created_at")?,
+                updated_at: row.try_get("category_updated_at")?,
+                archived_at: row.try_get("category_archived_at")?,
+            }),
+            _ => None,
+        };
+
+        Ok(Self::new(task, category))
+    }
+}
+
+/// One page of a keyset-paginated task listing.
+///
+/// Pass `next_cursor` back as `after_id` to fetch the following page; it is None once
+/// the last page has been returned.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskPage {
+    pub items: Vec<Task>,
+    pub next_cursor: Option<i64>,
+}
+
+/// The first tasks of a listing together with how many there are in total, e.g. for
+/// "showing 50 of 300".
+#[derive(Debug, Clone, Serialize)]
+pub struct CappedTasks {
+    pub items: Vec<Task>,
+    pub total: i64,
+}
+
+/// What `get_all_tasks` returns: the plain list, or a capped one when a limit is given.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum TaskListing {
+    All(Vec<Task>),
+    Capped(CappedTasks),
+}
+
+/// A task that was just completed together with the follow-up created after it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletedWithFollowup {
+    pub completed: Task,
+    pub followup: Task,
+}
+
+/// `Task` with its epoch-second timestamps rendered as RFC 3339 strings.
+///
+/// Opt-in alternative to the epoch-based API for frontends that would
+/// otherwise convert every timestamp themselves. Null timestamps stay null.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskDto {
+    pub id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub category_id: Option<i64>,
+    pub priority: Priority,
+    pub parent_id: Option<i64>,
+    pub is_done: bool,
+    pub position: f64,
+    #[serde(serialize_with = "serialize_optional_timestamp")]
+    pub due_date: Option<i64>,
+    #[serde(serialize_with = "serialize_timestamp")]
+    pub created_at: i64,
+    #[serde(serialize_with = "serialize_timestamp")]
+    pub updated_at: i64,
+    #[serde(serialize_with = "serialize_optional_timestamp")]
+    pub completed_at: Option<i64>,
+    pub external_id: Option<String>,
+    pub effort: Option<i32>,
+    pub series_id: Option<i64>,
+    pub is_locked: bool,
+    pub path: String,
+    pub reminder_offset_minutes: Option<i32>,
+    pub color: Option<String>,
+}
+
+impl From<Task> for TaskDto {
+    fn from(task: Task) -> Self {
+        Self {
+            id: task.id,
+            title: task.title,
+            description: task.description,
+            category_id: task.category_id,
+            priority: task.priority,
+            parent_id: task.parent_id,
+            is_done: task.is_done,
+            position: task.position,
+            due_date: task.due_date,
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+            completed_at: task.completed_at,
+            external_id: task.external_id,
+            effort: task.effort,
+            series_id: task.series_id,
+            is_locked: task.is_locked,
+            path: task.path,
+            reminder_offset_minutes: task.reminder_offset_minutes,
+            color: task.color,
+        }
+    }
+}
+
+fn serialize_timestamp<S: Serializer>(timestamp: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    let datetime = chrono::DateTime::from_timestamp(*timestamp, 0).ok_or_else(|| {
+        serde::ser::Error::custom(format!("timestamp {} is out of range", timestamp))
+    })?;
+    serializer.serialize_str(&datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+fn serialize_optional_timestamp<S: Serializer>(
+    timestamp: &Option<i64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match timestamp {
+        Some(timestamp) => serialize_timestamp(timestamp, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Criteria for narrowing down tasks. Unset fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TaskFilter {
+    /// Case-insensitive substring matched against title and description
+    pub search: Option<String>,
+    pub priority: Option<String>,
+    pub is_done: Option<bool>,
+    /// Only tasks with a due date earlier than this, e.g. now for overdue tasks
+    pub due_before: Option<i64>,
+}
+
+impl TaskFilter {
+    /// True when no field would narrow anything down.
+    pub fn is_empty(&self) -> bool {
+        let has_search = self.search.as_deref().is_some_and(|s| !s.trim().is_empty());
+        !has_search
+            && self.priority.is_none()
+            && self.is_done.is_none()
+            && self.due_before.is_none()
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        if let Some(ref priority) = self.priority {
+            if task.priority.as_str() != priority {
+                return false;
+            }
+        }
+        if let Some(is_done) = self.is_done {
+            if task.is_done != is_done {
+                return false;
+            }
+        }
+        if let Some(due_before) = self.due_before {
+            if task.due_date.is_none_or(|due| due >= due_before) {
+                return false;
+            }
+        }
+        if let Some(ref search) = self.search {
+            let needle = search.trim().to_lowercase();
+            if !needle.is_empty() {
+                let in_title = task.title.to_lowercase().contains(&needle);
+                let in_description = task
+                    .description
+                    .as_ref()
+                    .is_some_and(|d| d.to_lowercase().contains(&needle));
+                if !in_title && !in_description {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Task tree after filtering, keeping the ancestor path of every match.
+///
+/// Ancestors that don't match the filter themselves are kept with
+/// `context_only: true` so the UI can render them de-emphasized.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilteredTaskTree {
+    #[serde(flatten)]
+    pub task: Task,
+    pub context_only: bool,
+    pub subtasks: Vec<FilteredTaskTree>,
+}
+
+// Prune a task tree down to matching nodes and their ancestors
+pub fn filter_task_tree(tree: Vec<TaskTree>, filter: &TaskFilter) -> Vec<FilteredTaskTree> {
+    fn prune(node: TaskTree, filter: &TaskFilter) -> Option<FilteredTaskTree> {
+        let subtasks: Vec<FilteredTaskTree> = node
+            .subtasks
+            .into_iter()
+            .filter_map(|child| prune(child, filter))
+            .collect();
+        let is_match = filter.matches(&node.task);
+
+        if !is_match && subtasks.is_empty() {
+            return None;
+        }
+
+        Some(FilteredTaskTree {
+            task: node.task,
+            context_only: !is_match,
+            subtasks,
+        })
+    }
+
+    tree.into_iter()
+        .filter_map(|node| prune(node, filter))
+        .collect()
+}
+
+// Drop completed tasks, keeping a done task only while it still has open descendants
+pub fn remove_done_tasks(tree: Vec<TaskTree>) -> Vec<TaskTree> {
+    tree.into_iter()
+        .filter_map(|mut node| {
+            node.subtasks = remove_done_tasks(node.subtasks);
+            (!node.task.is_done || !node.subtasks.is_empty()).then_some(node)
+        })
+        .collect()
+}
+
+// Order roots by `sort`, falling back to position order for ties
+pub fn sort_task_tree(tree: Vec<TaskTree>, sort: TreeSort) -> Vec<TaskTree> {
+    fn latest_update(node: &TaskTree) -> i64 {
+        node.subtasks
+            .iter()
+            .map(latest_update)
+            .fold(node.task.updated_at, i64::max)
+    }
+
+    fn earliest_open_due(node: &TaskTree) -> Option<i64> {
+        let own = node.task.due_date.filter(|_| !node.task.is_done);
+        node.subtasks
+            .iter()
+            .filter_map(earliest_open_due)
+            .chain(own)
+            .min()
+    }
+
+    let mut keyed: Vec<(i64, TaskTree)> = tree
+        .into_iter()
+        .map(|node| {
+            let key = match sort {
+                TreeSort::Position => 0,
+                TreeSort::RecentActivity => -latest_update(&node),
+                TreeSort::DueSoonest => earliest_open_due(&node).unwrap_or(i64::MAX),
+            };
+            (key, node)
+        })
+        .collect();
+    keyed.sort_by(|(a_key, a), (b_key, b)| {
+        a_key
+            .cmp(b_key)
+            .then(a.task.position.total_cmp(&b.task.position))
+            .then(a.task.id.cmp(&b.task.id))
+    });
+    keyed.into_iter().map(|(_, node)| node).collect()
+}
+
 // Helper function to build recursive task tree
 pub fn build_task_tree(tasks: Vec<Task>) -> Vec<TaskTree> {
     // Build map of task_id -> TaskTree nodes
@@ -118,17 +553,67 @@ mod tests {
             title: title.to_string(),
             description: None,
             category_id: None,
-            priority: "Medium".to_string(),
+            priority: Priority::Medium,
             parent_id,
             is_done: false,
-            position: 0,
+            position: 0.0,
             due_date: None,
             created_at: 0,
             updated_at: 0,
             completed_at: None,
+            external_id: None,
+            effort: None,
+            series_id: None,
+            is_locked: false,
+            path: match parent_id {
+                Some(parent_id) => format!("/{}/{}/", parent_id, id),
+                None => format!("/{}/", id),
+            },
+            reminder_offset_minutes: None,
+            color: None,
+        }
+    }
+
+    fn work_category() -> Category {
+        Category {
+            id: 1,
+            name: "Work".to_string(),
+            color: "#e0af68".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            archived_at: None,
         }
     }
 
+    #[test]
+    fn test_resolved_color_inherits_category_color() {
+        let task = TaskWithCategory::new(create_test_task(1, "Task", None), Some(work_category()));
+        assert_eq!(task.resolved_color, "#e0af68");
+
+        let json = serde_json::to_value(&task).unwrap();
+        assert_eq!(json["resolved_color"], "#e0af68");
+    }
+
+    #[test]
+    fn test_resolved_color_defaults_without_category() {
+        let task = TaskWithCategory::new(create_test_task(1, "Task", None), None);
+        assert_eq!(task.resolved_color, DEFAULT_TASK_COLOR);
+    }
+
+    #[test]
+    fn test_resolved_color_prefers_task_color() {
+        let task = Task {
+            color: Some("#7aa2f7".to_string()),
+            ..create_test_task(1, "Task", None)
+        };
+
+        let with_category = TaskWithCategory::new(task.clone(), Some(work_category()));
+        assert_eq!(with_category.resolved_color, "#7aa2f7");
+
+        let without_category = TaskWithCategory::new(task, None);
+        assert_eq!(without_category.resolved_color, "#7aa2f7");
+    }
+
     #[test]
     fn test_build_task_tree_empty() {
         let tasks = vec![];
@@ -252,6 +737,176 @@ mod tests {
         assert_eq!(root2.subtasks[0].task.id, 6);
     }
 
+    #[test]
+    fn test_filter_task_tree_keeps_ancestors_of_match() {
+        let tasks = vec![
+            create_test_task(1, "Grandparent", None),
+            create_test_task(2, "Parent", Some(1)),
+            create_test_task(3, "Buy milk", Some(2)),
+            create_test_task(4, "Unrelated sibling", Some(1)),
+            create_test_task(5, "Other root", None),
+        ];
+        let filter = TaskFilter {
+            search: Some("MILK".to_string()),
+            ..Default::default()
+        };
+        let filtered = filter_task_tree(build_task_tree(tasks), &filter);
+
+        assert_eq!(filtered.len(), 1, "Only the matching branch should remain");
+        let grandparent = &filtered[0];
+        assert_eq!(grandparent.task.id, 1);
+        assert!(grandparent.context_only);
+        assert_eq!(grandparent.subtasks.len(), 1, "Unrelated sibling is pruned");
+
+        let parent = &grandparent.subtasks[0];
+        assert_eq!(parent.task.id, 2);
+        assert!(parent.context_only);
+
+        let grandchild = &parent.subtasks[0];
+        assert_eq!(grandchild.task.id, 3);
+        assert!(!grandchild.context_only);
+        assert_eq!(grandchild.subtasks.len(), 0);
+    }
+
+    #[test]
+    fn test_filter_task_tree_no_matches() {
+        let tasks = vec![
+            create_test_task(1, "Root", None),
+            create_test_task(2, "Child", Some(1)),
+        ];
+        let filter = TaskFilter {
+            priority: Some("Urgent".to_string()),
+            ..Default::default()
+        };
+        let filtered = filter_task_tree(build_task_tree(tasks), &filter);
+
+        assert_eq!(filtered.len(), 0);
+    }
+
+    #[test]
+    fn test_task_filter_combines_fields() {
+        let mut task = create_test_task(1, "Write report", None);
+        task.description = Some("Quarterly numbers".to_string());
+        task.is_done = true;
+
+        assert!(TaskFilter::default().matches(&task));
+        assert!(TaskFilter {
+            search: Some("quarterly".to_string()),
+            is_done: Some(true),
+            ..Default::default()
+        }
+        .matches(&task));
+        assert!(!TaskFilter {
+            search: Some("report".to_string()),
+            is_done: Some(false),
+            ..Default::default()
+        }
+        .matches(&task));
+    }
+
+    #[test]
+    fn test_task_filter_due_before() {
+        let mut task = create_test_task(1, "Renew passport", None);
+        let filter = TaskFilter {
+            due_before: Some(100),
+            ..Default::default()
+        };
+        assert!(!filter.is_empty());
+        assert!(!filter.matches(&task), "Undated tasks are never due before");
+
+        task.due_date = Some(99);
+        assert!(filter.matches(&task));
+        task.due_date = Some(100);
+        assert!(!filter.matches(&task));
+
+        assert!(TaskFilter {
+            search: Some("  ".to_string()),
+            ..Default::default()
+        }
+        .is_empty());
+    }
+
+    #[test]
+    fn test_task_dto_serializes_timestamps_as_rfc3339() {
+        let mut task = create_test_task(1, "Task", None);
+        task.updated_at = 86_400;
+        task.due_date = Some(90_061);
+
+        let json = serde_json::to_value(TaskDto::from(task)).unwrap();
+        assert_eq!(json["created_at"], "1970-01-01T00:00:00Z");
+        assert_eq!(json["updated_at"], "1970-01-02T00:00:00Z");
+        assert_eq!(json["due_date"], "1970-01-02T01:01:01Z");
+        assert!(json["completed_at"].is_null(), "Null timestamps stay null");
+        assert_eq!(json["title"], "Task");
+    }
+
+    #[test]
+    fn test_remove_done_tasks_hides_done_leaf() {
+        let mut done_leaf = create_test_task(2, "Done leaf", Some(1));
+        done_leaf.is_done = true;
+        let tasks = vec![
+            create_test_task(1, "Root", None),
+            done_leaf,
+            create_test_task(3, "Open leaf", Some(1)),
+        ];
+        let tree = remove_done_tasks(build_task_tree(tasks));
+
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].subtasks.len(), 1);
+        assert_eq!(tree[0].subtasks[0].task.id, 3);
+    }
+
+    #[test]
+    fn test_remove_done_tasks_keeps_done_parent_with_open_child() {
+        let mut done_parent = create_test_task(1, "Done parent", None);
+        done_parent.is_done = true;
+        let mut done_root = create_test_task(3, "Done root", None);
+        done_root.is_done = true;
+        let tasks = vec![
+            done_parent,
+            create_test_task(2, "Open child", Some(1)),
+            done_root,
+        ];
+        let tree = remove_done_tasks(build_task_tree(tasks));
+
+        assert_eq!(tree.len(), 1, "Done root without open children is hidden");
+        assert_eq!(tree[0].task.id, 1);
+        assert_eq!(tree[0].subtasks[0].task.id, 2);
+    }
+
+    #[test]
+    fn test_update_input_distinguishes_null_from_absent() {
+        let input: UpdateTaskInput =
+            serde_json::from_str(r#"{"due_date": null, "description": "notes"}"#).unwrap();
+        assert_eq!(input.due_date, Some(None), "Explicit null clears");
+        assert_eq!(input.description, Some(Some("notes".to_string())));
+        assert_eq!(input.category_id, None, "Absent leaves unchanged");
+        assert_eq!(input.parent_id, None);
+        assert_eq!(input.effort, None);
+    }
+
+    #[test]
+    fn test_priority_round_trips_between_name_and_rank() {
+        for (rank, name) in PRIORITIES.iter().enumerate() {
+            let priority: Priority = name.parse().unwrap();
+            assert_eq!(priority as usize, rank);
+            assert_eq!(priority.as_str(), *name);
+            assert_eq!(
+                serde_json::to_string(&priority).unwrap(),
+                format!("\"{}\"", name)
+            );
+            assert_eq!(
+                serde_json::from_str::<Priority>(&format!("\"{}\"", name)).unwrap(),
+                priority
+            );
+        }
+        assert!(
+            "high".parse::<Priority>().is_err(),
+            "Names are case-sensitive"
+        );
+        assert!(Priority::Urgent < Priority::High && Priority::Medium < Priority::Low);
+    }
+
     #[test]
     fn test_build_task_tree_orphaned_child() {
         // Child with non-existent parent should be treated as root
@@ -0,0 +1,38 @@
+use crate::models::{Priority, TaskTree};
+use serde::{Deserialize, Serialize};
+
+/// One task of a template: only what carries over to every copy, so no ids, dates or
+/// completion state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateNode {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Priority,
+    pub effort: Option<i32>,
+    pub children: Vec<TemplateNode>,
+}
+
+impl TemplateNode {
+    pub fn from_tree(tree: &TaskTree) -> Self {
+        // Subtasks don't come out of build_task_tree in position order
+        let mut subtasks: Vec<&TaskTree> = tree.subtasks.iter().collect();
+        subtasks.sort_by(|a, b| a.task.position.total_cmp(&b.task.position));
+        Self {
+            title: tree.task.title.clone(),
+            description: tree.task.description.clone(),
+            priority: tree.task.priority,
+            effort: tree.task.effort,
+            children: subtasks.into_iter().map(Self::from_tree).collect(),
+        }
+    }
+}
+
+/// A saved task structure, e.g. an onboarding checklist, that `instantiate_template`
+/// copies into fresh tasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskTemplate {
+    pub id: i64,
+    pub name: String,
+    pub root: TemplateNode,
+    pub created_at: i64,
+}
@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+/// A command's result along with how long the backend took to produce it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Timed<T> {
+    pub data: T,
+    pub elapsed_ms: u64,
+}
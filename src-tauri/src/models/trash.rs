@@ -0,0 +1,13 @@
+use serde::Serialize;
+
+/// A deleted task, held with its descendants until restored or emptied.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TrashEntry {
+    pub id: i64,
+    /// Id of the deleted task, which it gets back when restored
+    pub task_id: i64,
+    pub title: String,
+    /// The task plus its descendants
+    pub task_count: i64,
+    pub trashed_at: i64,
+}
@@ -2,6 +2,12 @@
 
 export type Priority = "Urgent" | "High" | "Medium" | "Low"
 
+// What deleting a task does to its subtasks
+export type DeleteStrategy = "Cascade" | "Reparent" | "Block"
+
+// Order for the roots of a task tree
+export type TreeSort = "Position" | "RecentActivity" | "DueSoonest"
+
 export interface Task {
   id: number
   title: string
@@ -15,6 +21,13 @@ export interface Task {
   created_at: number
   updated_at: number
   completed_at: number | null
+  external_id: string | null
+  effort: number | null
+  series_id: number | null
+  is_locked: boolean
+  path: string
+  reminder_offset_minutes: number | null
+  color: string | null
 }
 
 export interface TaskTree {
@@ -30,6 +43,13 @@ export interface TaskTree {
   created_at: number
   updated_at: number
   completed_at: number | null
+  external_id: string | null
+  effort: number | null
+  series_id: number | null
+  is_locked: boolean
+  path: string
+  reminder_offset_minutes: number | null
+  color: string | null
   subtasks: TaskTree[]
 }
 
@@ -37,11 +57,15 @@ export interface CreateTaskInput {
   title: string
   description?: string | null
   category_id?: number | null
-  priority: Priority
+  priority?: Priority
   parent_id?: number | null
   due_date?: number | null
+  effort?: number | null
+  reminder_offset_minutes?: number | null
+  color?: string | null
 }
 
+// Omitted fields are left unchanged; an explicit null clears a nullable field
 export interface UpdateTaskInput {
   title?: string
   description?: string | null
@@ -51,6 +75,18 @@ export interface UpdateTaskInput {
   is_done?: boolean
   position?: number
   due_date?: number | null
+  effort?: number | null
+  reminder_offset_minutes?: number | null
+  color?: string | null
+}
+
+// Unset fields match everything
+export interface TaskFilter {
+  search?: string
+  priority?: Priority
+  is_done?: boolean
+  // Only tasks due earlier than this, e.g. now for overdue tasks
+  due_before?: number
 }
 
 export interface Category {
@@ -59,6 +95,7 @@ export interface Category {
   color: string
   created_at: number
   updated_at: number
+  archived_at: number | null
 }
 
 export interface CreateCategoryInput {
@@ -70,3 +107,87 @@ export interface UpdateCategoryInput {
   name?: string
   color?: string
 }
+
+export interface PagedCategories {
+  items: Category[]
+  total: number
+}
+
+export interface CategoryUsage {
+  total_tasks: number
+  open_tasks: number
+  done_tasks: number
+  // The category's tasks plus all their subtasks, including any filed elsewhere
+  subtree_tasks: number
+}
+
+export interface TaskPage {
+  items: Task[]
+  next_cursor: number | null
+}
+
+export interface Timed<T> {
+  data: T
+  elapsed_ms: number
+}
+
+export interface CappedTasks {
+  items: Task[]
+  total: number
+}
+
+// A task without its subtasks, marked with whether it has any
+export interface TaskNode extends Task {
+  has_children: boolean
+  // Number of direct subtasks
+  child_count: number
+}
+
+export interface TaskWithCategory extends Task {
+  category: Category | null
+  // The task's own color, else its category's, else the default foreground
+  resolved_color: string
+}
+
+export interface CompletedWithFollowup {
+  completed: Task
+  followup: Task
+}
+
+export interface ExportBundle {
+  version: number
+  exported_at: number
+  categories: Category[]
+  // Parents come before their children
+  tasks: Task[]
+}
+
+export interface ImportSummary {
+  categories_created: number
+  tasks_created: number
+}
+
+export interface TemplateNode {
+  title: string
+  description: string | null
+  priority: Priority
+  effort: number | null
+  children: TemplateNode[]
+}
+
+export interface TaskTemplate {
+  id: number
+  name: string
+  root: TemplateNode
+  created_at: number
+}
+
+export interface TrashEntry {
+  id: number
+  // Id of the deleted task, which it gets back when restored
+  task_id: number
+  title: string
+  // The task plus its descendants
+  task_count: number
+  trashed_at: number
+}